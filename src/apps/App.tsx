@@ -1,7 +1,12 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import WallpaperManager from "./components/WallpaperManager";
 import DateWidget from "./components/DateWidget";
+import ScheduleEditor from "./components/ScheduleEditor";
+import SolarEditor from "./components/SolarEditor";
+import DynamicSetEditor from "./components/DynamicSetEditor";
+import MonitorSlideshowEditor from "./components/MonitorSlideshowEditor";
 import {
   WallpaperSettings,
   DateWidgetSettings,
@@ -9,12 +14,12 @@ import {
 } from "./types/wallpaper";
 import "./index.css";
 import { BsCalendar2Date } from "react-icons/bs";
-import { LuWallpaper } from "react-icons/lu";
+import { LuClock, LuWallpaper } from "react-icons/lu";
 
 function App() {
-  const [activeTab, setActiveTab] = useState<"wallpaper" | "datewidget">(
-    "wallpaper"
-  );
+  const [activeTab, setActiveTab] = useState<
+    "wallpaper" | "schedule" | "datewidget"
+  >("wallpaper");
   const [autostartEnabled, setAutostartEnabled] = useState(false);
   const [loading, setLoading] = useState(true);
   const [wallpaperSettings, setWallpaperSettings] = useState<WallpaperSettings>(
@@ -23,6 +28,7 @@ function App() {
       interval: 30,
       randomOrder: false,
       pauseOnFullscreen: true,
+      collapseDuplicates: false,
     }
   );
 
@@ -71,6 +77,18 @@ function App() {
     loadAppState();
   }, []);
 
+  // The slideshow can be started and stopped from outside this window
+  useEffect(() => {
+    const unlisten = listen<WallpaperSettings>(
+      "wallpaper-settings-changed",
+      (event) => setWallpaperSettings(event.payload)
+    );
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Save wallpaper settings when they change
   useEffect(() => {
     if (!loading) {
@@ -152,6 +170,12 @@ function App() {
           <LuWallpaper className="tab-icon" />
           Wallpaper Manager
         </button>
+        <button
+          className={`tab-button ${activeTab === "schedule" ? "active" : ""}`}
+          onClick={() => setActiveTab("schedule")}>
+          <LuClock className="tab-icon" />
+          Schedule
+        </button>
         <button
           className={`tab-button ${activeTab === "datewidget" ? "active" : ""}`}
           onClick={() => setActiveTab("datewidget")}>
@@ -167,6 +191,14 @@ function App() {
             onSettingsChange={setWallpaperSettings}
           />
         )}
+        {activeTab === "schedule" && (
+          <>
+            <ScheduleEditor />
+            <SolarEditor />
+            <DynamicSetEditor />
+            <MonitorSlideshowEditor />
+          </>
+        )}
         {activeTab === "datewidget" && (
           <DateWidget
             settings={dateWidgetSettings}
@@ -0,0 +1,331 @@
+import React, { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { open, save } from "@tauri-apps/plugin-dialog";
+import { FiPlus, FiTrash, FiX } from "react-icons/fi";
+import {
+  AppPersistentState,
+  DynamicFrame,
+  DynamicSet,
+  GnomeImport,
+} from "../types/wallpaper";
+
+const DAY_SECONDS = 24 * 60 * 60;
+
+// "HH:MM:SS" for a keyframe in a day-long cycle
+const toClock = (seconds: number) => {
+  const pad = (value: number) => String(value).padStart(2, "0");
+  return `${pad(Math.floor(seconds / 3600))}:${pad(
+    Math.floor((seconds % 3600) / 60)
+  )}:${pad(seconds % 60)}`;
+};
+
+const fromClock = (text: string) => {
+  const [hours = 0, minutes = 0, seconds = 0] = text.split(":").map(Number);
+  return hours * 3600 + minutes * 60 + seconds;
+};
+
+const fileName = (path: string) => path.split(/[\\/]/).pop();
+
+const DynamicSetEditor: React.FC = () => {
+  const [sets, setSets] = useState<DynamicSet[]>([]);
+  const [dirtyIds, setDirtyIds] = useState<number[]>([]);
+  const [activeId, setActiveId] = useState<number | null>(null);
+  const [name, setName] = useState("");
+  const [gnomeFiles, setGnomeFiles] = useState<string[]>([]);
+  const [gnomeFile, setGnomeFile] = useState("");
+
+  const loadSets = useCallback(async () => {
+    try {
+      setSets(await invoke<DynamicSet[]>("get_dynamic_sets"));
+      setDirtyIds([]);
+    } catch (error) {
+      console.error("Error loading dynamic sets:", error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadSets();
+    invoke<AppPersistentState>("load_app_state")
+      .then((state) => setActiveId(state.active_dynamic_set ?? null))
+      .catch((error) => console.error("Error loading app state:", error));
+    invoke<string[]>("find_gnome_backgrounds")
+      .then(setGnomeFiles)
+      .catch((error) => console.error("Error looking for GNOME backgrounds:", error));
+  }, [loadSets]);
+
+  const updateSet = (id: number, changes: Partial<DynamicSet>) => {
+    setSets((prev) =>
+      prev.map((set) => (set.id === id ? { ...set, ...changes } : set))
+    );
+    setDirtyIds((prev) => (prev.includes(id) ? prev : [...prev, id]));
+  };
+
+  const updateFrame = (
+    set: DynamicSet,
+    index: number,
+    changes: Partial<DynamicFrame>
+  ) => {
+    updateSet(set.id, {
+      frames: set.frames.map((frame, i) =>
+        i === index ? { ...frame, ...changes } : frame
+      ),
+    });
+  };
+
+  const handleAddFrames = async (set: DynamicSet) => {
+    const paths = await open({ multiple: true, title: "Select Wallpapers" });
+    if (!paths) {
+      return;
+    }
+    const added = (Array.isArray(paths) ? paths : [paths]).map((path, i) => {
+      // New frames go after the last keyframe, an hour apart
+      const last = set.frames[set.frames.length - 1]?.at ?? -3600;
+      return { path, at: (last + 3600 * (i + 1)) % set.period, transition: 0 };
+    });
+    updateSet(set.id, { frames: [...set.frames, ...added] });
+  };
+
+  const handleSave = async (set: DynamicSet) => {
+    try {
+      const saved = await invoke<DynamicSet>("update_dynamic_set", { set });
+      setSets((prev) => prev.map((entry) => (entry.id === set.id ? saved : entry)));
+      setDirtyIds((prev) => prev.filter((id) => id !== set.id));
+    } catch (error) {
+      alert(`Could not save the dynamic set: ${error}`);
+    }
+  };
+
+  const handleCreate = async () => {
+    try {
+      await invoke<DynamicSet>("create_dynamic_set", {
+        set: { id: 0, name, period: DAY_SECONDS, frames: [] },
+      });
+      setName("");
+      await loadSets();
+    } catch (error) {
+      alert(`Could not create the dynamic set: ${error}`);
+    }
+  };
+
+  const handleActivate = async (id: number | null) => {
+    try {
+      await invoke("set_active_dynamic_set", { id });
+      setActiveId(id);
+    } catch (error) {
+      alert(`Could not switch dynamic sets: ${error}`);
+    }
+  };
+
+  const handleImportGnome = async () => {
+    try {
+      const result = await invoke<GnomeImport>("import_gnome_backgrounds", {
+        path: gnomeFile,
+      });
+      if (result.dynamic_sets.length === 0) {
+        alert(`${fileName(gnomeFile)} has no slideshows; its pictures were added to the library`);
+      }
+      setGnomeFile("");
+      await loadSets();
+    } catch (error) {
+      alert(`Could not import the GNOME background: ${error}`);
+    }
+  };
+
+  const handleExportGnome = async (set: DynamicSet) => {
+    const path = await save({
+      defaultPath: `${set.name}.xml`,
+      filters: [{ name: "GNOME slideshow", extensions: ["xml"] }],
+    });
+    if (!path) {
+      return;
+    }
+    try {
+      await invoke("export_dynamic_set_gnome", {
+        id: set.id,
+        path,
+        register: confirm("Also list this slideshow in GNOME Settings?"),
+      });
+    } catch (error) {
+      alert(`Could not export the dynamic set: ${error}`);
+    }
+  };
+
+  const handleDelete = async (set: DynamicSet) => {
+    if (!confirm(`Delete the dynamic set "${set.name}"? The pictures stay in the library.`)) {
+      return;
+    }
+    try {
+      await invoke("delete_dynamic_set", { id: set.id });
+      if (activeId === set.id) {
+        setActiveId(null);
+      }
+      await loadSets();
+    } catch (error) {
+      console.error("Error deleting dynamic set:", error);
+    }
+  };
+
+  return (
+    <div className="section">
+      <div className="control-row">
+        <span className="control-label">
+          Dynamic sets show each picture from its keyframe and fade into the next
+        </span>
+      </div>
+
+      {sets.map((set) => {
+        const daily = set.period === DAY_SECONDS;
+        return (
+          <div
+            key={set.id}
+            className={`solar-set ${activeId === set.id ? "active" : ""}`}>
+            <div className="control-row">
+              <input
+                type="text"
+                className="tag-filter-input"
+                value={set.name}
+                onChange={(e) => updateSet(set.id, { name: e.target.value })}
+              />
+              <label className="toggle-switch" title="Show this set">
+                <input
+                  type="checkbox"
+                  checked={activeId === set.id}
+                  disabled={dirtyIds.includes(set.id)}
+                  onChange={(e) => handleActivate(e.target.checked ? set.id : null)}
+                />
+                <span className="toggle-slider"></span>
+              </label>
+              <button
+                className="icon-btn delete-btn"
+                title="Delete dynamic set"
+                onClick={() => handleDelete(set)}>
+                <FiTrash />
+              </button>
+            </div>
+
+            <div className="control-row">
+              <span className="control-label">Cycle (hours)</span>
+              <input
+                type="number"
+                className="position-input"
+                min={0.01}
+                step="any"
+                value={set.period / 3600}
+                onChange={(e) =>
+                  updateSet(set.id, {
+                    period: Math.round(parseFloat(e.target.value) * 3600) || DAY_SECONDS,
+                  })
+                }
+              />
+            </div>
+
+            {set.frames.map((frame, index) => (
+              <div key={index} className="control-row">
+                {daily ? (
+                  <input
+                    type="time"
+                    step={1}
+                    value={toClock(frame.at)}
+                    onChange={(e) =>
+                      updateFrame(set, index, { at: fromClock(e.target.value) })
+                    }
+                  />
+                ) : (
+                  <input
+                    type="number"
+                    className="position-input"
+                    title="Minutes into the cycle"
+                    min={0}
+                    value={Math.round(frame.at / 60)}
+                    onChange={(e) =>
+                      updateFrame(set, index, { at: parseInt(e.target.value) * 60 || 0 })
+                    }
+                  />
+                )}
+                <input
+                  type="number"
+                  className="position-input"
+                  title="Fade in, in seconds"
+                  min={0}
+                  value={frame.transition}
+                  onChange={(e) =>
+                    updateFrame(set, index, {
+                      transition: parseInt(e.target.value) || 0,
+                    })
+                  }
+                />
+                <span className="control-label">{fileName(frame.path)}</span>
+                <button
+                  className="icon-btn delete-btn"
+                  title="Remove frame"
+                  onClick={() =>
+                    updateSet(set.id, {
+                      frames: set.frames.filter((_, i) => i !== index),
+                    })
+                  }>
+                  <FiX />
+                </button>
+              </div>
+            ))}
+
+            <div className="control-row">
+              <button className="btn" onClick={() => handleAddFrames(set)}>
+                Add pictures
+              </button>
+              <button
+                className="btn"
+                disabled={!dirtyIds.includes(set.id)}
+                onClick={() => handleSave(set)}>
+                Save
+              </button>
+              <button
+                className="btn"
+                disabled={dirtyIds.includes(set.id) || set.frames.length === 0}
+                onClick={() => handleExportGnome(set)}>
+                Export for GNOME
+              </button>
+            </div>
+          </div>
+        );
+      })}
+
+      <div className="control-row">
+        <input
+          type="text"
+          className="tag-filter-input"
+          placeholder="Name"
+          value={name}
+          onChange={(e) => setName(e.target.value)}
+        />
+        <button
+          className="icon-btn folder-btn"
+          title="Add dynamic set"
+          disabled={!name.trim()}
+          onClick={handleCreate}>
+          <FiPlus />
+        </button>
+      </div>
+
+      {gnomeFiles.length > 0 && (
+        <div className="control-row">
+          <select
+            className="sort-select"
+            value={gnomeFile}
+            onChange={(e) => setGnomeFile(e.target.value)}>
+            <option value="">GNOME background…</option>
+            {gnomeFiles.map((file) => (
+              <option key={file} value={file}>
+                {file}
+              </option>
+            ))}
+          </select>
+          <button className="btn" disabled={!gnomeFile} onClick={handleImportGnome}>
+            Import
+          </button>
+        </div>
+      )}
+    </div>
+  );
+};
+
+export default DynamicSetEditor;
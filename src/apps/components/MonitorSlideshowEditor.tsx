@@ -0,0 +1,230 @@
+import React, { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { FiChevronRight } from "react-icons/fi";
+import {
+  AppPersistentState,
+  Collection,
+  MonitorInfo,
+  MonitorSlideshow,
+  MonitorSlideshows,
+  WallpaperSettings,
+} from "../types/wallpaper";
+
+// Same defaults as the backend
+const DEFAULT_SETTINGS: WallpaperSettings = {
+  autoChange: false,
+  interval: 30,
+  randomOrder: false,
+  pauseOnFullscreen: true,
+};
+
+const fileName = (path: string) => path.split(/[\\/]/).pop();
+
+const MonitorSlideshowEditor: React.FC = () => {
+  const [monitors, setMonitors] = useState<MonitorInfo[]>([]);
+  const [config, setConfig] = useState<MonitorSlideshows>({
+    independent: false,
+    monitors: [],
+  });
+  const [collections, setCollections] = useState<Collection[]>([]);
+  // What a screen starts from before it has a slideshow of its own
+  const [shared, setShared] = useState<Omit<MonitorSlideshow, "monitor">>({
+    settings: DEFAULT_SETTINGS,
+  });
+
+  const loadMonitors = useCallback(async () => {
+    try {
+      setMonitors(await invoke<MonitorInfo[]>("get_monitors"));
+      setConfig(await invoke<MonitorSlideshows>("get_monitor_slideshows"));
+    } catch (error) {
+      console.error("Error loading monitors:", error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadMonitors();
+    invoke<Collection[]>("get_collections")
+      .then(setCollections)
+      .catch((error) => console.error("Error loading collections:", error));
+    invoke<AppPersistentState>("load_app_state")
+      .then((state) =>
+        setShared({
+          collection_id: state.active_collection ?? null,
+          settings: state.wallpaper_settings ?? DEFAULT_SETTINGS,
+        })
+      )
+      .catch((error) => console.error("Error loading app state:", error));
+
+    const unlisten = listen<MonitorInfo[]>("monitors-changed", (event) => {
+      setMonitors(event.payload);
+      invoke<MonitorSlideshows>("get_monitor_slideshows")
+        .then(setConfig)
+        .catch((error) => console.error("Error loading monitor slideshows:", error));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadMonitors]);
+
+  const slideshowFor = (name: string): MonitorSlideshow =>
+    config.monitors.find((slideshow) => slideshow.monitor === name) ?? {
+      monitor: name,
+      ...shared,
+    };
+
+  const handleIndependent = async (independent: boolean) => {
+    try {
+      setConfig(await invoke<MonitorSlideshows>("set_monitors_independent", { independent }));
+    } catch (error) {
+      alert(`Could not change how screens share the slideshow: ${error}`);
+    }
+  };
+
+  const handleUpdate = async (
+    slideshow: MonitorSlideshow,
+    changes: { collectionId?: number | null; settings?: Partial<WallpaperSettings> }
+  ) => {
+    try {
+      const updated = await invoke<MonitorSlideshows>("update_monitor_slideshow", {
+        monitor: slideshow.monitor,
+        collectionId:
+          changes.collectionId !== undefined
+            ? changes.collectionId
+            : slideshow.collection_id ?? null,
+        settings: { ...slideshow.settings, ...changes.settings },
+      });
+      setConfig(updated);
+    } catch (error) {
+      alert(`Could not save the screen's slideshow: ${error}`);
+    }
+  };
+
+  const handleNext = async (name: string) => {
+    try {
+      setMonitors(await invoke<MonitorInfo[]>("next_monitor_wallpaper", { monitor: name }));
+    } catch (error) {
+      alert(`Could not change the wallpaper: ${error}`);
+    }
+  };
+
+  if (monitors.length < 2 && !config.independent) {
+    return null;
+  }
+
+  return (
+    <div className="section">
+      <div className="control-row">
+        <span className="control-label">Separate slideshow on each screen</span>
+        <label className="toggle-switch">
+          <input
+            type="checkbox"
+            checked={config.independent}
+            onChange={(e) => handleIndependent(e.target.checked)}
+          />
+          <span className="toggle-slider"></span>
+        </label>
+      </div>
+
+      {config.independent &&
+        monitors.map((monitor) => {
+          const slideshow = slideshowFor(monitor.name);
+          const { settings } = slideshow;
+          return (
+            <div key={monitor.name} className="solar-set">
+              <div className="control-row">
+                <span className="control-label">
+                  {monitor.name} · {monitor.width}×{monitor.height}
+                  {monitor.primary ? " · primary" : ""}
+                </span>
+                <button
+                  className="icon-btn"
+                  title="Next wallpaper on this screen"
+                  onClick={() => handleNext(monitor.name)}>
+                  <FiChevronRight />
+                </button>
+              </div>
+
+              <div className="control-row">
+                <span className="control-label">Show</span>
+                <select
+                  className="sort-select"
+                  value={slideshow.collection_id ?? ""}
+                  onChange={(e) =>
+                    handleUpdate(slideshow, {
+                      collectionId: e.target.value ? parseInt(e.target.value) : null,
+                    })
+                  }>
+                  <option value="">Whole library</option>
+                  {collections.map((collection) => (
+                    <option key={collection.id} value={collection.id}>
+                      {collection.name}
+                    </option>
+                  ))}
+                </select>
+              </div>
+
+              <div className="control-row">
+                <span className="control-label">Change automatically</span>
+                <label className="toggle-switch">
+                  <input
+                    type="checkbox"
+                    checked={settings.autoChange}
+                    onChange={(e) =>
+                      handleUpdate(slideshow, { settings: { autoChange: e.target.checked } })
+                    }
+                  />
+                  <span className="toggle-slider"></span>
+                </label>
+              </div>
+
+              <div className="control-row">
+                <span className="control-label">Every (minutes)</span>
+                <input
+                  key={settings.interval}
+                  type="number"
+                  className="position-input"
+                  min={1}
+                  defaultValue={Math.max(1, Math.round(settings.interval / 60))}
+                  onBlur={(e) => {
+                    const interval = (parseInt(e.target.value) || 1) * 60;
+                    if (interval !== settings.interval) {
+                      handleUpdate(slideshow, { settings: { interval } });
+                    }
+                  }}
+                />
+              </div>
+
+              <div className="control-row">
+                <span className="control-label">Shuffle order</span>
+                <label className="toggle-switch">
+                  <input
+                    type="checkbox"
+                    checked={settings.randomOrder}
+                    onChange={(e) =>
+                      handleUpdate(slideshow, { settings: { randomOrder: e.target.checked } })
+                    }
+                  />
+                  <span className="toggle-slider"></span>
+                </label>
+              </div>
+
+              {(monitor.current || monitor.next_change_at) && (
+                <div className="schedule-preview">
+                  {monitor.current && <span>Showing {fileName(monitor.current)}</span>}
+                  {monitor.next_change_at && (
+                    <span>
+                      Next change{" "}
+                      {new Date(monitor.next_change_at * 1000).toLocaleTimeString()}
+                    </span>
+                  )}
+                </div>
+              )}
+            </div>
+          );
+        })}
+    </div>
+  );
+};
+
+export default MonitorSlideshowEditor;
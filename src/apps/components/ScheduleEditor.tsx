@@ -0,0 +1,282 @@
+import React, { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { open } from "@tauri-apps/plugin-dialog";
+import { FiPlus, FiTrash } from "react-icons/fi";
+import {
+  Collection,
+  SchedulePreview,
+  ScheduleRule,
+  ScheduleTarget,
+  ScheduleTime,
+} from "../types/wallpaper";
+
+const DAY_NAMES = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+const defaultRule = (): ScheduleRule => ({
+  name: "Every hour",
+  enabled: true,
+  when: { kind: "cron", expression: "0 * * * *" },
+  target: { kind: "next" },
+});
+
+// datetime-local wants "YYYY-MM-DDTHH:MM" in local time
+const toLocalInput = (date: Date) => {
+  const pad = (value: number) => String(value).padStart(2, "0");
+  return `${date.getFullYear()}-${pad(date.getMonth() + 1)}-${pad(
+    date.getDate()
+  )}T${pad(date.getHours())}:${pad(date.getMinutes())}`;
+};
+
+const ScheduleEditor: React.FC = () => {
+  const [rules, setRules] = useState<ScheduleRule[]>([]);
+  const [collections, setCollections] = useState<Collection[]>([]);
+  const [dirty, setDirty] = useState(false);
+  const [previewAt, setPreviewAt] = useState(toLocalInput(new Date()));
+  const [preview, setPreview] = useState<SchedulePreview | null>(null);
+
+  useEffect(() => {
+    invoke<ScheduleRule[]>("get_schedule")
+      .then(setRules)
+      .catch((error) => console.error("Error loading schedule:", error));
+    invoke<Collection[]>("get_collections")
+      .then(setCollections)
+      .catch((error) => console.error("Error loading collections:", error));
+  }, []);
+
+  const updateRule = (index: number, changes: Partial<ScheduleRule>) => {
+    setRules((prev) =>
+      prev.map((rule, i) => (i === index ? { ...rule, ...changes } : rule))
+    );
+    setDirty(true);
+  };
+
+  const handleKindChange = (index: number, kind: ScheduleTime["kind"]) => {
+    const when: ScheduleTime =
+      kind === "range"
+        ? { kind: "range", start: "09:00", end: "18:00", days: [] }
+        : { kind: "cron", expression: "0 * * * *" };
+    updateRule(index, { when });
+  };
+
+  const handleTargetChange = async (index: number, value: string) => {
+    let target: ScheduleTarget;
+    if (value === "next") {
+      target = { kind: "next" };
+    } else if (value === "wallpaper") {
+      const path = await open({ multiple: false, title: "Select Wallpaper" });
+      if (typeof path !== "string") {
+        return;
+      }
+      target = { kind: "wallpaper", path };
+    } else {
+      target = { kind: "collection", id: parseInt(value) };
+    }
+    updateRule(index, { target });
+  };
+
+  const toggleDay = (index: number, when: ScheduleTime, day: number) => {
+    if (when.kind !== "range") {
+      return;
+    }
+    const days = when.days.includes(day)
+      ? when.days.filter((d) => d !== day)
+      : [...when.days, day].sort();
+    updateRule(index, { when: { ...when, days } });
+  };
+
+  const handleSave = async () => {
+    try {
+      await invoke("set_schedule", { rules });
+      setDirty(false);
+    } catch (error) {
+      alert(`Could not save the schedule: ${error}`);
+    }
+  };
+
+  const handlePreview = async () => {
+    try {
+      const at = Math.floor(new Date(previewAt).getTime() / 1000);
+      setPreview(await invoke<SchedulePreview>("preview_schedule", { at }));
+    } catch (error) {
+      console.error("Error previewing schedule:", error);
+    }
+  };
+
+  const targetValue = (target: ScheduleTarget) =>
+    target.kind === "collection" ? String(target.id) : target.kind;
+
+  return (
+    <div className="schedule-container">
+      <div className="section">
+        <div className="control-row">
+          <span className="control-label">
+            Rules are checked from the top; the first time range in effect wins
+          </span>
+          <button
+            className="icon-btn folder-btn"
+            title="Add rule"
+            onClick={() => {
+              setRules((prev) => [...prev, defaultRule()]);
+              setDirty(true);
+            }}>
+            <FiPlus />
+          </button>
+        </div>
+
+        {rules.map((rule, index) => (
+          <div key={index} className="schedule-rule">
+            <div className="control-row">
+              <input
+                type="text"
+                className="tag-filter-input"
+                value={rule.name}
+                onChange={(e) => updateRule(index, { name: e.target.value })}
+              />
+              <label className="toggle-switch">
+                <input
+                  type="checkbox"
+                  checked={rule.enabled}
+                  onChange={(e) =>
+                    updateRule(index, { enabled: e.target.checked })
+                  }
+                />
+                <span className="toggle-slider"></span>
+              </label>
+              <button
+                className="icon-btn delete-btn"
+                title="Remove rule"
+                onClick={() => {
+                  setRules((prev) => prev.filter((_, i) => i !== index));
+                  setDirty(true);
+                }}>
+                <FiTrash />
+              </button>
+            </div>
+
+            <div className="control-row">
+              <select
+                className="sort-select"
+                value={rule.when.kind}
+                onChange={(e) =>
+                  handleKindChange(index, e.target.value as ScheduleTime["kind"])
+                }>
+                <option value="range">Between</option>
+                <option value="cron">Cron</option>
+              </select>
+              {rule.when.kind === "range" ? (
+                <>
+                  <input
+                    type="time"
+                    value={rule.when.start}
+                    onChange={(e) =>
+                      rule.when.kind === "range" &&
+                      updateRule(index, {
+                        when: { ...rule.when, start: e.target.value },
+                      })
+                    }
+                  />
+                  <input
+                    type="time"
+                    value={rule.when.end}
+                    onChange={(e) =>
+                      rule.when.kind === "range" &&
+                      updateRule(index, {
+                        when: { ...rule.when, end: e.target.value },
+                      })
+                    }
+                  />
+                </>
+              ) : (
+                <input
+                  type="text"
+                  className="tag-filter-input"
+                  placeholder="minute hour day month weekday"
+                  value={rule.when.expression}
+                  onChange={(e) =>
+                    updateRule(index, {
+                      when: { kind: "cron", expression: e.target.value },
+                    })
+                  }
+                />
+              )}
+            </div>
+
+            {rule.when.kind === "range" && (
+              <div className="schedule-days">
+                {DAY_NAMES.map((name, day) => (
+                  <label key={day}>
+                    <input
+                      type="checkbox"
+                      checked={
+                        rule.when.kind === "range" && rule.when.days.includes(day)
+                      }
+                      onChange={() => toggleDay(index, rule.when, day)}
+                    />
+                    {name}
+                  </label>
+                ))}
+              </div>
+            )}
+
+            <div className="control-row">
+              <span className="control-label">Show</span>
+              <select
+                className="sort-select"
+                value={targetValue(rule.target)}
+                onChange={(e) => handleTargetChange(index, e.target.value)}>
+                <option value="next">Next wallpaper (cron only)</option>
+                <option value="wallpaper">
+                  {rule.target.kind === "wallpaper"
+                    ? rule.target.path.split(/[\\/]/).pop()
+                    : "A wallpaper…"}
+                </option>
+                {collections.map((collection) => (
+                  <option key={collection.id} value={String(collection.id)}>
+                    {collection.name}
+                  </option>
+                ))}
+              </select>
+            </div>
+          </div>
+        ))}
+
+        <button className="btn" disabled={!dirty} onClick={handleSave}>
+          Save schedule
+        </button>
+      </div>
+
+      <div className="section">
+        <div className="control-row">
+          <span className="control-label">Preview</span>
+          <input
+            type="datetime-local"
+            value={previewAt}
+            onChange={(e) => setPreviewAt(e.target.value)}
+          />
+          <button className="btn" onClick={handlePreview}>
+            Check
+          </button>
+        </div>
+        {preview && (
+          <div className="schedule-preview">
+            <span>
+              {preview.rule ? `Rule: ${preview.rule.name}` : "Regular slideshow"}
+            </span>
+            {preview.wallpaper && <span>Shows {preview.wallpaper.name}</span>}
+            {preview.collection && (
+              <span>From collection {preview.collection.name}</span>
+            )}
+            {preview.next_change_at && (
+              <span>
+                Next change{" "}
+                {new Date(preview.next_change_at * 1000).toLocaleString()}
+              </span>
+            )}
+          </div>
+        )}
+      </div>
+    </div>
+  );
+};
+
+export default ScheduleEditor;
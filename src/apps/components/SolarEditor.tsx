@@ -0,0 +1,301 @@
+import React, { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { open } from "@tauri-apps/plugin-dialog";
+import { FiPlus, FiTrash } from "react-icons/fi";
+import {
+  AppPersistentState,
+  City,
+  SolarPhase,
+  SolarSet,
+  SolarStatus,
+  SolarVariants,
+} from "../types/wallpaper";
+
+const PHASES: { phase: SolarPhase; label: string }[] = [
+  { phase: "day", label: "Day" },
+  { phase: "golden_hour", label: "Golden hour" },
+  { phase: "dusk", label: "Dusk" },
+  { phase: "night", label: "Night" },
+];
+
+const phaseLabel = (phase: SolarPhase) =>
+  PHASES.find((entry) => entry.phase === phase)?.label ?? phase;
+
+const fileName = (path: string) => path.split(/[\\/]/).pop();
+
+const SolarEditor: React.FC = () => {
+  const [sets, setSets] = useState<SolarSet[]>([]);
+  const [activeId, setActiveId] = useState<number | null>(null);
+  const [statuses, setStatuses] = useState<Record<number, SolarStatus>>({});
+  const [name, setName] = useState("");
+  const [latitude, setLatitude] = useState("");
+  const [longitude, setLongitude] = useState("");
+  const [location, setLocation] = useState<string | null>(null);
+  const [cityQuery, setCityQuery] = useState("");
+  const [cities, setCities] = useState<City[]>([]);
+
+  const loadSets = useCallback(async () => {
+    try {
+      const loaded = await invoke<SolarSet[]>("get_solar_sets");
+      setSets(loaded);
+      const entries = await Promise.all(
+        loaded.map(
+          async (set) =>
+            [set.id, await invoke<SolarStatus>("get_solar_status", { id: set.id })] as const
+        )
+      );
+      setStatuses(Object.fromEntries(entries));
+    } catch (error) {
+      console.error("Error loading solar sets:", error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadSets();
+    invoke<AppPersistentState>("load_app_state")
+      .then((state) => setActiveId(state.active_solar_set ?? null))
+      .catch((error) => console.error("Error loading app state:", error));
+  }, [loadSets]);
+
+  useEffect(() => {
+    if (!cityQuery.trim()) {
+      setCities([]);
+      return;
+    }
+    invoke<City[]>("search_cities", { query: cityQuery })
+      .then(setCities)
+      .catch((error) => console.error("Error searching cities:", error));
+  }, [cityQuery]);
+
+  const pickCity = (city: City) => {
+    setLatitude(String(city.latitude));
+    setLongitude(String(city.longitude));
+    setLocation(`${city.name}, ${city.country}`);
+    if (!name.trim()) {
+      setName(city.name);
+    }
+    setCityQuery("");
+  };
+
+  const handleCreate = async () => {
+    try {
+      await invoke<SolarSet>("create_solar_set", {
+        set: {
+          id: 0,
+          name,
+          latitude: parseFloat(latitude),
+          longitude: parseFloat(longitude),
+          location,
+          variants: {},
+        },
+      });
+      setName("");
+      setLatitude("");
+      setLongitude("");
+      setLocation(null);
+      await loadSets();
+    } catch (error) {
+      alert(`Could not create the solar set: ${error}`);
+    }
+  };
+
+  const saveSet = async (set: SolarSet) => {
+    try {
+      await invoke("update_solar_set", { set });
+      await loadSets();
+    } catch (error) {
+      alert(`Could not save the solar set: ${error}`);
+    }
+  };
+
+  const pickVariant = async (set: SolarSet, phase: keyof SolarVariants) => {
+    const path = await open({ multiple: false, title: "Select Wallpaper" });
+    if (typeof path !== "string") {
+      return;
+    }
+    await saveSet({ ...set, variants: { ...set.variants, [phase]: path } });
+  };
+
+  const updateCoordinates = (set: SolarSet, changes: Partial<SolarSet>) => {
+    setSets((prev) =>
+      prev.map((entry) => (entry.id === set.id ? { ...entry, ...changes } : entry))
+    );
+  };
+
+  const handleActivate = async (id: number | null) => {
+    try {
+      await invoke("set_active_solar_set", { id });
+      setActiveId(id);
+    } catch (error) {
+      alert(`Could not switch solar sets: ${error}`);
+    }
+  };
+
+  const handleDelete = async (set: SolarSet) => {
+    if (!confirm(`Delete the solar set "${set.name}"? The pictures stay in the library.`)) {
+      return;
+    }
+    try {
+      await invoke("delete_solar_set", { id: set.id });
+      if (activeId === set.id) {
+        setActiveId(null);
+      }
+      await loadSets();
+    } catch (error) {
+      console.error("Error deleting solar set:", error);
+    }
+  };
+
+  return (
+    <div className="section">
+      <div className="control-row">
+        <span className="control-label">
+          Solar sets follow the sun at a place, switching pictures at golden hour, dusk and night
+        </span>
+      </div>
+
+      {sets.map((set) => {
+        const status = statuses[set.id];
+        return (
+          <div
+            key={set.id}
+            className={`solar-set ${activeId === set.id ? "active" : ""}`}>
+            <div className="control-row">
+              <span className="control-label">
+                {set.name}
+                {set.location ? ` · ${set.location}` : ""}
+              </span>
+              <label className="toggle-switch" title="Show this set">
+                <input
+                  type="checkbox"
+                  checked={activeId === set.id}
+                  onChange={(e) => handleActivate(e.target.checked ? set.id : null)}
+                />
+                <span className="toggle-slider"></span>
+              </label>
+              <button
+                className="icon-btn delete-btn"
+                title="Delete solar set"
+                onClick={() => handleDelete(set)}>
+                <FiTrash />
+              </button>
+            </div>
+
+            <div className="control-row">
+              <input
+                type="number"
+                className="position-input"
+                min={-90}
+                max={90}
+                step="any"
+                value={set.latitude}
+                onChange={(e) =>
+                  updateCoordinates(set, { latitude: parseFloat(e.target.value) })
+                }
+                onBlur={() => saveSet(set)}
+              />
+              <input
+                type="number"
+                className="position-input"
+                min={-180}
+                max={180}
+                step="any"
+                value={set.longitude}
+                onChange={(e) =>
+                  updateCoordinates(set, { longitude: parseFloat(e.target.value) })
+                }
+                onBlur={() => saveSet(set)}
+              />
+            </div>
+
+            {PHASES.map(({ phase, label }) => (
+              <div key={phase} className="control-row">
+                <span className="control-label">{label}</span>
+                <button className="btn" onClick={() => pickVariant(set, phase)}>
+                  {set.variants[phase] ? fileName(set.variants[phase]!) : "Choose…"}
+                </button>
+              </div>
+            ))}
+
+            {status && (
+              <div className="schedule-preview">
+                <span>
+                  Now {phaseLabel(status.phase)}, sun at{" "}
+                  {status.position.elevation.toFixed(1)}°
+                </span>
+                {status.next_change_at && status.next_phase && (
+                  <span>
+                    {phaseLabel(status.next_phase)} from{" "}
+                    {new Date(status.next_change_at * 1000).toLocaleTimeString()}
+                  </span>
+                )}
+              </div>
+            )}
+          </div>
+        );
+      })}
+
+      <div className="control-row">
+        <input
+          type="text"
+          className="tag-filter-input"
+          placeholder="Name"
+          value={name}
+          onChange={(e) => setName(e.target.value)}
+        />
+        <input
+          type="text"
+          className="tag-filter-input"
+          placeholder="Find a city"
+          value={cityQuery}
+          onChange={(e) => setCityQuery(e.target.value)}
+        />
+      </div>
+      {cities.length > 0 && (
+        <div className="city-results">
+          {cities.map((city) => (
+            <button
+              key={`${city.name}-${city.country}`}
+              className="btn"
+              onClick={() => pickCity(city)}>
+              {city.name}, {city.country}
+            </button>
+          ))}
+        </div>
+      )}
+      <div className="control-row">
+        <input
+          type="number"
+          className="position-input"
+          placeholder="Latitude"
+          step="any"
+          value={latitude}
+          onChange={(e) => {
+            setLatitude(e.target.value);
+            setLocation(null);
+          }}
+        />
+        <input
+          type="number"
+          className="position-input"
+          placeholder="Longitude"
+          step="any"
+          value={longitude}
+          onChange={(e) => {
+            setLongitude(e.target.value);
+            setLocation(null);
+          }}
+        />
+        <button
+          className="icon-btn folder-btn"
+          title="Add solar set"
+          disabled={!name.trim() || latitude === "" || longitude === ""}
+          onClick={handleCreate}>
+          <FiPlus />
+        </button>
+      </div>
+    </div>
+  );
+};
+
+export default SolarEditor;
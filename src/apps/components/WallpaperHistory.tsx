@@ -0,0 +1,73 @@
+import React, { useState, useEffect, useCallback } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { HistoryEntry, SlideshowStatus, WallpaperHistory as History } from "../types/wallpaper";
+
+const fileName = (path: string) => path.split(/[\\/]/).pop();
+
+const formatTime = (seconds: number) => {
+  const date = new Date(seconds * 1000);
+  return date.toDateString() === new Date().toDateString()
+    ? date.toLocaleTimeString([], { hour: "2-digit", minute: "2-digit" })
+    : date.toLocaleDateString();
+};
+
+const WallpaperHistory: React.FC = () => {
+  const [history, setHistory] = useState<History>({ entries: [], position: 0 });
+
+  const loadHistory = useCallback(async () => {
+    try {
+      setHistory(await invoke<History>("get_wallpaper_history"));
+    } catch (error) {
+      console.error("Error loading wallpaper history:", error);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadHistory();
+    const unlisten = listen<SlideshowStatus>("slideshow-changed", () => loadHistory());
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadHistory]);
+
+  const handleReapply = async (entry: HistoryEntry) => {
+    try {
+      await invoke<SlideshowStatus>("reapply_history_wallpaper", { appliedAt: entry.applied_at, path: entry.path });
+      await loadHistory();
+    } catch (error) {
+      alert(`Could not show that wallpaper again: ${error}`);
+    }
+  };
+
+  if (history.entries.length === 0) {
+    return null;
+  }
+
+  // Newest first
+  const entries = history.entries
+    .map((entry, index) => ({ entry, index }))
+    .reverse();
+
+  return (
+    <>
+      <div className="control-row">
+        <span className="control-label">Recently shown</span>
+      </div>
+      <div className="history-list">
+        {entries.map(({ entry, index }) => (
+          <button
+            key={`${index}-${entry.applied_at}`}
+            className={`history-entry ${index === history.position ? "current" : ""}`}
+            title={entry.path}
+            onClick={() => handleReapply(entry)}>
+            <span>{fileName(entry.path)}</span>
+            <span>{formatTime(entry.applied_at)}</span>
+          </button>
+        ))}
+      </div>
+    </>
+  );
+};
+
+export default WallpaperHistory;
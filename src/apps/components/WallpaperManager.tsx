@@ -1,10 +1,54 @@
 import React, { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { convertFileSrc } from "@tauri-apps/api/core";
-import { open } from "@tauri-apps/plugin-dialog";
-import { WallpaperInfo, WallpaperSettings } from "../types/wallpaper";
-import { FiTrash } from "react-icons/fi";
+import { listen } from "@tauri-apps/api/event";
+import { open, save } from "@tauri-apps/plugin-dialog";
+import {
+  Collection,
+  CollectionImport,
+  DuplicateGroup,
+  GnomeImport,
+  ImageOrientation,
+  LibrarySort,
+  LibraryChange,
+  MediaKind,
+  ReconcileReport,
+  ScanBatch,
+  ScanProgress,
+  SlideshowFilter,
+  SlideshowStatus,
+  WallpaperAttributeUpdate,
+  WallpaperInfo,
+  WallpaperPage,
+  WallpaperSettings,
+  WallpaperQuery,
+  WallpaperQueryPage,
+} from "../types/wallpaper";
+import {
+  FiAlertTriangle,
+  FiArrowDown,
+  FiArrowUp,
+  FiChevronLeft,
+  FiChevronRight,
+  FiCopy,
+  FiDownload,
+  FiEye,
+  FiFolderMinus,
+  FiFolderPlus,
+  FiLink,
+  FiMonitor,
+  FiPlus,
+  FiSlash,
+  FiStar,
+  FiTag,
+  FiTrash,
+  FiUpload,
+  FiX,
+} from "react-icons/fi";
 import { CgFolderAdd } from "react-icons/cg";
+import WallpaperThumbnail from "./WallpaperThumbnail";
+import WallpaperHistory from "./WallpaperHistory";
+import WeightingEditor, { DEFAULT_WEIGHTING } from "./WeightingEditor";
 
 interface WallpaperManagerProps {
   settings: WallpaperSettings;
@@ -18,18 +62,53 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
   const [wallpapers, setWallpapers] = useState<WallpaperInfo[]>([]);
   const [currentWallpaper, setCurrentWallpaper] = useState<string>("");
   const [loading, setLoading] = useState(false);
-  const [slideshowInterval, setSlideshowInterval] = useState<ReturnType<
-    typeof setInterval
-  > | null>(null);
+  const [scanProgress, setScanProgress] = useState<ScanProgress | null>(null);
+  const [query, setQuery] = useState<WallpaperQuery>({
+    sort_by: "added",
+    descending: false,
+  });
+  const [visibleWallpapers, setVisibleWallpapers] = useState<WallpaperInfo[]>(
+    []
+  );
+  const [slideshowStatus, setSlideshowStatus] =
+    useState<SlideshowStatus | null>(null);
+  const [collections, setCollections] = useState<Collection[]>([]);
+  const [activeCollection, setActiveCollection] = useState<Collection | null>(
+    null
+  );
+  const [collectionPaths, setCollectionPaths] = useState<Set<string>>(
+    new Set()
+  );
 
-  // Load wallpapers from persistent state on component mount
+  // A collection with its own settings replaces the global ones while it is active
+  const activeSettings = activeCollection?.settings ?? settings;
+
+  const handleSettingsChange = (next: WallpaperSettings) => {
+    if (activeCollection?.settings) {
+      setActiveCollection({ ...activeCollection, settings: next });
+      invoke("set_collection_settings", {
+        id: activeCollection.id,
+        settings: next,
+      }).catch((error) =>
+        console.error("Error saving collection settings:", error)
+      );
+    } else {
+      onSettingsChange(next);
+    }
+  };
+
+  const loadCollections = () => {
+    invoke<Collection[]>("get_collections")
+      .then(setCollections)
+      .catch((error) => console.error("Error loading collections:", error));
+  };
+
+  // Load wallpapers from the library database on component mount
   useEffect(() => {
     const loadWallpapers = async () => {
       try {
-        const state = await invoke<any>("load_app_state");
-        if (state.wallpaper_list && state.wallpaper_list.length > 0) {
-          setWallpapers(state.wallpaper_list);
-        }
+        const library = await invoke<WallpaperInfo[]>("get_library_wallpapers");
+        setWallpapers(library);
       } catch (error) {
         console.error("Error loading wallpapers:", error);
       }
@@ -37,49 +116,74 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
     loadWallpapers();
   }, []);
 
-  // Save wallpapers to persistent state whenever wallpapers change
+  // Merge live changes from watched library folders
   useEffect(() => {
-    if (wallpapers.length > 0) {
-      invoke("save_wallpaper_list", { wallpapers }).catch((error) =>
-        console.error("Error saving wallpapers:", error)
+    const unlisten = listen<LibraryChange>("library-changed", (event) => {
+      const { added, updated, removed } = event.payload;
+      const changed = new Map(
+        [...added, ...updated].map((w) => [w.path, w] as const)
       );
-    }
-  }, [wallpapers]);
+      const removedPaths = new Set(removed);
+
+      setWallpapers((prev) => {
+        const merged = prev
+          .filter((w) => !removedPaths.has(w.path))
+          .map((w) => changed.get(w.path) ?? w);
+        const known = new Set(merged.map((w) => w.path));
+        return [...merged, ...added.filter((w) => !known.has(w.path))];
+      });
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
 
-  // Handle slideshow functionality
+  // Collections come from the library database; the tray can switch the active one too
   useEffect(() => {
-    if (settings.autoChange && wallpapers.length > 1 && settings.interval > 0) {
-      const interval = setInterval(() => {
-        const availableWallpapers = wallpapers.filter(
-          (w) => w.path !== currentWallpaper
-        );
-        if (availableWallpapers.length > 0) {
-          const randomIndex = Math.floor(
-            Math.random() * availableWallpapers.length
-          );
-          const nextWallpaper = availableWallpapers[randomIndex];
-          handleSetWallpaper(nextWallpaper);
-        }
-      }, settings.interval * 1000);
+    loadCollections();
+    invoke<Collection | null>("get_active_collection")
+      .then(setActiveCollection)
+      .catch((error) => console.error("Error loading collection:", error));
 
-      setSlideshowInterval(interval);
+    const unlisten = listen<Collection | null>(
+      "active-collection-changed",
+      (event) => {
+        setActiveCollection(event.payload);
+        loadCollections();
+      }
+    );
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
 
-      return () => {
-        clearInterval(interval);
-        setSlideshowInterval(null);
-      };
-    } else if (slideshowInterval) {
-      clearInterval(slideshowInterval);
-      setSlideshowInterval(null);
+  useEffect(() => {
+    if (!activeCollection) {
+      setCollectionPaths(new Set());
+      return;
     }
-  }, [settings.autoChange, settings.interval, wallpapers, currentWallpaper]);
+    invoke<WallpaperInfo[]>("get_collection_wallpapers", {
+      id: activeCollection.id,
+    })
+      .then((items) => setCollectionPaths(new Set(items.map((w) => w.path))))
+      .catch((error) => console.error("Error loading collection:", error));
+  }, [activeCollection?.id]);
 
-  // Cleanup interval on unmount
+  // The slideshow runs in the backend; follow what it shows
   useEffect(() => {
+    invoke<SlideshowStatus>("get_slideshow_status")
+      .then(setSlideshowStatus)
+      .catch((error) => console.error("Error loading slideshow:", error));
+
+    const unlisten = listen<SlideshowStatus>("slideshow-changed", (event) => {
+      setSlideshowStatus(event.payload);
+      setCurrentWallpaper(event.payload.current ?? "");
+    });
+
     return () => {
-      if (slideshowInterval) {
-        clearInterval(slideshowInterval);
-      }
+      unlisten.then((fn) => fn());
     };
   }, []);
 
@@ -117,7 +221,16 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
           filePaths: filePaths,
         });
 
-        handleAddWallpapers(wallpaperInfos);
+        const rejected = wallpaperInfos.filter((w) => !w.is_valid);
+        if (rejected.length > 0) {
+          alert(
+            `Some files were not added:\n${rejected
+              .map((w) => `${w.name}: ${w.invalid_reason}`)
+              .join("\n")}`
+          );
+        }
+
+        await handleAddWallpapers(wallpaperInfos.filter((w) => w.is_valid));
       }
     } catch (error) {
       console.error("Error loading files:", error);
@@ -126,19 +239,410 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
     }
   };
 
-  const handleAddWallpapers = (newWallpapers: WallpaperInfo[]) => {
-    setWallpapers((prev) => [...prev, ...newWallpapers]);
+  // Stored entries win over fresh scan results, which carry no tags or ratings
+  const importScanned = async (scanned: WallpaperInfo[], imported: Set<string>) => {
+    const fresh = scanned.filter((w) => w.is_valid && !imported.has(w.path));
+    if (fresh.length === 0) {
+      return;
+    }
+    fresh.forEach((w) => imported.add(w.path));
+    await invoke("add_library_wallpapers", { wallpapers: fresh });
+    setWallpapers((prev) => {
+      const known = new Set(prev.map((w) => w.path));
+      return [...prev, ...fresh.filter((w) => !known.has(w.path))];
+    });
+  };
+
+  // Large folders are scanned in the background; batches join the library as they arrive
+  const handleImportFolder = async () => {
+    const directory = await open({
+      directory: true,
+      title: "Select Folder to Import",
+    });
+    if (typeof directory !== "string") {
+      return;
+    }
+
+    const imported = new Set<string>();
+    let scanId: string | null = null;
+    let finish = () => {};
+    const finished = new Promise<void>((resolve) => {
+      finish = resolve;
+    });
+
+    const unlistenBatch = await listen<ScanBatch>("library-scan-batch", (event) => {
+      if (event.payload.scan_id === scanId) {
+        importScanned(event.payload.wallpapers, imported).catch((error) =>
+          console.error("Error saving wallpapers:", error)
+        );
+      }
+    });
+    const unlistenProgress = await listen<ScanProgress>("library-scan-progress", (event) => {
+      if (event.payload.scan_id === scanId) {
+        setScanProgress(event.payload);
+        if (event.payload.status !== "running") {
+          finish();
+        }
+      }
+    });
+
+    try {
+      scanId = await invoke<string>("start_library_scan", { directory });
+      setScanProgress({
+        scan_id: scanId,
+        discovered: 0,
+        processed: 0,
+        matched: 0,
+        status: "running",
+      });
+
+      // A small folder can be done before the scan ID came back, along with its events
+      const first = await invoke<WallpaperPage>("get_library_scan_page", {
+        scanId,
+        limit: 1,
+      });
+      if (first.finished) {
+        finish();
+      }
+      await finished;
+
+      // Page through the results to pick up batches that arrived before the scan ID was known
+      for (let offset = 0; ; ) {
+        const page = await invoke<WallpaperPage>("get_library_scan_page", {
+          scanId,
+          offset,
+        });
+        await importScanned(page.items, imported);
+        offset += page.items.length;
+        if (page.items.length === 0 || offset >= page.total) {
+          break;
+        }
+      }
+    } catch (error) {
+      console.error("Error importing folder:", error);
+    } finally {
+      unlistenBatch();
+      unlistenProgress();
+      setScanProgress(null);
+    }
+  };
+
+  const handleCancelScan = () => {
+    if (scanProgress) {
+      invoke("cancel_library_scan", { scanId: scanProgress.scan_id }).catch(
+        (error) => console.error("Error cancelling scan:", error)
+      );
+    }
+  };
+
+  const handleWatchFolder = async () => {
+    try {
+      const directory = await open({
+        directory: true,
+        title: "Select Folder to Watch",
+      });
+
+      if (typeof directory === "string") {
+        setLoading(true);
+        // New files arrive through the library-changed event
+        await invoke<LibraryChange>("watch_library_folder", { directory });
+      }
+    } catch (error) {
+      console.error("Error watching folder:", error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleRepairMissing = async () => {
+    try {
+      setLoading(true);
+      // Relinked and still-missing entries arrive through the library-changed event
+      const report = await invoke<ReconcileReport>("repair_broken_wallpapers");
+      if (
+        report.missing.length > 0 &&
+        confirm(
+          `${report.missing.length} wallpaper(s) could not be found. Remove them from the library?`
+        )
+      ) {
+        await invoke("prune_broken_wallpapers", { paths: report.missing });
+      }
+    } catch (error) {
+      console.error("Error repairing wallpapers:", error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  const handleMergeDuplicates = async () => {
+    try {
+      setLoading(true);
+      const groups = await invoke<DuplicateGroup[]>("find_duplicate_wallpapers");
+      if (groups.length === 0) {
+        alert("No duplicates found.");
+        return;
+      }
+
+      const extra = groups.reduce((sum, g) => sum + g.items.length - 1, 0);
+      if (
+        !confirm(
+          `Found ${groups.length} group(s) of duplicates. Keep the highest resolution of each and remove ${extra} wallpaper(s) from the library? Files on disk are not deleted.`
+        )
+      ) {
+        return;
+      }
+
+      // Removed entries arrive through the library-changed event
+      for (const group of groups) {
+        await invoke("merge_duplicate_wallpapers", {
+          paths: group.items.map((item) => item.wallpaper.path),
+        });
+      }
+    } catch (error) {
+      console.error("Error merging duplicates:", error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // The updated entries arrive through the library-changed event
+  const handleUpdateAttributes = (
+    paths: string[],
+    update: WallpaperAttributeUpdate
+  ) => {
+    invoke("update_wallpaper_attributes", { paths, update }).catch((error) =>
+      console.error("Error updating wallpapers:", error)
+    );
+  };
+
+  const handleEditTags = (wallpaper: WallpaperInfo) => {
+    const current = wallpaper.tags ?? [];
+    const input = prompt("Tags, separated by commas:", current.join(", "));
+    if (input === null) {
+      return;
+    }
+
+    const tags = input
+      .split(",")
+      .map((tag) => tag.trim())
+      .filter((tag) => tag.length > 0);
+    const lower = tags.map((tag) => tag.toLowerCase());
+    handleUpdateAttributes([wallpaper.path], {
+      add_tags: tags,
+      remove_tags: current.filter((tag) => !lower.includes(tag.toLowerCase())),
+    });
+  };
+
+  const slideshowFilter: SlideshowFilter = activeSettings.filter ?? {
+    tags: [],
+    favoritesOnly: false,
+  };
+
+  const handleFilterChange = (filter: Partial<SlideshowFilter>) => {
+    handleSettingsChange({
+      ...activeSettings,
+      filter: { ...slideshowFilter, ...filter },
+    });
+  };
+
+  const handleSelectCollection = async (value: string) => {
+    try {
+      // The tray and this list are updated through the active-collection-changed event
+      await invoke("set_active_collection", {
+        id: value === "all" ? null : Number(value),
+      });
+    } catch (error) {
+      console.error("Error switching collection:", error);
+    }
+  };
+
+  const handleCreateCollection = async () => {
+    const name = prompt("Collection name:");
+    if (!name?.trim()) {
+      return;
+    }
+
+    try {
+      const collection = await invoke<Collection>("create_collection", {
+        name,
+      });
+      loadCollections();
+      await handleSelectCollection(String(collection.id));
+    } catch (error) {
+      alert(error);
+    }
+  };
+
+  const handleDeleteCollection = async () => {
+    if (
+      !activeCollection ||
+      !confirm(
+        `Delete the collection "${activeCollection.name}"? Its wallpapers stay in the library.`
+      )
+    ) {
+      return;
+    }
+
+    try {
+      await invoke("delete_collection", { id: activeCollection.id });
+      loadCollections();
+    } catch (error) {
+      console.error("Error deleting collection:", error);
+    }
+  };
+
+  const handleToggleOwnSettings = (enabled: boolean) => {
+    if (!activeCollection) {
+      return;
+    }
+
+    const collectionSettings = enabled ? { ...settings } : null;
+    setActiveCollection({
+      ...activeCollection,
+      settings: collectionSettings ?? undefined,
+    });
+    invoke("set_collection_settings", {
+      id: activeCollection.id,
+      settings: collectionSettings,
+    }).catch((error) =>
+      console.error("Error saving collection settings:", error)
+    );
+  };
+
+  const handleToggleInCollection = async (wallpaper: WallpaperInfo) => {
+    if (!activeCollection) {
+      return;
+    }
+
+    const inCollection = collectionPaths.has(wallpaper.path);
+    try {
+      await invoke(
+        inCollection ? "remove_from_collection" : "add_to_collection",
+        { id: activeCollection.id, paths: [wallpaper.path] }
+      );
+      setCollectionPaths((prev) => {
+        const next = new Set(prev);
+        if (inCollection) {
+          next.delete(wallpaper.path);
+        } else {
+          next.add(wallpaper.path);
+        }
+        return next;
+      });
+      loadCollections();
+    } catch (error) {
+      console.error("Error updating collection:", error);
+    }
+  };
+
+  const handleExportCollection = async () => {
+    if (!activeCollection) {
+      return;
+    }
+
+    try {
+      const path = await save({
+        defaultPath: `${activeCollection.name}.json`,
+        filters: [
+          { name: "Collection", extensions: ["json"] },
+          { name: "Playlist", extensions: ["m3u", "m3u8"] },
+          { name: "GNOME slideshow", extensions: ["xml"] },
+        ],
+      });
+      if (!path) {
+        return;
+      }
+      if (path.toLowerCase().endsWith(".xml")) {
+        await invoke("export_collection_gnome", {
+          id: activeCollection.id,
+          path,
+          transition: null,
+          register: confirm("Also list this slideshow in GNOME Settings?"),
+        });
+      } else {
+        await invoke("export_collection", { id: activeCollection.id, path });
+      }
+    } catch (error) {
+      console.error("Error exporting collection:", error);
+    }
+  };
+
+  const handleImportCollection = async () => {
+    try {
+      const path = await open({
+        multiple: false,
+        filters: [
+          { name: "Collections", extensions: ["json", "m3u", "m3u8"] },
+          { name: "GNOME backgrounds", extensions: ["xml"] },
+        ],
+      });
+      if (typeof path !== "string") {
+        return;
+      }
+
+      setLoading(true);
+      if (path.toLowerCase().endsWith(".xml")) {
+        await handleImportGnome(path);
+        return;
+      }
+      const result = await invoke<CollectionImport>("import_collection", {
+        path,
+      });
+      loadCollections();
+      if (result.skipped.length > 0) {
+        alert(
+          `Imported "${result.collection.name}". These entries could not be found:\n${result.skipped.join("\n")}`
+        );
+      }
+      await handleSelectCollection(String(result.collection.id));
+    } catch (error) {
+      console.error("Error importing collection:", error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // Slideshows become dynamic sets; plain pictures land in a new collection
+  const handleImportGnome = async (path: string) => {
+    const result = await invoke<GnomeImport>("import_gnome_backgrounds", {
+      path,
+    });
+    loadCollections();
+    const notes = [];
+    if (result.dynamic_sets.length > 0) {
+      notes.push(
+        `Added dynamic sets: ${result.dynamic_sets.map((set) => set.name).join(", ")}`
+      );
+    }
+    if (result.skipped.length > 0) {
+      notes.push(`These entries could not be read:\n${result.skipped.join("\n")}`);
+    }
+    if (notes.length > 0) {
+      alert(notes.join("\n\n"));
+    }
+    if (result.collection) {
+      await handleSelectCollection(String(result.collection.id));
+    }
+  };
+
+  const handleAddWallpapers = async (newWallpapers: WallpaperInfo[]) => {
+    try {
+      await invoke("add_library_wallpapers", { wallpapers: newWallpapers });
+      setWallpapers(await invoke<WallpaperInfo[]>("get_library_wallpapers"));
+    } catch (error) {
+      console.error("Error saving wallpapers:", error);
+    }
   };
 
   const handleDeleteWallpaper = (wallpaperPath: string) => {
     setWallpapers((prev) => prev.filter((w) => w.path !== wallpaperPath));
+    invoke("remove_library_wallpapers", { paths: [wallpaperPath] }).catch(
+      (error) => console.error("Error removing wallpaper:", error)
+    );
 
     if (currentWallpaper === wallpaperPath) {
       setCurrentWallpaper("");
-      if (
-        isVideoFile(getWallpaperByPath(wallpaperPath)?.file_type || "") ||
-        isGifFile(getWallpaperByPath(wallpaperPath)?.file_type || "")
-      ) {
+      if (isMotionWallpaper(getWallpaperByPath(wallpaperPath))) {
         handleStopVideo();
       }
     }
@@ -148,17 +652,13 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
     try {
       setLoading(true);
 
-      if (
-        currentWallpaper &&
-        (isVideoFile(getWallpaperByPath(currentWallpaper)?.file_type || "") ||
-          isGifFile(getWallpaperByPath(currentWallpaper)?.file_type || ""))
-      ) {
+      if (currentWallpaper && isMotionWallpaper(getWallpaperByPath(currentWallpaper))) {
         if (wallpaper.path !== currentWallpaper) {
           await handleStopVideo();
         }
       }
 
-      if (isVideoFile(wallpaper.file_type) || isGifFile(wallpaper.file_type)) {
+      if (isMotionWallpaper(wallpaper)) {
         const convertedPath = convertFileSrc(wallpaper.path);
         const result = await invoke<string>("create_video_wallpaper", {
           filePath: wallpaper.path,
@@ -166,11 +666,7 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
         });
         console.log(result);
       } else {
-        if (
-          currentWallpaper &&
-          (isVideoFile(getWallpaperByPath(currentWallpaper)?.file_type || "") ||
-            isGifFile(getWallpaperByPath(currentWallpaper)?.file_type || ""))
-        ) {
+        if (currentWallpaper && isMotionWallpaper(getWallpaperByPath(currentWallpaper))) {
           await handleStopVideo();
         }
         const result = await invoke<string>("set_static_wallpaper", {
@@ -188,6 +684,17 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
     }
   };
 
+  const handleSlideshowStep = async (command: string) => {
+    try {
+      setLoading(true);
+      setSlideshowStatus(await invoke<SlideshowStatus>(command));
+    } catch (error) {
+      console.error("Error changing wallpaper:", error);
+    } finally {
+      setLoading(false);
+    }
+  };
+
   const handleStopVideo = async () => {
     try {
       setLoading(true);
@@ -201,57 +708,130 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
     }
   };
 
+  // Undated wallpapers go last when sorting by capture date, matching the backend order
+  // Searching, filtering and sorting happen in the library database
+  useEffect(() => {
+    invoke<WallpaperQueryPage>("query_wallpapers", { query })
+      .then((page) => setVisibleWallpapers(page.items))
+      .catch((error) => console.error("Error querying wallpapers:", error));
+  }, [query, wallpapers]);
+
+  const updateQuery = (changes: Partial<WallpaperQuery>) => {
+    setQuery((prev) => ({ ...prev, ...changes }));
+  };
+
   const getWallpaperByPath = (path: string) => {
     return wallpapers.find((w) => w.path === path);
   };
 
-  const isVideoFile = (fileType: string) => {
-    return ["mp4", "webm", "avi", "mov", "mkv"].includes(
-      fileType.toLowerCase()
-    );
+  // Entries saved before content sniffing have no media kind, so fall back to the extension
+  const getMediaKind = (wallpaper: WallpaperInfo) => {
+    if (wallpaper.mime_type) {
+      return wallpaper.media_kind;
+    }
+    const fileType = wallpaper.file_type.toLowerCase();
+    if (["mp4", "webm", "avi", "mov", "mkv"].includes(fileType)) {
+      return "video";
+    }
+    return fileType === "gif" ? "animated" : "static";
   };
 
-  const isGifFile = (fileType: string) => {
-    return fileType.toLowerCase() === "gif";
+  const isMotionWallpaper = (wallpaper?: WallpaperInfo) => {
+    return !!wallpaper && getMediaKind(wallpaper) !== "static";
   };
 
-  const renderPreview = (wallpaper: WallpaperInfo) => {
-    if (isVideoFile(wallpaper.file_type)) {
-      return (
-        <div className="media-preview">
-          <video
-            src={convertFileSrc(wallpaper.path)}
-            muted
-            preload="metadata"
-            className="preview-media"
-          />
-        </div>
-      );
-    }
+  // Flag videos that are far larger than the screen they will play on
+  const isOversized = (wallpaper: WallpaperInfo) => {
+    const width = wallpaper.video?.width ?? 0;
+    const height = wallpaper.video?.height ?? 0;
+    const screenWidth = window.screen.width * window.devicePixelRatio;
+    const screenHeight = window.screen.height * window.devicePixelRatio;
+    return width > screenWidth * 1.5 || height > screenHeight * 1.5;
+  };
 
+  const renderPreview = (wallpaper: WallpaperInfo) => {
     return (
-      <div className="media-preview">
-        <img
-          src={convertFileSrc(wallpaper.path)}
-          alt={wallpaper.name}
-          className="preview-media"
-        />
-      </div>
+      <WallpaperThumbnail
+        wallpaper={wallpaper}
+        isVideo={getMediaKind(wallpaper) === "video"}
+      />
     );
   };
 
   return (
     <div className="wallpaper-container">
+      <div className="section collection-controls">
+        <div className="control-row">
+          <span className="control-label">Collection</span>
+          <select
+            className="sort-select"
+            value={activeCollection ? String(activeCollection.id) : "all"}
+            onChange={(e) => handleSelectCollection(e.target.value)}>
+            <option value="all">All wallpapers</option>
+            {collections.map((collection) => (
+              <option key={collection.id} value={String(collection.id)}>
+                {collection.name} ({collection.item_count})
+              </option>
+            ))}
+          </select>
+          <button
+            className="icon-btn folder-btn"
+            onClick={handleCreateCollection}
+            title="New collection">
+            <FiPlus />
+          </button>
+          <button
+            className="icon-btn folder-btn"
+            onClick={handleImportCollection}
+            disabled={loading}
+            title="Import collection">
+            <FiUpload />
+          </button>
+          {activeCollection && (
+            <>
+              <button
+                className="icon-btn folder-btn"
+                onClick={handleExportCollection}
+                title="Export collection">
+                <FiDownload />
+              </button>
+              <button
+                className="icon-btn delete-btn"
+                onClick={handleDeleteCollection}
+                title="Delete collection">
+                <FiTrash />
+              </button>
+            </>
+          )}
+        </div>
+
+        {activeCollection && (
+          <div className="control-row">
+            <span className="control-label">
+              Own slideshow settings for this collection
+            </span>
+            <label className="toggle-switch">
+              <input
+                type="checkbox"
+                checked={!!activeCollection.settings}
+                onChange={(e) => handleToggleOwnSettings(e.target.checked)}
+              />
+              <span className="toggle-slider"></span>
+            </label>
+          </div>
+        )}
+      </div>
+
       <div className="section slideshow-controls">
         <div className="control-row">
-          <span className="control-label">Randomize slideshow</span>
+          <span className="control-label">Change wallpaper automatically</span>
           <label className="toggle-switch">
             <input
               type="checkbox"
-              checked={settings.autoChange}
+              checked={activeSettings.autoChange}
               onChange={(e) =>
-                onSettingsChange({
-                  ...settings,
+                handleSettingsChange({
+                  ...activeSettings,
                   autoChange: e.target.checked,
                 })
               }
@@ -260,6 +840,141 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
           </label>
         </div>
 
+        <div className="control-row">
+          <span className="control-label">Shuffle order</span>
+          <label className="toggle-switch">
+            <input
+              type="checkbox"
+              checked={activeSettings.randomOrder}
+              onChange={(e) =>
+                handleSettingsChange({
+                  ...activeSettings,
+                  randomOrder: e.target.checked,
+                })
+              }
+            />
+            <span className="toggle-slider"></span>
+          </label>
+        </div>
+
+        {activeSettings.randomOrder && (
+          <WeightingEditor
+            weighting={activeSettings.weighting ?? DEFAULT_WEIGHTING}
+            onChange={(weighting) =>
+              handleSettingsChange({ ...activeSettings, weighting })
+            }
+          />
+        )}
+
+        <div className="control-row">
+          <span className="control-label">Change wallpaper now</span>
+          <div className="slideshow-nav">
+            <button
+              className="icon-btn"
+              title="Previous wallpaper"
+              disabled={!slideshowStatus?.can_go_back}
+              onClick={() => handleSlideshowStep("previous_slideshow_wallpaper")}>
+              <FiChevronLeft />
+            </button>
+            <button
+              className="icon-btn"
+              title="Next wallpaper"
+              onClick={() => handleSlideshowStep("next_slideshow_wallpaper")}>
+              <FiChevronRight />
+            </button>
+          </div>
+        </div>
+
+        <WallpaperHistory />
+
+        <div className="control-row">
+          <span className="control-label">Treat similar images as one</span>
+          <label className="toggle-switch">
+            <input
+              type="checkbox"
+              checked={activeSettings.collapseDuplicates ?? false}
+              onChange={(e) =>
+                handleSettingsChange({
+                  ...activeSettings,
+                  collapseDuplicates: e.target.checked,
+                })
+              }
+            />
+            <span className="toggle-slider"></span>
+          </label>
+        </div>
+
+        <div className="control-row">
+          <span className="control-label">Favorites only</span>
+          <label className="toggle-switch">
+            <input
+              type="checkbox"
+              checked={slideshowFilter.favoritesOnly}
+              onChange={(e) =>
+                handleFilterChange({ favoritesOnly: e.target.checked })
+              }
+            />
+            <span className="toggle-slider"></span>
+          </label>
+        </div>
+
+        <div className="control-row">
+          <span className="control-label">Minimum rating</span>
+          <select
+            className="sort-select"
+            value={slideshowFilter.minRating ?? 0}
+            onChange={(e) => {
+              const rating = parseInt(e.target.value) || 0;
+              handleFilterChange({ minRating: rating > 0 ? rating : undefined });
+            }}>
+            <option value={0}>Any</option>
+            {[1, 2, 3, 4, 5].map((rating) => (
+              <option key={rating} value={rating}>
+                {"★".repeat(rating)}
+              </option>
+            ))}
+          </select>
+        </div>
+
+        <div className="control-row">
+          <span className="control-label">Only tags</span>
+          <input
+            type="text"
+            key={activeCollection?.id ?? "all"}
+            className="tag-filter-input"
+            placeholder="Any"
+            defaultValue={slideshowFilter.tags.join(", ")}
+            onBlur={(e) =>
+              handleFilterChange({
+                tags: e.target.value
+                  .split(",")
+                  .map((tag) => tag.trim())
+                  .filter((tag) => tag.length > 0),
+              })
+            }
+          />
+        </div>
+
+        <div className="control-row">
+          <span className="control-label">Only colors near</span>
+          <div className="color-filter">
+            <input
+              type="color"
+              className="color-filter-input"
+              value={slideshowFilter.color ?? "#000000"}
+              onChange={(e) => handleFilterChange({ color: e.target.value })}
+            />
+            {slideshowFilter.color && (
+              <button
+                className="icon-btn"
+                title="Any color"
+                onClick={() => handleFilterChange({ color: undefined })}>
+                <FiX />
+              </button>
+            )}
+          </div>
+        </div>
+
         <div className="time-control-row">
           <span className="control-label">Change wallpaper every</span>
           <div className="time-inputs">
@@ -269,13 +984,13 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
                 type="number"
                 min="0"
                 max="23"
-                value={Math.floor(settings.interval / 3600)}
+                value={Math.floor(activeSettings.interval / 3600)}
                 onChange={(e) => {
                   const hours = parseInt(e.target.value) || 0;
-                  const minutes = Math.floor((settings.interval % 3600) / 60);
-                  const seconds = settings.interval % 60;
-                  onSettingsChange({
-                    ...settings,
+                  const minutes = Math.floor((activeSettings.interval % 3600) / 60);
+                  const seconds = activeSettings.interval % 60;
+                  handleSettingsChange({
+                    ...activeSettings,
                     interval: hours * 3600 + minutes * 60 + seconds,
                   });
                 }}
@@ -288,13 +1003,13 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
                 type="number"
                 min="0"
                 max="59"
-                value={Math.floor((settings.interval % 3600) / 60)}
+                value={Math.floor((activeSettings.interval % 3600) / 60)}
                 onChange={(e) => {
                   const minutes = parseInt(e.target.value) || 0;
-                  const hours = Math.floor(settings.interval / 3600);
-                  const seconds = settings.interval % 60;
-                  onSettingsChange({
-                    ...settings,
+                  const hours = Math.floor(activeSettings.interval / 3600);
+                  const seconds = activeSettings.interval % 60;
+                  handleSettingsChange({
+                    ...activeSettings,
                     interval: hours * 3600 + minutes * 60 + seconds,
                   });
                 }}
@@ -307,13 +1022,13 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
                 type="number"
                 min="0"
                 max="59"
-                value={settings.interval % 60}
+                value={activeSettings.interval % 60}
                 onChange={(e) => {
                   const seconds = parseInt(e.target.value) || 0;
-                  const hours = Math.floor(settings.interval / 3600);
-                  const minutes = Math.floor((settings.interval % 3600) / 60);
-                  onSettingsChange({
-                    ...settings,
+                  const hours = Math.floor(activeSettings.interval / 3600);
+                  const minutes = Math.floor((activeSettings.interval % 3600) / 60);
+                  handleSettingsChange({
+                    ...activeSettings,
                     interval: hours * 3600 + minutes * 60 + seconds,
                   });
                 }}
@@ -326,12 +1041,127 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
       <div className="section wallpapers-section">
         <div className="wallpapers-header">
           <span className="wallpapers-label">Wallpapers</span>
+          <select
+            value={query.sort_by}
+            onChange={(e) =>
+              updateQuery({ sort_by: e.target.value as LibrarySort })
+            }
+            className="sort-select">
+            <option value="added">Date added</option>
+            <option value="name">Name</option>
+            <option value="modified">Date modified</option>
+            <option value="capture_date">Capture date</option>
+            <option value="size">File size</option>
+            <option value="rating">Rating</option>
+            <option value="usage">Times shown</option>
+          </select>
+          <button
+            className="icon-btn folder-btn"
+            onClick={() => updateQuery({ descending: !query.descending })}
+            title={query.descending ? "Descending" : "Ascending"}>
+            {query.descending ? <FiArrowDown /> : <FiArrowUp />}
+          </button>
           <button
             className="icon-btn folder-btn"
             onClick={handleAddFiles}
             disabled={loading}>
             <CgFolderAdd />
           </button>
+          <button
+            className="icon-btn folder-btn"
+            onClick={handleImportFolder}
+            disabled={loading || scanProgress !== null}
+            title="Import a folder">
+            <FiFolderPlus />
+          </button>
+          {wallpapers.some((w) => w.is_missing) && (
+            <button
+              className="icon-btn folder-btn"
+              onClick={handleRepairMissing}
+              disabled={loading}
+              title="Find or remove missing files">
+              <FiLink />
+            </button>
+          )}
+          <button
+            className="icon-btn folder-btn"
+            onClick={handleMergeDuplicates}
+            disabled={loading || wallpapers.length < 2}
+            title="Find duplicates">
+            <FiCopy />
+          </button>
+          <button
+            className="icon-btn folder-btn"
+            onClick={handleWatchFolder}
+            disabled={loading}
+            title="Watch a folder">
+            <FiEye />
+          </button>
+        </div>
+
+        <div className="wallpapers-filters">
+          <input
+            type="search"
+            className="tag-filter-input"
+            placeholder="Search by name"
+            value={query.name ?? ""}
+            onChange={(e) => updateQuery({ name: e.target.value })}
+          />
+          <select
+            className="sort-select"
+            value={query.kinds?.[0] ?? "all"}
+            onChange={(e) =>
+              updateQuery({
+                kinds:
+                  e.target.value === "all"
+                    ? []
+                    : [e.target.value as MediaKind],
+              })
+            }>
+            <option value="all">All kinds</option>
+            <option value="static">Images</option>
+            <option value="animated">Animated</option>
+            <option value="video">Videos</option>
+          </select>
+          <select
+            className="sort-select"
+            value={query.orientation ?? "any"}
+            onChange={(e) =>
+              updateQuery({
+                orientation:
+                  e.target.value === "any"
+                    ? undefined
+                    : (e.target.value as ImageOrientation),
+              })
+            }>
+            <option value="any">Any shape</option>
+            <option value="landscape">Landscape</option>
+            <option value="portrait">Portrait</option>
+            <option value="square">Square</option>
+          </select>
+          <button
+            className={`icon-btn collection-btn ${query.fits_monitor ? "active" : ""}`}
+            onClick={() => updateQuery({ fits_monitor: !query.fits_monitor })}
+            title="Only wallpapers that fit this monitor">
+            <FiMonitor />
+          </button>
+          <div className="color-filter">
+            <input
+              type="color"
+              className="color-filter-input"
+              title="Find wallpapers with this color"
+              value={query.color ?? "#000000"}
+              onChange={(e) => updateQuery({ color: e.target.value })}
+            />
+            {query.color && (
+              <button
+                className="icon-btn"
+                title="Any color"
+                onClick={() => updateQuery({ color: undefined })}>
+                <FiX />
+              </button>
+            )}
+          </div>
         </div>
 
         {wallpapers.length === 0 ? (
@@ -340,14 +1170,114 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
           </div>
         ) : (
           <div className="wallpapers-list">
-            {wallpapers.map((wallpaper) => (
+            {visibleWallpapers.map((wallpaper) => (
               <div key={wallpaper.path} className="wallpaper-item">
                 <div
                   className="wallpaper-preview-small"
                   onClick={() => handleSetWallpaper(wallpaper)}>
                   {renderPreview(wallpaper)}
                 </div>
-                <span className="wallpaper-name">{wallpaper.name}</span>
+                <div className="wallpaper-details">
+                  <span className="wallpaper-name">{wallpaper.name}</span>
+                  {(wallpaper.tags?.length ?? 0) > 0 && (
+                    <span className="wallpaper-tags">
+                      {wallpaper.tags?.join(", ")}
+                    </span>
+                  )}
+                  {(wallpaper.palette?.length ?? 0) > 0 && (
+                    <span className="wallpaper-palette">
+                      {wallpaper.palette?.map((color) => (
+                        <span
+                          key={color}
+                          className="palette-swatch"
+                          title={color}
+                          style={{ backgroundColor: color }}
+                        />
+                      ))}
+                    </span>
+                  )}
+                </div>
+                {wallpaper.is_missing && (
+                  <span
+                    className="wallpaper-warning"
+                    title="File could not be found; it may have been moved or deleted">
+                    <FiAlertTriangle />
+                  </span>
+                )}
+                {isOversized(wallpaper) && (
+                  <span
+                    className="wallpaper-warning"
+                    title={`${wallpaper.video?.width}×${wallpaper.video?.height} is much larger than this monitor and may use a lot of resources`}>
+                    <FiAlertTriangle />
+                  </span>
+                )}
+                <select
+                  className="rating-select"
+                  title="Rating"
+                  value={wallpaper.rating ?? 0}
+                  onClick={(e) => e.stopPropagation()}
+                  onChange={(e) =>
+                    handleUpdateAttributes([wallpaper.path], {
+                      rating: parseInt(e.target.value) || 0,
+                    })
+                  }>
+                  <option value={0}>☆</option>
+                  {[1, 2, 3, 4, 5].map((rating) => (
+                    <option key={rating} value={rating}>
+                      {"★".repeat(rating)}
+                    </option>
+                  ))}
+                </select>
+                <button
+                  className={`icon-btn favorite-btn ${wallpaper.favorite ? "active" : ""}`}
+                  title={wallpaper.favorite ? "Remove from favorites" : "Add to favorites"}
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    handleUpdateAttributes([wallpaper.path], {
+                      favorite: !wallpaper.favorite,
+                    });
+                  }}>
+                  <FiStar />
+                </button>
+                {activeCollection && (
+                  <button
+                    className={`icon-btn collection-btn ${collectionPaths.has(wallpaper.path) ? "active" : ""}`}
+                    title={
+                      collectionPaths.has(wallpaper.path)
+                        ? `Remove from ${activeCollection.name}`
+                        : `Add to ${activeCollection.name}`
+                    }
+                    onClick={(e) => {
+                      e.stopPropagation();
+                      handleToggleInCollection(wallpaper);
+                    }}>
+                    {collectionPaths.has(wallpaper.path) ? (
+                      <FiFolderMinus />
+                    ) : (
+                      <FiFolderPlus />
+                    )}
+                  </button>
+                )}
+                <button
+                  className="icon-btn tag-btn"
+                  title="Edit tags"
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    handleEditTags(wallpaper);
+                  }}>
+                  <FiTag />
+                </button>
+                <button
+                  className={`icon-btn block-btn ${wallpaper.blocked ? "active" : ""}`}
+                  title={wallpaper.blocked ? "Allow in slideshow" : "Never show in slideshow"}
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    handleUpdateAttributes([wallpaper.path], {
+                      blocked: !wallpaper.blocked,
+                    });
+                  }}>
+                  <FiSlash />
+                </button>
                 <button
                   className="icon-btn delete-btn"
                   onClick={(e) => {
@@ -368,6 +1298,22 @@ const WallpaperManager: React.FC<WallpaperManagerProps> = ({
           <span>Processing selected files...</span>
         </div>
       )}
+
+      {scanProgress && (
+        <div className="loading-indicator">
+          <div className="spinner"></div>
+          <span>
+            Scanning folder: {scanProgress.processed} of {scanProgress.discovered} files
+            read, {scanProgress.matched} wallpapers found
+          </span>
+          <button
+            className="icon-btn"
+            onClick={handleCancelScan}
+            title="Cancel scan">
+            <FiX />
+          </button>
+        </div>
+      )}
     </div>
   );
 };
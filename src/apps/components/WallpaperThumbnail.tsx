@@ -0,0 +1,83 @@
+import React, { useEffect, useState } from "react";
+import { invoke, convertFileSrc } from "@tauri-apps/api/core";
+import { WallpaperInfo } from "../types/wallpaper";
+
+interface WallpaperThumbnailProps {
+  wallpaper: WallpaperInfo;
+  isVideo: boolean;
+  size?: number;
+}
+
+const WallpaperThumbnail: React.FC<WallpaperThumbnailProps> = ({
+  wallpaper,
+  isVideo,
+  size = 128,
+}) => {
+  const [thumbnailPath, setThumbnailPath] = useState<string | null>(null);
+  const [failed, setFailed] = useState(false);
+
+  useEffect(() => {
+    let cancelled = false;
+    setThumbnailPath(null);
+    setFailed(false);
+
+    invoke<string>("get_thumbnail", { path: wallpaper.path, size })
+      .then((path) => {
+        if (!cancelled) {
+          setThumbnailPath(path);
+        }
+      })
+      .catch((error) => {
+        console.error("Error loading thumbnail:", error);
+        if (!cancelled) {
+          setFailed(true);
+        }
+      });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [wallpaper.path, size]);
+
+  if (thumbnailPath) {
+    return (
+      <div className="media-preview">
+        <img
+          src={convertFileSrc(thumbnailPath)}
+          alt={wallpaper.name}
+          className="preview-media"
+        />
+      </div>
+    );
+  }
+
+  if (!failed) {
+    return <div className="media-preview" />;
+  }
+
+  // Fall back to loading the original file when no thumbnail could be made
+  if (isVideo) {
+    return (
+      <div className="media-preview">
+        <video
+          src={convertFileSrc(wallpaper.path)}
+          muted
+          preload="metadata"
+          className="preview-media"
+        />
+      </div>
+    );
+  }
+
+  return (
+    <div className="media-preview">
+      <img
+        src={convertFileSrc(wallpaper.path)}
+        alt={wallpaper.name}
+        className="preview-media"
+      />
+    </div>
+  );
+};
+
+export default WallpaperThumbnail;
@@ -0,0 +1,99 @@
+import React, { useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { PickWeight, SlideshowWeighting } from "../types/wallpaper";
+
+// Same defaults as the backend
+export const DEFAULT_WEIGHTING: SlideshowWeighting = {
+  enabled: false,
+  ratingFactor: 1.5,
+  favoriteFactor: 2,
+  recencyHours: 24,
+  frequencyPenalty: 0.5,
+};
+
+const FIELDS: { key: keyof Omit<SlideshowWeighting, "enabled">; label: string; step: number }[] = [
+  { key: "ratingFactor", label: "Weight per star", step: 0.1 },
+  { key: "favoriteFactor", label: "Favorite boost", step: 0.5 },
+  { key: "recencyHours", label: "Hold back after showing (hours)", step: 1 },
+  { key: "frequencyPenalty", label: "Penalty for showing often", step: 0.1 },
+];
+
+// How many of the most likely picks the odds list shows
+const ODDS_SHOWN = 15;
+
+interface WeightingEditorProps {
+  weighting: SlideshowWeighting;
+  onChange: (weighting: SlideshowWeighting) => void;
+}
+
+const WeightingEditor: React.FC<WeightingEditorProps> = ({ weighting, onChange }) => {
+  const [odds, setOdds] = useState<PickWeight[] | null>(null);
+
+  const handleShowOdds = async () => {
+    try {
+      setOdds(await invoke<PickWeight[]>("get_slideshow_probabilities"));
+    } catch (error) {
+      console.error("Error loading slideshow odds:", error);
+    }
+  };
+
+  return (
+    <>
+      <div className="control-row">
+        <span className="control-label">Favor highly rated and less seen</span>
+        <label className="toggle-switch">
+          <input
+            type="checkbox"
+            checked={weighting.enabled}
+            onChange={(e) => onChange({ ...weighting, enabled: e.target.checked })}
+          />
+          <span className="toggle-slider"></span>
+        </label>
+      </div>
+
+      {weighting.enabled && (
+        <>
+          {FIELDS.map(({ key, label, step }) => (
+            <div key={key} className="control-row">
+              <span className="control-label">{label}</span>
+              <input
+                type="number"
+                className="position-input"
+                min={0}
+                step={step}
+                value={weighting[key]}
+                onChange={(e) =>
+                  onChange({ ...weighting, [key]: parseFloat(e.target.value) || 0 })
+                }
+              />
+            </div>
+          ))}
+
+          <div className="control-row">
+            <span className="control-label">Odds of coming up next</span>
+            <button className="btn" onClick={handleShowOdds}>
+              {odds ? "Refresh" : "Show"}
+            </button>
+          </div>
+          {odds && (
+            <div className="schedule-preview">
+              {odds.slice(0, ODDS_SHOWN).map((pick) => (
+                <span
+                  key={pick.path}
+                  title={`Rating ×${pick.rating_factor.toFixed(2)}, favorite ×${pick.favorite_factor.toFixed(
+                    2
+                  )}, recency ×${pick.recency_factor.toFixed(2)}, frequency ×${pick.frequency_factor.toFixed(
+                    2
+                  )}, shown ${pick.usage.times_shown} times`}>
+                  {(pick.probability * 100).toFixed(1)}% {pick.name}
+                </span>
+              ))}
+            </div>
+          )}
+        </>
+      )}
+    </>
+  );
+};
+
+export default WeightingEditor;
@@ -1,8 +1,121 @@
+export type MediaKind = 'static' | 'animated' | 'video';
+
 export interface WallpaperInfo {
   path: string;
   name: string;
   file_type: string;
   size: number;
+  modified_at?: number;
+  mime_type: string;
+  media_kind: MediaKind;
+  is_valid: boolean;
+  invalid_reason?: string;
+  is_missing?: boolean;
+  width?: number;
+  height?: number;
+  orientation?: number;
+  captured_at?: string;
+  camera_model?: string;
+  gps_location?: GpsLocation;
+  video?: VideoMetadata;
+  tags?: string[];
+  favorite?: boolean;
+  rating?: number;
+  blocked?: boolean;
+  // Dominant colors as #rrggbb, most dominant first
+  palette?: string[];
+}
+
+export interface WallpaperAttributeUpdate {
+  favorite?: boolean;
+  // 1 to 5 stars, or 0 to clear the rating
+  rating?: number;
+  blocked?: boolean;
+  add_tags?: string[];
+  remove_tags?: string[];
+}
+
+export interface Collection {
+  id: number;
+  name: string;
+  item_count: number;
+  // Used instead of the global slideshow settings while the collection is active
+  settings?: WallpaperSettings | null;
+}
+
+export interface CollectionImport {
+  collection: Collection;
+  skipped: string[];
+}
+
+export interface TagCount {
+  name: string;
+  count: number;
+}
+
+export interface VideoMetadata {
+  duration_secs?: number;
+  width?: number;
+  height?: number;
+  frame_rate?: number;
+  codec?: string;
+  has_audio: boolean;
+  frame_count?: number;
+}
+
+export interface GpsLocation {
+  latitude: number;
+  longitude: number;
+}
+
+export type WallpaperSort = 'name' | 'capture_date';
+
+export type LibrarySort =
+  | 'added'
+  | 'name'
+  | 'modified'
+  | 'capture_date'
+  | 'size'
+  | 'rating'
+  | 'usage';
+
+export type ImageOrientation = 'landscape' | 'portrait' | 'square';
+
+export interface WallpaperQuery {
+  name?: string;
+  kinds?: MediaKind[];
+  tags?: string[];
+  favorites_only?: boolean;
+  min_rating?: number;
+  min_size?: number;
+  max_size?: number;
+  min_width?: number;
+  max_width?: number;
+  min_height?: number;
+  max_height?: number;
+  min_aspect_ratio?: number;
+  max_aspect_ratio?: number;
+  orientation?: ImageOrientation;
+  // Inclusive YYYY-MM-DD bounds
+  date_from?: string;
+  date_to?: string;
+  // #rrggbb; matches wallpapers with a dominant color within color_distance (CIE76)
+  color?: string;
+  color_distance?: number;
+  // Hue range in degrees; a range like 330 to 30 wraps through red
+  min_hue?: number;
+  max_hue?: number;
+  fits_monitor?: boolean;
+  sort_by?: LibrarySort;
+  descending?: boolean;
+  offset?: number;
+  limit?: number;
+}
+
+export interface WallpaperQueryPage {
+  items: WallpaperInfo[];
+  offset: number;
+  total: number;
 }
 
 export interface WallpaperSettings {
@@ -10,6 +123,95 @@ export interface WallpaperSettings {
   interval: number;
   randomOrder: boolean;
   pauseOnFullscreen: boolean;
+  collapseDuplicates?: boolean;
+  filter?: SlideshowFilter;
+  weighting?: SlideshowWeighting;
+}
+
+// How shuffle weighs its picks while enabled
+export interface SlideshowWeighting {
+  enabled: boolean;
+  // Per star above or below three
+  ratingFactor: number;
+  favoriteFactor: number;
+  // Hours a wallpaper takes to regain its full weight after being shown
+  recencyHours: number;
+  frequencyPenalty: number;
+}
+
+export interface UsageStats {
+  times_shown: number;
+  last_shown_at?: number | null;
+}
+
+export interface PickWeight {
+  path: string;
+  name: string;
+  usage: UsageStats;
+  rating_factor: number;
+  favorite_factor: number;
+  recency_factor: number;
+  frequency_factor: number;
+  weight: number;
+  probability: number;
+}
+
+export interface SlideshowStatus {
+  enabled: boolean;
+  interval: number;
+  collection_id?: number | null;
+  current?: string | null;
+  // Unix seconds
+  next_change_at?: number | null;
+  can_go_back: boolean;
+}
+
+export interface HistoryEntry {
+  path: string;
+  // Unix seconds
+  applied_at: number;
+}
+
+// Oldest first; the entry at position is on screen
+export interface WallpaperHistory {
+  entries: HistoryEntry[];
+  position: number;
+}
+
+// Off, every screen shares one rotation; on, each runs its own slideshow
+export interface MonitorSlideshows {
+  independent: boolean;
+  monitors: MonitorSlideshow[];
+}
+
+export interface MonitorSlideshow {
+  monitor: string;
+  collection_id?: number | null;
+  settings: WallpaperSettings;
+  current?: string | null;
+}
+
+// Position and size in physical pixels
+export interface MonitorInfo {
+  name: string;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  primary: boolean;
+  current?: string | null;
+  // Unix seconds
+  next_change_at?: number | null;
+}
+
+export interface SlideshowFilter {
+  tags: string[];
+  favoritesOnly: boolean;
+  minRating?: number;
+  color?: string;
+  colorDistance?: number;
+  minHue?: number;
+  maxHue?: number;
 }
 
 export interface DateWidgetSettings {
@@ -34,6 +236,87 @@ export interface AppPersistentState {
   wallpaper_settings?: WallpaperSettings;
   wallpaper_list: WallpaperInfo[];
   autostart_enabled: boolean;
+  library_folders: LibraryFolder[];
+  active_collection?: number | null;
+  schedule?: ScheduleRule[];
+  active_solar_set?: number | null;
+  active_dynamic_set?: number | null;
+  monitor_slideshows?: MonitorSlideshows;
+}
+
+export type SymlinkPolicy = 'skip' | 'files_only' | 'follow';
+
+export interface ScanOptions {
+  max_depth: number | null;
+  symlink_policy: SymlinkPolicy;
+  include_patterns: string[];
+  exclude_patterns: string[];
+  include_hidden: boolean;
+  min_size?: number;
+  max_size?: number;
+  min_width?: number;
+  max_width?: number;
+  min_height?: number;
+  max_height?: number;
+}
+
+export interface LibraryFolder {
+  path: string;
+  scan_options: ScanOptions;
+  watched: boolean;
+}
+
+export interface LibraryChange {
+  added: WallpaperInfo[];
+  updated: WallpaperInfo[];
+  removed: string[];
+}
+
+export interface RelinkedWallpaper {
+  from: string;
+  to: string;
+}
+
+export interface ReconcileReport {
+  checked: number;
+  relinked: RelinkedWallpaper[];
+  missing: string[];
+}
+
+export type DuplicateKind = 'exact' | 'similar';
+
+export interface DuplicateItem {
+  wallpaper: WallpaperInfo;
+  similarity: number;
+}
+
+export interface DuplicateGroup {
+  kind: DuplicateKind;
+  best: string;
+  similarity: number;
+  items: DuplicateItem[];
+}
+
+export type ScanStatus = 'running' | 'completed' | 'cancelled';
+
+export interface ScanProgress {
+  scan_id: string;
+  discovered: number;
+  processed: number;
+  matched: number;
+  status: ScanStatus;
+}
+
+export interface ScanBatch {
+  scan_id: string;
+  wallpapers: WallpaperInfo[];
+}
+
+export interface WallpaperPage {
+  items: WallpaperInfo[];
+  offset: number;
+  total: number;
+  finished: boolean;
 }
 
 export interface FontOption {
@@ -41,4 +324,85 @@ export interface FontOption {
   value: string;
   type: 'google' | 'local';
   url?: string;
+}
+export type ScheduleTime =
+  // Local "HH:MM"; an end before the start runs past midnight. Days are 0 (Sunday) to 6
+  | { kind: "range"; start: string; end: string; days: number[] }
+  | { kind: "cron"; expression: string };
+
+export type ScheduleTarget =
+  | { kind: "wallpaper"; path: string }
+  | { kind: "collection"; id: number }
+  | { kind: "next" };
+
+export interface ScheduleRule {
+  name: string;
+  enabled: boolean;
+  when: ScheduleTime;
+  target: ScheduleTarget;
+}
+
+export interface SchedulePreview {
+  rule?: ScheduleRule | null;
+  collection?: Collection | null;
+  wallpaper?: WallpaperInfo | null;
+  // Unix seconds
+  next_change_at?: number | null;
+}
+
+export type SolarPhase = "day" | "golden_hour" | "dusk" | "night";
+
+export interface SolarVariants {
+  day?: string | null;
+  golden_hour?: string | null;
+  dusk?: string | null;
+  night?: string | null;
+}
+
+export interface SolarSet {
+  id: number;
+  name: string;
+  latitude: number;
+  longitude: number;
+  location?: string | null;
+  variants: SolarVariants;
+}
+
+export interface SolarStatus {
+  // Degrees; azimuth is clockwise from north
+  position: { elevation: number; azimuth: number };
+  phase: SolarPhase;
+  wallpaper?: string | null;
+  // Unix seconds
+  next_change_at?: number | null;
+  next_phase?: SolarPhase | null;
+}
+
+export interface City {
+  name: string;
+  country: string;
+  latitude: number;
+  longitude: number;
+}
+
+export interface DynamicFrame {
+  path: string;
+  // Seconds into the cycle where the picture is fully shown
+  at: number;
+  // Seconds of crossfade from the previous picture, ending at `at`
+  transition: number;
+}
+
+export interface DynamicSet {
+  id: number;
+  name: string;
+  // Seconds; a day-long cycle keys the frames to the time of day
+  period: number;
+  frames: DynamicFrame[];
+}
+
+export interface GnomeImport {
+  dynamic_sets: DynamicSet[];
+  collection?: Collection | null;
+  skipped: string[];
 }
\ No newline at end of file
@@ -0,0 +1,53 @@
+use tauri::{AppHandle, Wry};
+use crate::slideshow::{next_wallpaper, previous_wallpaper, reapply_from_history, wallpaper_history};
+
+// Slideshow controls for scripts and key bindings. A running instance receives them through the
+// single-instance plugin, so `wallora --next` works whether or not the app is already up.
+//   --next          the next wallpaper, from the history first and then the rotation
+//   --previous      the wallpaper shown before this one
+//   --reapply <n>   the wallpaper applied n changes ago; 0 is the latest
+enum CliAction {
+    Next,
+    Previous,
+    Reapply(usize),
+}
+
+// Start the action the arguments ask for, if any. Returns whether there was one.
+pub fn handle_cli_args(app: &AppHandle<Wry>, args: &[String]) -> bool {
+    let Some(action) = parse_cli_action(args) else {
+        return false;
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match action {
+            CliAction::Next => next_wallpaper(&app).await.map(|_| ()),
+            CliAction::Previous => previous_wallpaper(&app).await.map(|_| ()),
+            CliAction::Reapply(back) => {
+                let entries = wallpaper_history(&app).entries;
+                match entries.len().checked_sub(back + 1).map(|index| &entries[index]) {
+                    Some(entry) => reapply_from_history(&app, entry.applied_at, &entry.path).await.map(|_| ()),
+                    None => Err(format!("The history only has {} wallpapers", entries.len())),
+                }
+            }
+        };
+        if let Err(_e) = result {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to run command line action: {}", _e);
+        }
+    });
+    true
+}
+
+fn parse_cli_action(args: &[String]) -> Option<CliAction> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--next" => return Some(CliAction::Next),
+            "--previous" => return Some(CliAction::Previous),
+            "--reapply" => return args.next().and_then(|back| back.parse().ok()).map(CliAction::Reapply),
+            _ => {}
+        }
+    }
+    None
+}
\ No newline at end of file
@@ -1,7 +1,11 @@
-use tauri::{AppHandle, Wry};
+use tauri::{AppHandle, Manager, Wry};
 use tauri_plugin_store::StoreExt;
 use tauri_plugin_autostart::ManagerExt;
-use crate::types::{AppPersistentState, DateWidgetSettings, WallpaperSettings, WallpaperInfo};
+use crate::types::{AppPersistentState, DateWidgetSettings, WallpaperSettings};
+use crate::library::db::with_library_db;
+use crate::state::AppState;
+use crate::slideshow::{note_wallpaper_applied, wake_slideshow, wallpaper_history};
+use crate::tray::refresh_tray_menu;
 
 const STORE_FILE: &str = "wallora-settings.json";
 const STATE_KEY: &str = "app_state";
@@ -11,19 +15,34 @@ pub async fn save_app_state(
     app: AppHandle<Wry>,
     state: AppPersistentState,
 ) -> Result<String, String> {
-    let store = app.store(STORE_FILE).map_err(|e| format!("Failed to access store: {}", e))?;
-    
-    let state_value = serde_json::to_value(&state)
-        .map_err(|e| format!("Failed to serialize state: {}", e))?;
-    
-    store.set(STATE_KEY, state_value);
-    store.save().map_err(|e| format!("Failed to save store: {}", e))?;
-    
+    let app_state = app.state::<AppState>();
+    let _guard = app_state.app_state_lock.lock().await;
+    write_app_state(&app, &state)?;
     Ok("App state saved successfully".to_string())
 }
 
 #[tauri::command]
 pub async fn load_app_state(app: AppHandle<Wry>) -> Result<AppPersistentState, String> {
+    read_app_state(&app)
+}
+
+// Read, change and write back the persisted state while holding its lock, so commands and
+// background tasks updating it at the same time do not overwrite each other's changes
+pub async fn update_app_state<T>(
+    app: &AppHandle<Wry>,
+    update: impl FnOnce(&mut AppPersistentState) -> T,
+) -> Result<T, String> {
+    let app_state = app.state::<AppState>();
+    let _guard = app_state.app_state_lock.lock().await;
+
+    let mut current_state = read_app_state(app)?;
+    let result = update(&mut current_state);
+    write_app_state(app, &current_state)?;
+    Ok(result)
+}
+
+// The store is read and written synchronously, so setup can use these before anything async runs
+pub fn read_app_state(app: &AppHandle<Wry>) -> Result<AppPersistentState, String> {
     let store = app.store(STORE_FILE).map_err(|e| format!("Failed to access store: {}", e))?;
     
     match store.get(STATE_KEY) {
@@ -35,6 +54,16 @@ pub async fn load_app_state(app: AppHandle<Wry>) -> Result<AppPersistentState, S
     }
 }
 
+pub fn write_app_state(app: &AppHandle<Wry>, state: &AppPersistentState) -> Result<(), String> {
+    let store = app.store(STORE_FILE).map_err(|e| format!("Failed to access store: {}", e))?;
+    
+    let state_value = serde_json::to_value(state)
+        .map_err(|e| format!("Failed to serialize state: {}", e))?;
+    
+    store.set(STATE_KEY, state_value);
+    store.save().map_err(|e| format!("Failed to save store: {}", e))
+}
+
 #[tauri::command]
 pub async fn set_autostart(app: AppHandle<Wry>, enable: bool) -> Result<String, String> {
     let autostart_manager = app.autolaunch();
@@ -46,9 +75,7 @@ pub async fn set_autostart(app: AppHandle<Wry>, enable: bool) -> Result<String,
     }
     
     // Update the persistent state
-    let mut current_state = load_app_state(app.clone()).await.unwrap_or_default();
-    current_state.autostart_enabled = enable;
-    save_app_state(app, current_state).await?;
+    update_app_state(&app, |state| state.autostart_enabled = enable).await?;
     
     Ok(format!("Autostart {}", if enable { "enabled" } else { "disabled" }))
 }
@@ -59,16 +86,31 @@ pub async fn get_autostart_status(app: AppHandle<Wry>) -> Result<bool, String> {
     autostart_manager.is_enabled().map_err(|e| format!("Failed to check autostart status: {}", e))
 }
 
+// Count a slideshow or manual change in the usage stats. Restores and solar or dynamic set
+// keyframes are left out, so they do not make a wallpaper look overplayed.
+pub fn record_wallpaper_shown(app: &AppHandle<Wry>, path: &str) {
+    // Usage stats are best effort; the wallpaper is already showing
+    let _ = with_library_db(app, |db| db.record_shown(path));
+}
+
 #[tauri::command]
 pub async fn update_wallpaper_state(
     app: AppHandle<Wry>,
     wallpaper_path: String,
     file_type: String,
 ) -> Result<String, String> {
-    let mut current_state = load_app_state(app.clone()).await.unwrap_or_default();
-    current_state.last_wallpaper_path = Some(wallpaper_path);
-    current_state.last_wallpaper_file_type = Some(file_type);
-    save_app_state(app, current_state).await
+    note_wallpaper_applied(&app, &wallpaper_path);
+
+    let history = wallpaper_history(&app);
+    let result = update_app_state(&app, |state| {
+        state.last_wallpaper_path = Some(wallpaper_path);
+        state.last_wallpaper_file_type = Some(file_type);
+        state.wallpaper_history = history;
+    })
+    .await;
+    // The tray lists recent wallpapers
+    refresh_tray_menu(&app).await;
+    result.map(|_| "App state saved successfully".to_string())
 }
 
 #[tauri::command]
@@ -76,9 +118,8 @@ pub async fn update_date_widget_state(
     app: AppHandle<Wry>,
     settings: DateWidgetSettings,
 ) -> Result<String, String> {
-    let mut current_state = load_app_state(app.clone()).await.unwrap_or_default();
-    current_state.date_widget_settings = Some(settings);
-    save_app_state(app, current_state).await
+    update_app_state(&app, |state| state.date_widget_settings = Some(settings)).await?;
+    Ok("App state saved successfully".to_string())
 }
 
 #[tauri::command]
@@ -86,17 +127,7 @@ pub async fn update_wallpaper_settings_state(
     app: AppHandle<Wry>,
     settings: WallpaperSettings,
 ) -> Result<String, String> {
-    let mut current_state = load_app_state(app.clone()).await.unwrap_or_default();
-    current_state.wallpaper_settings = Some(settings);
-    save_app_state(app, current_state).await
-}
-
-#[tauri::command]
-pub async fn save_wallpaper_list(
-    app: AppHandle<Wry>,
-    wallpapers: Vec<WallpaperInfo>,
-) -> Result<String, String> {
-    let mut current_state = load_app_state(app.clone()).await.unwrap_or_default();
-    current_state.wallpaper_list = wallpapers;
-    save_app_state(app, current_state).await
+    let result = update_app_state(&app, |state| state.wallpaper_settings = Some(settings)).await;
+    wake_slideshow(&app);
+    result.map(|_| "App state saved successfully".to_string())
 }
\ No newline at end of file
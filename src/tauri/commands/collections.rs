@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Wry};
+use crate::commands::load_app_state;
+use crate::library::collections::{activate_collection, read_collection_file, slideshow_scope, write_collection_file};
+use crate::library::db::with_library_db;
+use crate::slideshow::wake_slideshow;
+use crate::tray::refresh_tray_menu;
+use crate::types::{Collection, CollectionImport, WallpaperInfo, WallpaperSettings};
+
+#[tauri::command]
+pub async fn get_collections(app: AppHandle<Wry>) -> Result<Vec<Collection>, String> {
+    with_library_db(&app, |db| db.list_collections())
+}
+
+#[tauri::command]
+pub async fn create_collection(app: AppHandle<Wry>, name: String) -> Result<Collection, String> {
+    let collection = with_library_db(&app, |db| db.create_collection(&name))?;
+    refresh_tray_menu(&app).await;
+    Ok(collection)
+}
+
+#[tauri::command]
+pub async fn rename_collection(app: AppHandle<Wry>, id: i64, name: String) -> Result<(), String> {
+    with_library_db(&app, |db| db.rename_collection(id, &name))?;
+    refresh_tray_menu(&app).await;
+    Ok(())
+}
+
+// The wallpapers themselves stay in the library
+#[tauri::command]
+pub async fn delete_collection(app: AppHandle<Wry>, id: i64) -> Result<(), String> {
+    with_library_db(&app, |db| db.delete_collection(id))?;
+
+    if load_app_state(app.clone()).await?.active_collection == Some(id) {
+        activate_collection(app, None).await?;
+    } else {
+        refresh_tray_menu(&app).await;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_collection_wallpapers(app: AppHandle<Wry>, id: i64) -> Result<Vec<WallpaperInfo>, String> {
+    with_library_db(&app, |db| db.collection_wallpapers(id))
+}
+
+#[tauri::command]
+pub async fn add_to_collection(app: AppHandle<Wry>, id: i64, paths: Vec<String>) -> Result<usize, String> {
+    with_library_db(&app, |db| db.add_to_collection(id, &paths))
+}
+
+#[tauri::command]
+pub async fn remove_from_collection(app: AppHandle<Wry>, id: i64, paths: Vec<String>) -> Result<usize, String> {
+    with_library_db(&app, |db| db.remove_from_collection(id, &paths))
+}
+
+// `paths` move to the front in the given order; the rest of the collection follows
+#[tauri::command]
+pub async fn reorder_collection(app: AppHandle<Wry>, id: i64, paths: Vec<String>) -> Result<(), String> {
+    with_library_db(&app, |db| db.reorder_collection(id, &paths))
+}
+
+// Pass None to have the collection use the global slideshow settings
+#[tauri::command]
+pub async fn set_collection_settings(
+    app: AppHandle<Wry>,
+    id: i64,
+    settings: Option<WallpaperSettings>,
+) -> Result<(), String> {
+    with_library_db(&app, |db| db.set_collection_settings(id, settings.as_ref()))?;
+    wake_slideshow(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_active_collection(app: AppHandle<Wry>) -> Result<Option<Collection>, String> {
+    slideshow_scope(&app).await.map(|(collection, _)| collection)
+}
+
+// None switches the slideshow back to the whole library
+#[tauri::command]
+pub async fn set_active_collection(app: AppHandle<Wry>, id: Option<i64>) -> Result<Option<Collection>, String> {
+    activate_collection(app, id).await
+}
+
+// Writes an M3U playlist for .m3u/.m3u8 paths and JSON otherwise
+#[tauri::command]
+pub async fn export_collection(app: AppHandle<Wry>, id: i64, path: String) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || write_collection_file(&app, id, &PathBuf::from(path)))
+        .await
+        .map_err(|e| format!("Failed to export collection: {}", e))?
+}
+
+#[tauri::command]
+pub async fn import_collection(app: AppHandle<Wry>, path: String) -> Result<CollectionImport, String> {
+    let task_app = app.clone();
+    let import = tokio::task::spawn_blocking(move || read_collection_file(&task_app, &PathBuf::from(path)))
+        .await
+        .map_err(|e| format!("Failed to import collection: {}", e))??;
+
+    refresh_tray_menu(&app).await;
+    Ok(import)
+}
\ No newline at end of file
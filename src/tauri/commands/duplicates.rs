@@ -0,0 +1,59 @@
+use tauri::{AppHandle, Emitter, Wry};
+use crate::commands::app_thumbnail_root;
+use crate::library::db::with_library_db;
+use crate::library::duplicates::{best_wallpaper, find_duplicate_groups, index_library_hashes, DEFAULT_MAX_DISTANCE};
+use crate::library::watcher::LIBRARY_CHANGED_EVENT;
+use crate::types::{DuplicateGroup, LibraryChange};
+
+// Hash anything new, then group exact copies and visually similar wallpapers
+#[tauri::command]
+pub async fn find_duplicate_wallpapers(
+    app: AppHandle<Wry>,
+    max_distance: Option<u32>,
+) -> Result<Vec<DuplicateGroup>, String> {
+    let thumbnail_root = app_thumbnail_root(&app)?;
+
+    tokio::task::spawn_blocking(move || {
+        index_library_hashes(&app, &thumbnail_root)?;
+        find_duplicate_groups(&app, max_distance.unwrap_or(DEFAULT_MAX_DISTANCE))
+    })
+    .await
+    .map_err(|e| format!("Failed to run duplicate search: {}", e))?
+}
+
+// Keep one wallpaper out of `paths` (the highest resolution unless `keep` is given) and drop
+// the others from the library. Files on disk are left alone. Returns the path that was kept.
+#[tauri::command]
+pub async fn merge_duplicate_wallpapers(
+    app: AppHandle<Wry>,
+    paths: Vec<String>,
+    keep: Option<String>,
+) -> Result<String, String> {
+    let wallpapers = with_library_db(&app, |db| db.list_wallpapers())?;
+    let group: Vec<_> = wallpapers.iter().filter(|info| paths.contains(&info.path)).collect();
+
+    let keep = match keep {
+        Some(keep) if group.iter().any(|info| info.path == keep) => keep,
+        Some(keep) => return Err(format!("Not part of the group: {}", keep)),
+        None => best_wallpaper(group.iter().copied())
+            .map(|info| info.path.clone())
+            .ok_or_else(|| "None of the wallpapers are in the library".to_string())?,
+    };
+
+    let removed: Vec<String> = group.iter()
+        .map(|info| info.path.clone())
+        .filter(|path| *path != keep)
+        .collect();
+    // The kept entry may have picked up tags, a rating or flags from the others
+    let kept = with_library_db(&app, |db| {
+        db.merge_into(&keep, &removed)?;
+        db.get_wallpaper(&keep)
+    })?;
+
+    let _ = app.emit(LIBRARY_CHANGED_EVENT, LibraryChange {
+        updated: kept.into_iter().collect(),
+        removed,
+        ..Default::default()
+    });
+    Ok(keep)
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use tauri::{AppHandle, Wry};
+use crate::commands::load_app_state;
+use crate::library::db::with_library_db;
+use crate::slideshow::dynamic::{activate_dynamic_set, wake_dynamic};
+use crate::types::DynamicSet;
+
+#[tauri::command]
+pub async fn get_dynamic_sets(app: AppHandle<Wry>) -> Result<Vec<DynamicSet>, String> {
+    with_library_db(&app, |db| db.list_dynamic_sets())
+}
+
+// The id of `set` is ignored; frames come back sorted by keyframe
+#[tauri::command]
+pub async fn create_dynamic_set(app: AppHandle<Wry>, set: DynamicSet) -> Result<DynamicSet, String> {
+    with_library_db(&app, |db| db.create_dynamic_set(&set))
+}
+
+#[tauri::command]
+pub async fn update_dynamic_set(app: AppHandle<Wry>, set: DynamicSet) -> Result<DynamicSet, String> {
+    let set = with_library_db(&app, |db| db.update_dynamic_set(&set))?;
+    // Changed frames take effect on the active set right away
+    wake_dynamic(&app);
+    Ok(set)
+}
+
+#[tauri::command]
+pub async fn delete_dynamic_set(app: AppHandle<Wry>, id: i64) -> Result<(), String> {
+    with_library_db(&app, |db| db.delete_dynamic_set(id))?;
+
+    if load_app_state(app.clone()).await?.active_dynamic_set == Some(id) {
+        activate_dynamic_set(&app, None).await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_active_dynamic_set(app: AppHandle<Wry>) -> Result<Option<DynamicSet>, String> {
+    match load_app_state(app.clone()).await?.active_dynamic_set {
+        Some(id) => with_library_db(&app, |db| db.get_dynamic_set(id)),
+        None => Ok(None),
+    }
+}
+
+// None switches back to the regular slideshow
+#[tauri::command]
+pub async fn set_active_dynamic_set(app: AppHandle<Wry>, id: Option<i64>) -> Result<Option<DynamicSet>, String> {
+    activate_dynamic_set(&app, id).await
+}
\ No newline at end of file
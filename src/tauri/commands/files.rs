@@ -1,87 +1,216 @@
 use std::path::PathBuf;
-use walkdir::WalkDir;
-use crate::types::WallpaperInfo;
-use crate::utils::file_utils::get_supported_extensions;
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Emitter, State, Wry};
+use crate::library;
+use crate::library::watcher::{merge_wallpapers, restart_library_watcher, LIBRARY_CHANGED_EVENT};
+use crate::state::AppState;
+use crate::types::{LibraryChange, LibraryFolder, ScanOptions, WallpaperInfo, WallpaperPage, WallpaperSort};
+use crate::utils::scanner::{build_wallpaper_info, invalid_wallpaper_info, scan_directory, ScanFilter};
+use crate::commands::{load_app_state, update_app_state};
 
+// Undated wallpapers go after dated ones; ties fall back to the name
+pub fn sort_wallpapers(wallpapers: &mut [WallpaperInfo], sort_by: WallpaperSort) {
+    match sort_by {
+        WallpaperSort::Name => {
+            wallpapers.sort_by_cached_key(|wallpaper| wallpaper.name.to_lowercase());
+        }
+        WallpaperSort::CaptureDate => {
+            wallpapers.sort_by(|a, b| {
+                match (&a.captured_at, &b.captured_at) {
+                    (Some(a_date), Some(b_date)) => a_date.cmp(b_date),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => std::cmp::Ordering::Equal,
+                }
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            });
+        }
+    }
+}
+
+// Library folders are keyed by their canonical path so trailing separators and symlinks resolve to one entry
+pub fn library_folder_key(directory: &str) -> String {
+    std::fs::canonicalize(directory)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| directory.trim_end_matches(['/', '\\']).to_string())
+}
+
+// Page size used when the caller does not ask for one, and the most a single page may hold
+const DEFAULT_PAGE_SIZE: usize = 200;
+const MAX_PAGE_SIZE: usize = 1000;
+
+// Start scanning a folder in the background; batches and progress arrive as events tagged with the returned scan ID
 #[tauri::command]
-pub async fn get_wallpaper_files(directory: String) -> Result<Vec<WallpaperInfo>, String> {
-    let mut wallpapers = Vec::new();
-    let supported_extensions = get_supported_extensions();
+pub async fn start_library_scan(
+    app: AppHandle<Wry>,
+    directory: String,
+    sort_by: Option<WallpaperSort>,
+    options: Option<ScanOptions>,
+) -> Result<String, String> {
+    let folder_key = library_folder_key(&directory);
 
-    for entry in WalkDir::new(directory)
-        .follow_links(true)
-        .max_depth(2)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
-        if let Some(extension) = path.extension() {
-            if let Some(ext_str) = extension.to_str() {
-                let ext_lower = ext_str.to_lowercase();
-                if supported_extensions.contains(&ext_lower.as_str()) {
-                    if let Ok(metadata) = std::fs::metadata(path) {
-                        if metadata.is_file() {
-                            wallpapers.push(WallpaperInfo {
-                                path: path.to_string_lossy().to_string(),
-                                name: path.file_name()
-                                    .unwrap_or_default()
-                                    .to_string_lossy()
-                                    .to_string(),
-                                file_type: ext_lower,
-                                size: metadata.len(),
-                            });
-                        }
-                    }
+    // Explicit options are remembered for the folder; otherwise reuse what was saved last time
+    let options = match options {
+        Some(options) => {
+            update_app_state(&app, |state| {
+                match state.library_folders.iter_mut().find(|folder| folder.path == folder_key) {
+                    Some(folder) => folder.scan_options = options.clone(),
+                    None => state.library_folders.push(LibraryFolder {
+                        path: folder_key.clone(),
+                        scan_options: options.clone(),
+                        watched: false,
+                    }),
                 }
-            }
+            })
+            .await?;
+            options
         }
+        None => load_app_state(app.clone()).await.unwrap_or_default()
+            .library_folders
+            .into_iter()
+            .find(|folder| folder.path == folder_key)
+            .map(|folder| folder.scan_options)
+            .unwrap_or_default(),
+    };
+
+    let filter = ScanFilter::new(&options)?;
+    Ok(library::scan::start_library_scan(app, folder_key, filter, sort_by.unwrap_or_default()))
+}
+
+#[tauri::command]
+pub async fn cancel_library_scan(state: State<'_, AppState>, scan_id: String) -> Result<(), String> {
+    let scans = state.library_scans.lock().unwrap();
+    let scan = scans.get(&scan_id).ok_or_else(|| format!("Unknown scan: {}", scan_id))?;
+    scan.cancelled.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+// Read results of a running or finished scan a page at a time
+#[tauri::command]
+pub async fn get_library_scan_page(
+    state: State<'_, AppState>,
+    scan_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<WallpaperPage, String> {
+    let scan = state.library_scans.lock().unwrap()
+        .get(&scan_id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown scan: {}", scan_id))?;
+
+    // Read the flag first so a page marked finished never misses the last batch
+    let finished = scan.finished.load(Ordering::Acquire);
+    let results = scan.results.lock().unwrap();
+    let offset = offset.unwrap_or(0).min(results.len());
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    Ok(WallpaperPage {
+        items: results.iter().skip(offset).take(limit).cloned().collect(),
+        offset,
+        total: results.len(),
+        finished,
+    })
+}
+
+#[tauri::command]
+pub async fn get_library_folders(app: AppHandle<Wry>) -> Result<Vec<LibraryFolder>, String> {
+    Ok(load_app_state(app).await?.library_folders)
+}
+
+// Import a folder into the library and keep it in sync as files are added, changed or removed
+#[tauri::command]
+pub async fn watch_library_folder(
+    app: AppHandle<Wry>,
+    directory: String,
+    options: Option<ScanOptions>,
+) -> Result<LibraryChange, String> {
+    let folder_key = library_folder_key(&directory);
+    if !PathBuf::from(&folder_key).is_dir() {
+        return Err(format!("Folder does not exist: {}", directory));
     }
 
-    wallpapers.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    Ok(wallpapers)
+    let current_options = load_app_state(app.clone()).await.unwrap_or_default()
+        .library_folders
+        .into_iter()
+        .find(|folder| folder.path == folder_key)
+        .map(|folder| folder.scan_options);
+    let scan_options = options.or(current_options).unwrap_or_default();
+
+    let filter = ScanFilter::new(&scan_options)?;
+    let scan_root = PathBuf::from(&folder_key);
+    let wallpapers = tokio::task::spawn_blocking(move || scan_directory(&scan_root, &filter))
+        .await
+        .map_err(|e| format!("Failed to run folder scan: {}", e))?;
+
+    update_app_state(&app, |state| {
+        match state.library_folders.iter_mut().find(|folder| folder.path == folder_key) {
+            Some(folder) => {
+                folder.scan_options = scan_options;
+                folder.watched = true;
+            }
+            None => state.library_folders.push(LibraryFolder {
+                path: folder_key.clone(),
+                scan_options,
+                watched: true,
+            }),
+        }
+    })
+    .await?;
+
+    let mut change = LibraryChange::default();
+    merge_wallpapers(&app, wallpapers, &mut change)?;
+
+    restart_library_watcher(app.clone()).await?;
+    let _ = app.emit(LIBRARY_CHANGED_EVENT, change.clone());
+    Ok(change)
+}
+
+// Stop syncing a folder; wallpapers already imported from it stay in the list
+#[tauri::command]
+pub async fn unwatch_library_folder(app: AppHandle<Wry>, directory: String) -> Result<(), String> {
+    let folder_key = library_folder_key(&directory);
+    let watched = update_app_state(&app, |state| {
+        match state.library_folders.iter_mut().find(|folder| folder.path == folder_key) {
+            Some(folder) => {
+                folder.watched = false;
+                true
+            }
+            None => false,
+        }
+    })
+    .await?;
+
+    if !watched {
+        return Ok(());
+    }
+    restart_library_watcher(app).await
 }
 
 #[tauri::command]
 pub async fn get_files_info(file_paths: Vec<String>) -> Result<Vec<WallpaperInfo>, String> {
     let mut wallpapers = Vec::new();
-    let supported_extensions = get_supported_extensions();
 
     for file_path in file_paths {
         let path = PathBuf::from(&file_path);
         
         if !path.exists() {
-            #[cfg(debug_assertions)]
-            eprintln!("File does not exist: {}", file_path);
+            wallpapers.push(invalid_wallpaper_info(&path, "File does not exist"));
             continue;
         }
         
         if !path.is_file() {
-            #[cfg(debug_assertions)]
-            eprintln!("Path is not a file: {}", file_path);
+            wallpapers.push(invalid_wallpaper_info(&path, "Path is not a file"));
             continue;
         }
-        
-        if let Some(extension) = path.extension() {
-            if let Some(ext_str) = extension.to_str() {
-                let ext_lower = ext_str.to_lowercase();
-                if supported_extensions.contains(&ext_lower.as_str()) {
-                    if let Ok(metadata) = std::fs::metadata(&path) {
-                        wallpapers.push(WallpaperInfo {
-                            path: path.to_string_lossy().to_string(),
-                            name: path.file_name()
-                                .unwrap_or_default()
-                                .to_string_lossy()
-                                .to_string(),
-                            file_type: ext_lower,
-                            size: metadata.len(),
-                        });
-                    }
-                } else {
-                    #[cfg(debug_assertions)]
-                    eprintln!("Unsupported file type: {}", ext_str);
-                }
-            }
+
+        let info = build_wallpaper_info(&path);
+
+        #[cfg(debug_assertions)]
+        if let Some(reason) = &info.invalid_reason {
+            eprintln!("Rejected {}: {}", file_path, reason);
         }
+
+        wallpapers.push(info);
     }
 
     Ok(wallpapers)
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Wry};
+use crate::library::db::with_library_db;
+use crate::library::gnome::{
+    collection_to_dynamic_set, export_gnome_slideshow, find_gnome_backgrounds as find_backgrounds,
+    import_gnome_backgrounds as import_backgrounds, DEFAULT_EXPORT_TRANSITION,
+};
+use crate::tray::refresh_tray_menu;
+use crate::types::GnomeImport;
+
+#[tauri::command]
+pub async fn find_gnome_backgrounds(app: AppHandle<Wry>) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || find_backgrounds(&app))
+        .await
+        .map_err(|e| format!("Failed to look for GNOME backgrounds: {}", e))
+}
+
+// Takes a slideshow XML or a gnome-background-properties list
+#[tauri::command]
+pub async fn import_gnome_backgrounds(app: AppHandle<Wry>, path: String) -> Result<GnomeImport, String> {
+    let task_app = app.clone();
+    let import = tokio::task::spawn_blocking(move || import_backgrounds(&task_app, &PathBuf::from(path)))
+        .await
+        .map_err(|e| format!("Failed to import GNOME backgrounds: {}", e))??;
+
+    if import.collection.is_some() {
+        refresh_tray_menu(&app).await;
+    }
+    Ok(import)
+}
+
+// `transition` is the crossfade between pictures in seconds; `register` lists the slideshow in
+// GNOME Settings
+#[tauri::command]
+pub async fn export_collection_gnome(
+    app: AppHandle<Wry>,
+    id: i64,
+    path: String,
+    transition: Option<u32>,
+    register: bool,
+) -> Result<(), String> {
+    let set = collection_to_dynamic_set(&app, id, transition.unwrap_or(DEFAULT_EXPORT_TRANSITION)).await?;
+    tokio::task::spawn_blocking(move || export_gnome_slideshow(&app, &set, &PathBuf::from(path), register))
+        .await
+        .map_err(|e| format!("Failed to export collection: {}", e))?
+}
+
+#[tauri::command]
+pub async fn export_dynamic_set_gnome(app: AppHandle<Wry>, id: i64, path: String, register: bool) -> Result<(), String> {
+    let set = with_library_db(&app, |db| db.get_dynamic_set(id))?
+        .ok_or_else(|| format!("Dynamic set {} does not exist", id))?;
+    tokio::task::spawn_blocking(move || export_gnome_slideshow(&app, &set, &PathBuf::from(path), register))
+        .await
+        .map_err(|e| format!("Failed to export dynamic set: {}", e))?
+}
\ No newline at end of file
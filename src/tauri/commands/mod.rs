@@ -3,9 +3,29 @@ pub mod window;
 pub mod files;
 pub mod date_widget;
 pub mod app_state;
+pub mod thumbnail;
+pub mod wallpaper_list;
+pub mod duplicates;
+pub mod collections;
+pub mod slideshow;
+pub mod schedule;
+pub mod solar;
+pub mod dynamic;
+pub mod gnome;
+pub mod monitors;
 
 pub use wallpaper::*;
 pub use window::*;
 pub use files::*;
 pub use date_widget::*;
-pub use app_state::*;
\ No newline at end of file
+pub use app_state::*;
+pub use thumbnail::*;
+pub use wallpaper_list::*;
+pub use duplicates::*;
+pub use collections::*;
+pub use slideshow::*;
+pub use schedule::*;
+pub use solar::*;
+pub use dynamic::*;
+pub use gnome::*;
+pub use monitors::*;
\ No newline at end of file
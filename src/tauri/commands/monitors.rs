@@ -0,0 +1,35 @@
+use tauri::{AppHandle, Wry};
+use crate::commands::load_app_state;
+use crate::slideshow::monitors::{monitor_infos, next_screen_wallpaper, set_independent_screens, update_screen_slideshow};
+use crate::types::{MonitorInfo, MonitorSlideshows, WallpaperSettings};
+
+#[tauri::command]
+pub async fn get_monitors(app: AppHandle<Wry>) -> Result<Vec<MonitorInfo>, String> {
+    monitor_infos(&app).await
+}
+
+#[tauri::command]
+pub async fn get_monitor_slideshows(app: AppHandle<Wry>) -> Result<MonitorSlideshows, String> {
+    Ok(load_app_state(app).await?.monitor_slideshows)
+}
+
+// Off, every screen shares one rotation; on, each runs its own slideshow
+#[tauri::command]
+pub async fn set_monitors_independent(app: AppHandle<Wry>, independent: bool) -> Result<MonitorSlideshows, String> {
+    set_independent_screens(&app, independent).await
+}
+
+#[tauri::command]
+pub async fn update_monitor_slideshow(
+    app: AppHandle<Wry>,
+    monitor: String,
+    collection_id: Option<i64>,
+    settings: WallpaperSettings,
+) -> Result<MonitorSlideshows, String> {
+    update_screen_slideshow(&app, monitor, collection_id, settings).await
+}
+
+#[tauri::command]
+pub async fn next_monitor_wallpaper(app: AppHandle<Wry>, monitor: String) -> Result<Vec<MonitorInfo>, String> {
+    next_screen_wallpaper(&app, &monitor).await
+}
\ No newline at end of file
@@ -0,0 +1,25 @@
+use tauri::{AppHandle, Wry};
+use crate::commands::{load_app_state, update_app_state};
+use crate::slideshow::schedule::{preview_schedule as preview, validate_schedule, wake_schedule};
+use crate::types::{ScheduleRule, SchedulePreview};
+
+#[tauri::command]
+pub async fn get_schedule(app: AppHandle<Wry>) -> Result<Vec<ScheduleRule>, String> {
+    Ok(load_app_state(app).await?.schedule)
+}
+
+#[tauri::command]
+pub async fn set_schedule(app: AppHandle<Wry>, rules: Vec<ScheduleRule>) -> Result<(), String> {
+    validate_schedule(&rules)?;
+
+    update_app_state(&app, |state| state.schedule = rules).await?;
+
+    wake_schedule(&app);
+    Ok(())
+}
+
+// `at` is in unix seconds
+#[tauri::command]
+pub async fn preview_schedule(app: AppHandle<Wry>, at: i64) -> Result<SchedulePreview, String> {
+    preview(&app, at).await
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+use tauri::{AppHandle, Wry};
+use crate::slideshow::{
+    next_wallpaper, previous_wallpaper, reapply_from_history, set_auto_change, slideshow_probabilities, slideshow_status,
+    wallpaper_history,
+};
+use crate::types::{PickWeight, SlideshowStatus, WallpaperHistory};
+
+// Starting and stopping turn auto-change on or off in the settings in effect, so it sticks across restarts
+#[tauri::command]
+pub async fn start_slideshow(app: AppHandle<Wry>) -> Result<SlideshowStatus, String> {
+    set_auto_change(&app, true).await
+}
+
+#[tauri::command]
+pub async fn stop_slideshow(app: AppHandle<Wry>) -> Result<SlideshowStatus, String> {
+    set_auto_change(&app, false).await
+}
+
+#[tauri::command]
+pub async fn next_slideshow_wallpaper(app: AppHandle<Wry>) -> Result<SlideshowStatus, String> {
+    next_wallpaper(&app).await
+}
+
+#[tauri::command]
+pub async fn previous_slideshow_wallpaper(app: AppHandle<Wry>) -> Result<SlideshowStatus, String> {
+    previous_wallpaper(&app).await
+}
+
+#[tauri::command]
+pub async fn get_slideshow_status(app: AppHandle<Wry>) -> Result<SlideshowStatus, String> {
+    Ok(slideshow_status(&app).await)
+}
+
+#[tauri::command]
+pub async fn get_wallpaper_history(app: AppHandle<Wry>) -> Result<WallpaperHistory, String> {
+    Ok(wallpaper_history(&app))
+}
+
+#[tauri::command]
+pub async fn reapply_history_wallpaper(
+    app: AppHandle<Wry>,
+    applied_at: i64,
+    path: String,
+) -> Result<SlideshowStatus, String> {
+    reapply_from_history(&app, applied_at, &path).await
+}
+
+// For tuning the weighting: how likely each candidate is to come up next
+#[tauri::command]
+pub async fn get_slideshow_probabilities(app: AppHandle<Wry>) -> Result<Vec<PickWeight>, String> {
+    slideshow_probabilities(&app).await
+}
\ No newline at end of file
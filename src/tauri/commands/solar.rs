@@ -0,0 +1,61 @@
+use tauri::{AppHandle, Wry};
+use crate::commands::load_app_state;
+use crate::library::db::with_library_db;
+use crate::slideshow::schedule::wake_schedule;
+use crate::slideshow::solar::{activate_solar_set, solar_status};
+use crate::types::{City, SolarSet, SolarStatus};
+use crate::utils::cities::search_cities as find_cities;
+
+#[tauri::command]
+pub async fn get_solar_sets(app: AppHandle<Wry>) -> Result<Vec<SolarSet>, String> {
+    with_library_db(&app, |db| db.list_solar_sets())
+}
+
+// The id of `set` is ignored
+#[tauri::command]
+pub async fn create_solar_set(app: AppHandle<Wry>, set: SolarSet) -> Result<SolarSet, String> {
+    with_library_db(&app, |db| db.create_solar_set(&set))
+}
+
+#[tauri::command]
+pub async fn update_solar_set(app: AppHandle<Wry>, set: SolarSet) -> Result<(), String> {
+    with_library_db(&app, |db| db.update_solar_set(&set))?;
+    // New coordinates or pictures take effect on the active set right away
+    wake_schedule(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_solar_set(app: AppHandle<Wry>, id: i64) -> Result<(), String> {
+    with_library_db(&app, |db| db.delete_solar_set(id))?;
+
+    if load_app_state(app.clone()).await?.active_solar_set == Some(id) {
+        activate_solar_set(&app, None).await?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_active_solar_set(app: AppHandle<Wry>) -> Result<Option<SolarSet>, String> {
+    match load_app_state(app.clone()).await?.active_solar_set {
+        Some(id) => with_library_db(&app, |db| db.get_solar_set(id)),
+        None => Ok(None),
+    }
+}
+
+// None switches back to the regular slideshow
+#[tauri::command]
+pub async fn set_active_solar_set(app: AppHandle<Wry>, id: Option<i64>) -> Result<Option<SolarSet>, String> {
+    activate_solar_set(&app, id).await
+}
+
+// Where the sun is for a set right now, and when its picture changes next
+#[tauri::command]
+pub async fn get_solar_status(app: AppHandle<Wry>, id: i64) -> Result<SolarStatus, String> {
+    solar_status(&app, id).await
+}
+
+#[tauri::command]
+pub async fn search_cities(query: String) -> Result<Vec<City>, String> {
+    Ok(find_cities(&query))
+}
\ No newline at end of file
@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Wry};
+use crate::utils::thumbnails::get_or_create_cached_thumbnail;
+
+pub fn app_thumbnail_root(app: &AppHandle<Wry>) -> Result<PathBuf, String> {
+    app.path().app_cache_dir()
+        .map(|cache| cache.join("thumbnails"))
+        .map_err(|e| format!("Failed to resolve cache directory: {}", e))
+}
+
+#[tauri::command]
+pub async fn get_thumbnail(app: AppHandle<Wry>, path: String, size: u32) -> Result<String, String> {
+    let app_root = app_thumbnail_root(&app)?;
+
+    // Thumbnail generation decodes full images and may shell out to ffmpeg
+    tokio::task::spawn_blocking(move || {
+        let thumbnail = get_or_create_cached_thumbnail(&app_root, &PathBuf::from(&path), size)?;
+        Ok(thumbnail.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Failed to run thumbnail task: {}", e))?
+}
\ No newline at end of file
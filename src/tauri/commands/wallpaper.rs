@@ -1,83 +1,150 @@
-use std::path::PathBuf;
-use tauri::{AppHandle, State, Wry};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Monitor, State, Wry};
+use crate::slideshow::monitors::{monitor_name, uncover_screens};
 use crate::state::AppState;
-use crate::utils::file_utils::{get_mime_type, is_gif_type};
-use crate::commands::update_wallpaper_state;
+use crate::types::MediaKind;
+use crate::utils::media::sniff_media;
+use crate::utils::image_cache::{normalize_for_backend, NormalizeRequest};
+use crate::utils::image_metadata::read_image_metadata;
+use crate::utils::file_utils::modified_secs;
+use crate::library::db::with_library_db;
+use crate::platform::static_backends;
+use crate::commands::{record_wallpaper_shown, update_wallpaper_state};
 use tauri::Manager;
 
 #[cfg(target_os = "windows")]
 use crate::platform::windows::set_wallpaper_behind_desktop_sync;
 
 #[tauri::command]
-pub async fn set_static_wallpaper(file_path: String) -> Result<String, String> {
+pub async fn set_static_wallpaper(app: AppHandle<Wry>, file_path: String) -> Result<String, String> {
+    let result = apply_static_wallpaper(app.clone(), file_path.clone()).await?;
+    // Picked by hand, so it counts as shown
+    record_wallpaper_shown(&app, &file_path);
+    Ok(result)
+}
+
+// Show an image as the wallpaper and remember it, without counting it as shown
+async fn apply_static_wallpaper(app: AppHandle<Wry>, file_path: String) -> Result<String, String> {
     let path = PathBuf::from(&file_path);
     
     if !path.exists() {
         return Err("File does not exist".to_string());
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        use std::ffi::CString;
-        use winapi::um::winuser::{SystemParametersInfoA, SPI_SETDESKWALLPAPER, SPIF_UPDATEINIFILE, SPIF_SENDCHANGE};
-        
-        let path_cstring = CString::new(file_path.clone())
-            .map_err(|_| "Invalid file path".to_string())?;
-        
-        unsafe {
-            let result = SystemParametersInfoA(
-                SPI_SETDESKWALLPAPER,
-                0,
-                path_cstring.as_ptr() as *mut _,
-                SPIF_UPDATEINIFILE | SPIF_SENDCHANGE,
-            );
-            
-            if result == 0 {
-                return Err("Failed to set wallpaper".to_string());
-            }
-        }
+    let detection = sniff_media(&path);
+    if !detection.is_valid {
+        return Err(format!(
+            "Cannot use {}: {}",
+            file_path,
+            detection.invalid_reason.as_deref().unwrap_or("unsupported file")
+        ));
     }
 
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-        
-        let output = Command::new("osascript")
-            .arg("-e")
-            .arg(format!("tell application \"Finder\" to set desktop picture to POSIX file \"{}\"", file_path))
-            .output()
-            .map_err(|e| format!("Failed to execute osascript: {}", e))?;
-
-        if !output.status.success() {
-            return Err("Failed to set wallpaper on macOS".to_string());
-        }
-    }
+    let file_type = detection.file_type.clone();
+    show_static_image(&app, path, detection.file_type).await?;
+    // Screens running their own slideshows would cover it
+    uncover_screens(&app, &app.state::<AppState>())?;
 
-    #[cfg(target_os = "linux")]
-    {
-        use std::process::Command;
-        
-        let desktop_commands = [
-            ("gsettings", vec!["set", "org.gnome.desktop.background", "picture-uri", &format!("file://{}", file_path)]),
-            ("feh", vec!["--bg-fill", &file_path]),
-            ("nitrogen", vec!["--set-scaled", &file_path]),
-            ("xfconf-query", vec!["-c", "xfce4-desktop", "-p", "/backdrop/screen0/monitor0/workspace0/last-image", "-s", &file_path]),
-        ];
-
-        let mut success = false;
-        for (cmd, args) in desktop_commands.iter() {
-            if Command::new(cmd).args(args).output().is_ok() {
-                success = true;
-                break;
+    // Remember it so the wallpaper can be restored on the next start
+    let _ = update_wallpaper_state(app, file_path.clone(), file_type).await;
+    Ok(format!("Wallpaper set successfully: {}", file_path))
+}
+
+// Hand an image to the first static backend that takes it, without recording it as the
+// wallpaper. Crossfade frames go through here directly.
+pub async fn show_static_image(app: &AppHandle<Wry>, path: PathBuf, file_type: String) -> Result<(), String> {
+    let cache_dir = normalized_cache_dir(app)?;
+    let target_size = primary_monitor_size(app);
+    let known_hash = known_content_hash(app, &path);
+
+    // Decoding and resizing is CPU heavy, keep it off the async runtime
+    tokio::task::spawn_blocking(move || {
+        let metadata = read_image_metadata(&path, &file_type);
+        let mut last_error = None;
+
+        for backend in static_backends() {
+            if !(backend.is_available)() {
+                continue;
+            }
+
+            let request = NormalizeRequest {
+                source: &path,
+                file_type: &file_type,
+                formats: backend.formats,
+                target_size,
+                orientation: metadata.orientation,
+                content_hash: known_hash.as_deref(),
+            };
+            let display_path = match normalize_for_backend(&cache_dir, &request) {
+                Ok(display_path) => display_path,
+                Err(e) => {
+                    last_error = Some(format!("{}: {}", backend.name, e));
+                    continue;
+                }
+            };
+
+            match (backend.apply)(&display_path.to_string_lossy()) {
+                Ok(()) => {
+                    #[cfg(debug_assertions)]
+                    println!("Wallpaper applied with {} using {}", backend.name, display_path.display());
+
+                    return Ok(());
+                }
+                Err(e) => last_error = Some(format!("{}: {}", backend.name, e)),
             }
         }
-        
-        if !success {
-            return Err("Failed to set wallpaper on Linux".to_string());
-        }
-    }
 
-    Ok(format!("Wallpaper set successfully: {}", file_path))
+        Err(last_error.unwrap_or_else(|| "No wallpaper backend available".to_string()))
+    })
+    .await
+    .map_err(|e| format!("Failed to run wallpaper task: {}", e))?
+}
+
+// Decode and normalize an image into the cache for the backend that would show it, so showing
+// it later skips that work
+pub async fn prepare_static_image(app: &AppHandle<Wry>, path: PathBuf, file_type: String) -> Result<(), String> {
+    let cache_dir = normalized_cache_dir(app)?;
+    let target_size = primary_monitor_size(app);
+    let known_hash = known_content_hash(app, &path);
+
+    tokio::task::spawn_blocking(move || {
+        let Some(backend) = static_backends().iter().find(|backend| (backend.is_available)()) else {
+            return Ok(());
+        };
+        let request = NormalizeRequest {
+            source: &path,
+            file_type: &file_type,
+            formats: backend.formats,
+            target_size,
+            orientation: read_image_metadata(&path, &file_type).orientation,
+            content_hash: known_hash.as_deref(),
+        };
+        normalize_for_backend(&cache_dir, &request).map(|_| ())
+    })
+    .await
+    .map_err(|e| format!("Failed to run wallpaper task: {}", e))?
+}
+
+// Content hash the library stored for a file that has not changed since, if any
+pub fn known_content_hash(app: &AppHandle<Wry>, path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let path = path.to_string_lossy();
+    with_library_db(app, |db| db.stored_content_hash(&path, metadata.len(), modified_secs(&metadata)))
+        .ok()
+        .flatten()
+}
+
+pub fn normalized_cache_dir(app: &AppHandle<Wry>) -> Result<PathBuf, String> {
+    app.path().app_cache_dir()
+        .map(|dir| dir.join("normalized"))
+        .map_err(|e| format!("Failed to resolve cache directory: {}", e))
+}
+
+pub fn primary_monitor_size(app: &AppHandle<Wry>) -> Option<(u32, u32)> {
+    app.primary_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| (monitor.size().width, monitor.size().height))
 }
 
 #[tauri::command]
@@ -86,6 +153,19 @@ pub async fn create_video_wallpaper(
     file_path: String,
     converted_path: String,
     state: State<'_, AppState>,
+) -> Result<String, String> {
+    let result = apply_video_wallpaper(app.clone(), file_path.clone(), converted_path, &state).await?;
+    // Picked by hand, so it counts as shown
+    record_wallpaper_shown(&app, &file_path);
+    Ok(result)
+}
+
+// Play a video or animation as the wallpaper and remember it, without counting it as shown
+async fn apply_video_wallpaper(
+    app: AppHandle<Wry>,
+    file_path: String,
+    converted_path: String,
+    state: &AppState,
 ) -> Result<String, String> {
     let path = PathBuf::from(&file_path);
     
@@ -93,72 +173,78 @@ pub async fn create_video_wallpaper(
         return Err(format!("Video file does not exist: {}", file_path));
     }
 
-    // Create unique window label
-    let window_label = format!("wallpaper-{}", 
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-    );
-
-    // Handle video windows state
-    {
-        let mut video_windows = state.video_windows.lock().unwrap();
-        if let Some(existing_label) = video_windows.get("current") {
-            if let Some(window) = app.get_webview_window(existing_label) {
-                let _ = window.close();
-            }
-        }
-        video_windows.insert("current".to_string(), window_label.clone());
+    let detection = sniff_media(&path);
+    if !detection.is_valid {
+        return Err(format!(
+            "Cannot play {}: {}",
+            file_path,
+            detection.invalid_reason.as_deref().unwrap_or("unsupported file")
+        ));
     }
 
-    let file_extension = path.extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-    
-    let mime_type = if is_gif_type(&file_extension) {
-        "image/gif".to_string()
-    } else {
-        get_mime_type(&file_path)
-    };
+    let wallpaper_url = video_wallpaper_url(&converted_path, &detection.mime_type);
+    open_wallpaper_window(&app, state, wallpaper_url, None).await?;
 
-    // Create wallpaper window URL with parameters
-    let wallpaper_url = format!(
-        "wallpaper.html?path={}&type={}",
-        urlencoding::encode(&converted_path),
-        urlencoding::encode(&mime_type)
-    );
+    // Save wallpaper state
+    let _ = update_wallpaper_state(
+        app.clone(),
+        file_path.clone(),
+        detection.file_type,
+    ).await;
 
-    // Create wallpaper window
-    let video_window = tauri::WebviewWindowBuilder::new(
-        &app,
-        &window_label,
-        tauri::WebviewUrl::App(wallpaper_url.into()),
+    Ok(format!("Video wallpaper created successfully: {}", file_path))
+}
+
+// Wallpaper window URL with parameters
+pub fn video_wallpaper_url(converted_path: &str, mime_type: &str) -> String {
+    format!(
+        "wallpaper.html?path={}&type={}",
+        urlencoding::encode(converted_path),
+        urlencoding::encode(mime_type)
     )
-    .title("Video Wallpaper")
-    .minimizable(false)
-    .maximizable(false)
-    .closable(false)
-    .resizable(false)
-    .decorations(false)
-    .shadow(false)
-    .visible(false)
-    .skip_taskbar(true)
-    .fullscreen(true)
-    .build()
-    .map_err(|e| format!("Failed to create wallpaper window: {}", e))?;
+}
 
-    // Set window to always be on bottom
-    video_window.set_always_on_bottom(true)
-        .map_err(|e| format!("Failed to set always on bottom: {}", e))?;
+// A wallpaper window opened hidden ahead of time, holding playback until it is shown for `url`
+pub struct PreloadedWindow {
+    url: String,
+    label: String,
+}
+
+// Key in the window registry of the window covering every screen; windows on a single screen
+// are keyed by the monitor's name
+pub const ALL_MONITORS: &str = "all";
+
+// Open a fullscreen window behind the desktop icons showing `wallpaper_url`, replacing the one
+// open before on the same screen, or on every screen when `monitor` is None. Used for videos,
+// animations, live dynamic sets and per-screen slideshows. A window preloaded with the same URL
+// is shown instead of opening a new one.
+pub async fn open_wallpaper_window(
+    app: &AppHandle<Wry>,
+    state: &AppState,
+    wallpaper_url: String,
+    monitor: Option<&Monitor>,
+) -> Result<(), String> {
+    // Preloading only happens for the window covering every screen
+    let preloaded = match monitor {
+        Some(_) => None,
+        None => state.preloaded_window.lock().unwrap()
+            .take_if(|preloaded| preloaded.url == wallpaper_url)
+            .and_then(|preloaded| app.get_webview_window(&preloaded.label)),
+    };
+    let was_preloaded = preloaded.is_some();
+    let video_window = match preloaded {
+        Some(window) => window,
+        None => build_wallpaper_window(app, &wallpaper_url, monitor)?,
+    };
 
     // Show window after setup
     video_window.show()
         .map_err(|e| format!("Failed to show window: {}", e))?;
 
     // Wait for window to be ready
-    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    if !was_preloaded {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
 
     // Windows-specific: Use blocking task to avoid Send issues
     #[cfg(target_os = "windows")]
@@ -186,29 +272,107 @@ pub async fn create_video_wallpaper(
         }
     }
 
-    // Save wallpaper state
-    let file_extension = path.extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-    let _ = update_wallpaper_state(
-        app.clone(),
-        file_path.clone(),
-        file_extension,
-    ).await;
+    if was_preloaded {
+        let _ = video_window.eval("window.startPlayback && window.startPlayback()");
+    }
 
-    Ok(format!("Video wallpaper created successfully: {}", file_path))
+    // Handle video windows state, closing the old window only once the new one covers it
+    {
+        let key = monitor.map(monitor_name).unwrap_or_else(|| ALL_MONITORS.to_string());
+        let mut video_windows = state.video_windows.lock().unwrap();
+        if let Some(existing_label) = video_windows.get(&key) {
+            if let Some(window) = app.get_webview_window(existing_label) {
+                let _ = window.close();
+            }
+        }
+        video_windows.insert(key, video_window.label().to_string());
+    }
+
+    Ok(())
+}
+
+// Load `wallpaper_url` in a hidden wallpaper window with playback held, so that
+// open_wallpaper_window can show it right away. Replaces a window preloaded before.
+pub async fn preload_wallpaper_window(
+    app: &AppHandle<Wry>,
+    state: &AppState,
+    wallpaper_url: String,
+) -> Result<(), String> {
+    if state.preloaded_window.lock().unwrap().as_ref().is_some_and(|preloaded| preloaded.url == wallpaper_url) {
+        return Ok(());
+    }
+    discard_preloaded_window(app, state);
+
+    let window = build_wallpaper_window(app, &format!("{}&preload=1", wallpaper_url), None)?;
+    *state.preloaded_window.lock().unwrap() = Some(PreloadedWindow {
+        url: wallpaper_url,
+        label: window.label().to_string(),
+    });
+    Ok(())
+}
+
+pub fn discard_preloaded_window(app: &AppHandle<Wry>, state: &AppState) {
+    if let Some(preloaded) = state.preloaded_window.lock().unwrap().take()
+        && let Some(window) = app.get_webview_window(&preloaded.label)
+    {
+        let _ = window.close();
+    }
+}
+
+// A wallpaper window, set up but still hidden, on `monitor` or else wherever the system puts it
+fn build_wallpaper_window(
+    app: &AppHandle<Wry>,
+    wallpaper_url: &str,
+    monitor: Option<&Monitor>,
+) -> Result<tauri::WebviewWindow<Wry>, String> {
+    // Create unique window label; milliseconds since a preloaded window can open within the
+    // same second as the one it follows
+    let window_label = format!("wallpaper-{}", 
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+    );
+
+    // Create wallpaper window
+    let mut builder = tauri::WebviewWindowBuilder::new(
+        app,
+        &window_label,
+        tauri::WebviewUrl::App(wallpaper_url.into()),
+    )
+    .title("Video Wallpaper")
+    .minimizable(false)
+    .maximizable(false)
+    .closable(false)
+    .resizable(false)
+    .decorations(false)
+    .shadow(false)
+    .visible(false)
+    .skip_taskbar(true)
+    .fullscreen(true);
+
+    // Placed on the monitor, so going fullscreen fills that one
+    if let Some(monitor) = monitor {
+        let position = monitor.position().to_logical::<f64>(monitor.scale_factor());
+        let size = monitor.size().to_logical::<f64>(monitor.scale_factor());
+        builder = builder
+            .position(position.x, position.y)
+            .inner_size(size.width, size.height);
+    }
+
+    let video_window = builder.build()
+        .map_err(|e| format!("Failed to create wallpaper window: {}", e))?;
+
+    // Set window to always be on bottom
+    video_window.set_always_on_bottom(true)
+        .map_err(|e| format!("Failed to set always on bottom: {}", e))?;
+
+    Ok(video_window)
 }
 
 #[tauri::command]
 pub async fn stop_video_wallpaper(state: State<'_, AppState>, app: AppHandle<Wry>) -> Result<String, String> {
-    let mut video_windows = state.video_windows.lock().unwrap();
-    if let Some(window_label) = video_windows.get("current") {
-        if let Some(window) = app.get_webview_window(window_label) {
-            window.close().map_err(|e| format!("Failed to close video window: {}", e))?;
-        }
-        video_windows.remove("current");
-    }
+    close_wallpaper_window(&app, &state, ALL_MONITORS)?;
     
     // Clean up temporary files
     let temp_dir = std::env::temp_dir().join("wallpaper_manager");
@@ -217,4 +381,60 @@ pub async fn stop_video_wallpaper(state: State<'_, AppState>, app: AppHandle<Wry
     }
     
     Ok("Video wallpaper stopped and cleaned up".to_string())
+}
+
+// Close the wallpaper window registered under `key`, if there is one
+pub fn close_wallpaper_window(app: &AppHandle<Wry>, state: &AppState, key: &str) -> Result<(), String> {
+    let mut video_windows = state.video_windows.lock().unwrap();
+    if let Some(window_label) = video_windows.remove(key) {
+        if let Some(window) = app.get_webview_window(&window_label) {
+            window.close().map_err(|e| format!("Failed to close video window: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+// Show a file as the wallpaper, as a static image or in a video window depending on its content.
+// Callers that change the wallpaper on the user's behalf count it with record_wallpaper_shown.
+pub async fn apply_wallpaper(app: AppHandle<Wry>, file_path: String) -> Result<String, String> {
+    let detection = sniff_media(Path::new(&file_path));
+    let state = app.state::<AppState>();
+
+    if detection.media_kind != MediaKind::Static {
+        let converted_path = asset_url(&file_path);
+        apply_video_wallpaper(app.clone(), file_path, converted_path, &state).await
+    } else {
+        // A video window left open would cover the new static wallpaper
+        stop_video_wallpaper(state, app.clone()).await?;
+        apply_static_wallpaper(app.clone(), file_path).await
+    }
+}
+
+// Get a file ready to be applied: videos and animations load in a hidden window, images are
+// normalized into the cache
+pub async fn preload_wallpaper(app: &AppHandle<Wry>, file_path: &str) -> Result<(), String> {
+    let detection = sniff_media(Path::new(file_path));
+    if !detection.is_valid {
+        return Err(format!("Cannot use {}", file_path));
+    }
+    let state = app.state::<AppState>();
+
+    if detection.media_kind != MediaKind::Static {
+        let wallpaper_url = video_wallpaper_url(&asset_url(file_path), &detection.mime_type);
+        preload_wallpaper_window(app, &state, wallpaper_url).await
+    } else {
+        discard_preloaded_window(app, &state);
+        prepare_static_image(app, PathBuf::from(file_path), detection.file_type).await
+    }
+}
+
+// URL the wallpaper window loads a local file from, in the same form as the frontend's
+// convertFileSrc: Windows and Android serve the asset protocol over http
+pub fn asset_url(file_path: &str) -> String {
+    #[cfg(any(windows, target_os = "android"))]
+    let base = "http://asset.localhost/";
+    #[cfg(not(any(windows, target_os = "android")))]
+    let base = "asset://localhost/";
+
+    format!("{}{}", base, urlencoding::encode(file_path))
 }
\ No newline at end of file
@@ -0,0 +1,106 @@
+use tauri::{AppHandle, Emitter, Wry};
+use crate::library::db::with_library_db;
+use crate::library::indexing::schedule_library_indexing;
+use crate::library::reconcile::{prune_missing, reconcile_library};
+use crate::library::watcher::LIBRARY_CHANGED_EVENT;
+use crate::slideshow::slideshow_candidates;
+use crate::types::{
+    LibraryChange, ReconcileReport, TagCount, WallpaperAttributeUpdate, WallpaperInfo, WallpaperQuery,
+    WallpaperQueryPage,
+};
+
+#[tauri::command]
+pub async fn get_library_wallpapers(app: AppHandle<Wry>) -> Result<Vec<WallpaperInfo>, String> {
+    with_library_db(&app, |db| db.list_wallpapers())
+}
+
+// Search, filter, sort and page the library in one call
+#[tauri::command]
+pub async fn query_wallpapers(app: AppHandle<Wry>, query: WallpaperQuery) -> Result<WallpaperQueryPage, String> {
+    let monitor = if query.fits_monitor {
+        app.primary_monitor()
+            .ok()
+            .flatten()
+            .map(|monitor| (monitor.size().width, monitor.size().height))
+    } else {
+        None
+    };
+
+    // Color searches only see palettes indexed so far; entries still waiting simply do not match yet
+    tokio::task::spawn_blocking(move || {
+        let offset = query.offset;
+        let (items, total) = with_library_db(&app, |db| db.query_wallpapers(&query, monitor))?;
+        Ok(WallpaperQueryPage { items, offset, total })
+    })
+    .await
+    .map_err(|e| format!("Failed to query library: {}", e))?
+}
+
+// New wallpapers go to the end of the library; ones already present are refreshed in place
+#[tauri::command]
+pub async fn add_library_wallpapers(app: AppHandle<Wry>, wallpapers: Vec<WallpaperInfo>) -> Result<(), String> {
+    with_library_db(&app, |db| db.upsert_wallpapers(&wallpapers))?;
+    schedule_library_indexing(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_library_wallpapers(app: AppHandle<Wry>, paths: Vec<String>) -> Result<usize, String> {
+    with_library_db(&app, |db| db.remove_wallpapers(&paths))
+}
+
+// Favorite, rate, block or tag many wallpapers at once; returns the entries as they are now
+#[tauri::command]
+pub async fn update_wallpaper_attributes(
+    app: AppHandle<Wry>,
+    paths: Vec<String>,
+    update: WallpaperAttributeUpdate,
+) -> Result<Vec<WallpaperInfo>, String> {
+    let updated = with_library_db(&app, |db| {
+        db.update_attributes(&paths, &update)?;
+        paths.iter()
+            .filter_map(|path| db.get_wallpaper(path).transpose())
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    if !updated.is_empty() {
+        let _ = app.emit(LIBRARY_CHANGED_EVENT, LibraryChange {
+            updated: updated.clone(),
+            ..Default::default()
+        });
+    }
+    Ok(updated)
+}
+
+// Every tag in use, with how many wallpapers carry it
+#[tauri::command]
+pub async fn get_wallpaper_tags(app: AppHandle<Wry>) -> Result<Vec<TagCount>, String> {
+    with_library_db(&app, |db| db.list_tags())
+}
+
+// Wallpapers whose files could not be found the last time the library was checked
+#[tauri::command]
+pub async fn get_broken_wallpapers(app: AppHandle<Wry>) -> Result<Vec<WallpaperInfo>, String> {
+    let wallpapers = with_library_db(&app, |db| db.list_wallpapers())?;
+    Ok(wallpapers.into_iter().filter(|info| info.is_missing).collect())
+}
+
+// Re-check the given entries, or the whole library, relinking moved files found in watched folders
+#[tauri::command]
+pub async fn repair_broken_wallpapers(
+    app: AppHandle<Wry>,
+    paths: Option<Vec<String>>,
+) -> Result<ReconcileReport, String> {
+    reconcile_library(app, paths).await
+}
+
+#[tauri::command]
+pub async fn prune_broken_wallpapers(app: AppHandle<Wry>, paths: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    prune_missing(app, paths).await
+}
+
+// What the slideshow picks from, after the active collection and the slideshow filter
+#[tauri::command]
+pub async fn get_slideshow_candidates(app: AppHandle<Wry>) -> Result<Vec<WallpaperInfo>, String> {
+    slideshow_candidates(&app).await
+}
\ No newline at end of file
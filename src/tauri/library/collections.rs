@@ -0,0 +1,188 @@
+use std::collections::HashSet;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Wry};
+use crate::commands::{load_app_state, update_app_state};
+use crate::library::db::with_library_db;
+use crate::library::watcher::{merge_wallpapers, LIBRARY_CHANGED_EVENT};
+use crate::slideshow::schedule::scheduled_target;
+use crate::slideshow::wake_slideshow;
+use crate::tray::refresh_tray_menu;
+use crate::types::{Collection, CollectionImport, LibraryChange, ScheduleTarget, WallpaperSettings};
+use crate::utils::file_utils::has_supported_extension;
+use crate::utils::scanner::build_wallpaper_info;
+
+pub const ACTIVE_COLLECTION_CHANGED_EVENT: &str = "active-collection-changed";
+
+// JSON export format; M3U files only carry the name and the paths
+#[derive(Serialize, Deserialize)]
+struct CollectionFile {
+    name: String,
+    #[serde(default)]
+    settings: Option<WallpaperSettings>,
+    items: Vec<String>,
+}
+
+// Switch the slideshow to a collection, or back to the whole library with None
+pub async fn activate_collection(app: AppHandle<Wry>, id: Option<i64>) -> Result<Option<Collection>, String> {
+    let collection = match id {
+        Some(id) => Some(
+            with_library_db(&app, |db| db.get_collection(id))?
+                .ok_or_else(|| format!("Collection {} does not exist", id))?,
+        ),
+        None => None,
+    };
+
+    update_app_state(&app, |state| state.active_collection = id).await?;
+
+    refresh_tray_menu(&app).await;
+    wake_slideshow(&app);
+    let _ = app.emit(ACTIVE_COLLECTION_CHANGED_EVENT, collection.clone());
+    Ok(collection)
+}
+
+// The active collection, if it still exists, and the slideshow settings in effect for it.
+// A scheduled time range can stand in for the active collection.
+pub async fn slideshow_scope(app: &AppHandle<Wry>) -> Result<(Option<Collection>, Option<WallpaperSettings>), String> {
+    let current_state = load_app_state(app.clone()).await?;
+    let collection_id = match scheduled_target(app) {
+        Some(ScheduleTarget::Collection { id }) => Some(id),
+        _ => current_state.active_collection,
+    };
+    let collection = match collection_id {
+        Some(id) => with_library_db(app, |db| db.get_collection(id))?,
+        None => None,
+    };
+
+    // A collection with its own settings replaces the global ones entirely
+    let settings = collection.as_ref()
+        .and_then(|collection| collection.settings.clone())
+        .or(current_state.wallpaper_settings);
+    Ok((collection, settings))
+}
+
+// Files ending in .m3u or .m3u8 are written as playlists, anything else as JSON
+pub fn write_collection_file(app: &AppHandle<Wry>, id: i64, path: &Path) -> Result<(), String> {
+    let (collection, items) = with_library_db(app, |db| Ok((db.get_collection(id)?, db.collection_paths(id)?)))?;
+    let collection = collection.ok_or_else(|| format!("Collection {} does not exist", id))?;
+
+    let contents = if is_playlist(path) {
+        let mut contents = format!("#EXTM3U\n#PLAYLIST:{}\n", collection.name);
+        for item in &items {
+            contents.push_str(item);
+            contents.push('\n');
+        }
+        contents
+    } else {
+        let file = CollectionFile {
+            name: collection.name,
+            settings: collection.settings,
+            items,
+        };
+        serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to serialize collection: {}", e))?
+    };
+
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// Create a collection from an exported file. Entries not yet in the library are added to it;
+// relative paths are resolved against the file's folder.
+pub fn read_collection_file(app: &AppHandle<Wry>, path: &Path) -> Result<CollectionImport, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file = if is_playlist(path) {
+        parse_playlist(path, &contents)
+    } else {
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to read collection file: {}", e))?
+    };
+
+    let base = path.parent().unwrap_or(Path::new(""));
+    let known: HashSet<String> = with_library_db(app, |db| db.list_entries())?
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect();
+
+    let mut items = Vec::new();
+    let mut new_wallpapers = Vec::new();
+    let mut skipped = Vec::new();
+    for item in file.items {
+        let item_path = base.join(&item);
+        let key = item_path.to_string_lossy().to_string();
+        if known.contains(&key) {
+            items.push(key);
+            continue;
+        }
+
+        match item_path.is_file().then(|| build_wallpaper_info(&item_path)) {
+            Some(info) if info.is_valid || has_supported_extension(&item_path) => {
+                items.push(key);
+                new_wallpapers.push(info);
+            }
+            _ => skipped.push(item),
+        }
+    }
+
+    if !new_wallpapers.is_empty() {
+        let mut change = LibraryChange::default();
+        merge_wallpapers(app, new_wallpapers, &mut change)?;
+        let _ = app.emit(LIBRARY_CHANGED_EVENT, change);
+    }
+
+    let collection = with_library_db(app, |db| {
+        let name = unique_collection_name(&db.list_collections()?, &file.name);
+        let collection = db.create_collection(&name)?;
+        db.set_collection_settings(collection.id, file.settings.as_ref())?;
+        db.add_to_collection(collection.id, &items)?;
+        db.get_collection(collection.id)?
+            .ok_or_else(|| "Imported collection disappeared".to_string())
+    })?;
+
+    Ok(CollectionImport { collection, skipped })
+}
+
+fn is_playlist(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("m3u") || ext.eq_ignore_ascii_case("m3u8"))
+}
+
+// One path per line; comments are ignored except for the playlist name
+fn parse_playlist(path: &Path, contents: &str) -> CollectionFile {
+    let mut name = None;
+    let mut items = Vec::new();
+    for line in contents.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        match line.strip_prefix('#') {
+            Some(comment) => {
+                if let Some(playlist) = comment.strip_prefix("PLAYLIST:") {
+                    name = Some(playlist.trim().to_string());
+                }
+            }
+            None => items.push(line.to_string()),
+        }
+    }
+
+    CollectionFile {
+        name: name
+            .filter(|name| !name.is_empty())
+            .or_else(|| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "Imported".to_string()),
+        settings: None,
+        items,
+    }
+}
+
+// "Nature" becomes "Nature (2)" when a collection of that name already exists
+pub fn unique_collection_name(collections: &[Collection], name: &str) -> String {
+    let name = match name.trim() {
+        "" => "Imported",
+        name => name,
+    };
+    let taken: HashSet<String> = collections.iter().map(|collection| collection.name.to_lowercase()).collect();
+
+    let mut candidate = name.to_string();
+    let mut suffix = 2;
+    while taken.contains(&candidate.to_lowercase()) {
+        candidate = format!("{} ({})", name, suffix);
+        suffix += 1;
+    }
+    candidate
+}
\ No newline at end of file
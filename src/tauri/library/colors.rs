@@ -0,0 +1,40 @@
+use std::path::Path;
+use tauri::{AppHandle, Wry};
+use crate::library::db::with_library_db;
+use crate::utils::color_palette::dominant_colors;
+use crate::utils::thumbnails::get_or_create_cached_thumbnail;
+
+// Colors come from the same small cached thumbnail duplicate hashing uses, which exists for videos too
+const PALETTE_THUMBNAIL_SIZE: u32 = 128;
+
+// Extract dominant colors for entries that do not have them yet; returns how many were indexed.
+// Files whose colors could not be read are skipped until their contents change.
+pub fn index_library_colors(app: &AppHandle<Wry>, thumbnail_root: &Path) -> Result<usize, String> {
+    let entries = with_library_db(app, |db| db.list_entries())?;
+    let mut indexed = 0;
+
+    // The database lock is only taken per write so the UI is not blocked while decoding
+    for entry in entries.into_iter().filter(|entry| !entry.missing && !entry.colors_indexed && !entry.colors_failed) {
+        let path = Path::new(&entry.path);
+        if !path.is_file() {
+            continue;
+        }
+
+        let palette = get_or_create_cached_thumbnail(thumbnail_root, path, PALETTE_THUMBNAIL_SIZE)
+            .and_then(|thumbnail| dominant_colors(&thumbnail));
+        match palette {
+            Ok(palette) => {
+                with_library_db(app, |db| db.set_palette(&entry.path, &palette))?;
+                indexed += 1;
+            }
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("Failed to extract colors of {}: {}", entry.path, _e);
+
+                with_library_db(app, |db| db.set_colors_failed(&entry.path))?;
+            }
+        }
+    }
+
+    Ok(indexed)
+}
\ No newline at end of file
@@ -0,0 +1,1598 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row};
+use tauri::{AppHandle, Manager, Wry};
+use crate::commands::{read_app_state, write_app_state};
+use crate::state::AppState;
+use crate::utils::color_palette::{hue_degrees, parse_hex_color, rgb_to_lab, PaletteColor};
+use crate::types::{
+    Collection, DynamicSet, GpsLocation, ImageOrientation, LibrarySort, MediaKind, SlideshowFilter, SolarSet,
+    SolarVariants, TagCount, UsageStats, VideoMetadata, WallpaperAttributeUpdate, WallpaperInfo, WallpaperQuery,
+    WallpaperSettings,
+};
+
+const LIBRARY_DB_FILE: &str = "library.db";
+
+// Each entry upgrades the schema by one version; `PRAGMA user_version` records how many have run
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE wallpapers (
+        id INTEGER PRIMARY KEY,
+        path TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        file_type TEXT NOT NULL,
+        media_kind TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        width INTEGER,
+        height INTEGER,
+        captured_at TEXT,
+        is_valid INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        added_at INTEGER NOT NULL,
+        info TEXT NOT NULL
+    );
+    CREATE INDEX idx_wallpapers_position ON wallpapers(position);
+    CREATE INDEX idx_wallpapers_name ON wallpapers(name COLLATE NOCASE);
+    CREATE INDEX idx_wallpapers_captured_at ON wallpapers(captured_at);
+    CREATE INDEX idx_wallpapers_media_kind ON wallpapers(media_kind);
+
+    CREATE TABLE tags (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE
+    );
+    CREATE TABLE wallpaper_tags (
+        wallpaper_id INTEGER NOT NULL REFERENCES wallpapers(id) ON DELETE CASCADE,
+        tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+        PRIMARY KEY (wallpaper_id, tag_id)
+    );
+    CREATE INDEX idx_wallpaper_tags_tag ON wallpaper_tags(tag_id);
+
+    CREATE TABLE collections (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE collection_items (
+        collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+        wallpaper_id INTEGER NOT NULL REFERENCES wallpapers(id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (collection_id, wallpaper_id)
+    );
+
+    CREATE TABLE usage_stats (
+        wallpaper_id INTEGER PRIMARY KEY REFERENCES wallpapers(id) ON DELETE CASCADE,
+        times_shown INTEGER NOT NULL DEFAULT 0,
+        last_shown_at INTEGER
+    );
+
+    CREATE TABLE scan_metadata (
+        folder TEXT PRIMARY KEY,
+        last_scan_at INTEGER NOT NULL,
+        file_count INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL
+    );
+    ",
+    "
+    ALTER TABLE wallpapers ADD COLUMN content_hash TEXT;
+    ALTER TABLE wallpapers ADD COLUMN missing INTEGER NOT NULL DEFAULT 0;
+    CREATE INDEX idx_wallpapers_content_hash ON wallpapers(content_hash);
+    ",
+    "
+    ALTER TABLE wallpapers ADD COLUMN perceptual_hash INTEGER;
+    ",
+    "
+    ALTER TABLE wallpapers ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE wallpapers ADD COLUMN rating INTEGER;
+    ALTER TABLE wallpapers ADD COLUMN blocked INTEGER NOT NULL DEFAULT 0;
+    CREATE INDEX idx_wallpapers_favorite ON wallpapers(favorite);
+    CREATE INDEX idx_wallpapers_rating ON wallpapers(rating);
+    ",
+    "
+    ALTER TABLE collections ADD COLUMN settings TEXT;
+    CREATE INDEX idx_collection_items_position ON collection_items(collection_id, position);
+    ",
+    "
+    ALTER TABLE wallpapers ADD COLUMN modified_at INTEGER;
+    ALTER TABLE wallpapers ADD COLUMN orientation INTEGER;
+    UPDATE wallpapers SET orientation = json_extract(info, '$.orientation');
+    CREATE INDEX idx_wallpapers_size ON wallpapers(size);
+    CREATE INDEX idx_wallpapers_modified_at ON wallpapers(modified_at);
+    ",
+    "
+    CREATE TABLE wallpaper_colors (
+        wallpaper_id INTEGER NOT NULL REFERENCES wallpapers(id) ON DELETE CASCADE,
+        rank INTEGER NOT NULL,
+        red INTEGER NOT NULL,
+        green INTEGER NOT NULL,
+        blue INTEGER NOT NULL,
+        weight REAL NOT NULL,
+        lab_l REAL NOT NULL,
+        lab_a REAL NOT NULL,
+        lab_b REAL NOT NULL,
+        hue REAL,
+        PRIMARY KEY (wallpaper_id, rank)
+    );
+    CREATE INDEX idx_wallpaper_colors_hue ON wallpaper_colors(hue);
+    ALTER TABLE wallpapers ADD COLUMN colors_indexed INTEGER NOT NULL DEFAULT 0;
+    ",
+    "
+    CREATE TABLE solar_sets (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        latitude REAL NOT NULL,
+        longitude REAL NOT NULL,
+        location TEXT,
+        day_path TEXT,
+        golden_hour_path TEXT,
+        dusk_path TEXT,
+        night_path TEXT,
+        created_at INTEGER NOT NULL
+    );
+    ",
+    "
+    CREATE TABLE dynamic_sets (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        period INTEGER NOT NULL,
+        frames TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    ",
+    "
+    ALTER TABLE wallpapers ADD COLUMN mime_type TEXT NOT NULL DEFAULT '';
+    ALTER TABLE wallpapers ADD COLUMN invalid_reason TEXT;
+    ALTER TABLE wallpapers ADD COLUMN camera_model TEXT;
+    ALTER TABLE wallpapers ADD COLUMN latitude REAL;
+    ALTER TABLE wallpapers ADD COLUMN longitude REAL;
+    ALTER TABLE wallpapers ADD COLUMN video_duration_secs REAL;
+    ALTER TABLE wallpapers ADD COLUMN video_width INTEGER;
+    ALTER TABLE wallpapers ADD COLUMN video_height INTEGER;
+    ALTER TABLE wallpapers ADD COLUMN video_frame_rate REAL;
+    ALTER TABLE wallpapers ADD COLUMN video_codec TEXT;
+    ALTER TABLE wallpapers ADD COLUMN video_has_audio INTEGER;
+    ALTER TABLE wallpapers ADD COLUMN video_frame_count INTEGER;
+    UPDATE wallpapers SET
+        mime_type = COALESCE(json_extract(info, '$.mime_type'), ''),
+        invalid_reason = json_extract(info, '$.invalid_reason'),
+        camera_model = json_extract(info, '$.camera_model'),
+        latitude = json_extract(info, '$.gps_location.latitude'),
+        longitude = json_extract(info, '$.gps_location.longitude'),
+        video_duration_secs = json_extract(info, '$.video.duration_secs'),
+        video_width = json_extract(info, '$.video.width'),
+        video_height = json_extract(info, '$.video.height'),
+        video_frame_rate = json_extract(info, '$.video.frame_rate'),
+        video_codec = json_extract(info, '$.video.codec'),
+        video_has_audio = json_extract(info, '$.video.has_audio'),
+        video_frame_count = json_extract(info, '$.video.frame_count')
+    WHERE json_valid(info);
+    ALTER TABLE wallpapers DROP COLUMN info;
+    ",
+    "
+    ALTER TABLE wallpapers ADD COLUMN perceptual_hash_failed INTEGER NOT NULL DEFAULT 0;
+    ",
+    "
+    ALTER TABLE wallpapers ADD COLUMN colors_failed INTEGER NOT NULL DEFAULT 0;
+    ",
+    "
+    CREATE TABLE rotation (
+        scope TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        path TEXT NOT NULL,
+        PRIMARY KEY (scope, position)
+    );
+    ",
+];
+
+// Sizes as displayed; EXIF orientations 5 to 8 turn the picture a quarter turn
+const DISPLAY_WIDTH: &str = "(CASE WHEN w.orientation BETWEEN 5 AND 8 THEN w.height ELSE w.width END)";
+const DISPLAY_HEIGHT: &str = "(CASE WHEN w.orientation BETWEEN 5 AND 8 THEN w.width ELSE w.height END)";
+
+// YYYY-MM-DD of the capture date, or of the last modification for files without one
+const FILE_DATE: &str = "COALESCE(substr(w.captured_at, 1, 10), date(w.modified_at, 'unixepoch', 'localtime'))";
+
+// How far from 1:1 a picture can be and still count as square
+const SQUARE_TOLERANCE: f64 = 0.05;
+
+// How far, relative to the monitor's aspect ratio, a picture can be and still fit it
+const FIT_ASPECT_TOLERANCE: f64 = 0.1;
+
+// Lab difference used for color searches that do not give their own
+const DEFAULT_COLOR_DISTANCE: f64 = 20.0;
+
+// Palette colors covering less of the picture than this are ignored by color searches
+const MIN_COLOR_WEIGHT: f64 = 0.1;
+
+// Columns describing the file itself, written together whenever it is scanned again. Their values
+// come from `file_values` in the same order.
+const FILE_COLUMNS: [&str; 22] = [
+    "name", "file_type", "media_kind", "size", "width", "height", "captured_at", "is_valid", "modified_at",
+    "orientation", "mime_type", "invalid_reason", "camera_model", "latitude", "longitude", "video_duration_secs",
+    "video_width", "video_height", "video_frame_rate", "video_codec", "video_has_audio", "video_frame_count",
+];
+
+// Tags are joined with the unit separator since names may contain commas
+const SELECT_WALLPAPERS: &str = "
+    SELECT w.path, w.name, w.file_type, w.size, w.modified_at, w.mime_type, w.media_kind, w.is_valid,
+        w.invalid_reason, w.missing, w.width, w.height, w.orientation, w.captured_at, w.camera_model,
+        w.latitude, w.longitude, w.video_duration_secs, w.video_width, w.video_height, w.video_frame_rate,
+        w.video_codec, w.video_has_audio, w.video_frame_count, w.favorite, w.rating, w.blocked,
+        (SELECT group_concat(t.name, char(31))
+         FROM wallpaper_tags wt JOIN tags t ON t.id = wt.tag_id
+         WHERE wt.wallpaper_id = w.id),
+        (SELECT group_concat(printf('#%02x%02x%02x', c.red, c.green, c.blue), ',' ORDER BY c.rank)
+         FROM wallpaper_colors c
+         WHERE c.wallpaper_id = w.id)
+    FROM wallpapers w";
+
+const COLLECTION_JOIN: &str = "JOIN collection_items ci ON ci.wallpaper_id = w.id";
+
+const SELECT_COLLECTIONS: &str = "
+    SELECT c.id, c.name,
+        (SELECT COUNT(*) FROM collection_items ci WHERE ci.collection_id = c.id),
+        c.settings
+    FROM collections c";
+
+const SELECT_SOLAR_SETS: &str = "
+    SELECT id, name, latitude, longitude, location, day_path, golden_hour_path, dusk_path, night_path
+    FROM solar_sets";
+
+const SELECT_DYNAMIC_SETS: &str = "SELECT id, name, period, frames FROM dynamic_sets";
+
+// What reconciliation needs to know about a row without deserializing it
+pub struct StoredEntry {
+    pub path: String,
+    pub size: u64,
+    pub content_hash: Option<String>,
+    pub perceptual_hash: Option<u64>,
+    // Hashing failed for the current contents, so it is not retried until they change
+    pub perceptual_hash_failed: bool,
+    pub modified_at: Option<i64>,
+    pub colors_indexed: bool,
+    // Same as `perceptual_hash_failed`, for color extraction
+    pub colors_failed: bool,
+    pub missing: bool,
+}
+
+pub struct LibraryDb {
+    conn: Connection,
+    // Bumped whenever something duplicate grouping depends on changes, so cached groups know they are stale
+    hash_generation: AtomicU64,
+}
+
+impl LibraryDb {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create library directory: {}", e))?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open library database: {}", e))?;
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")
+            .map_err(|e| format!("Failed to configure library database: {}", e))?;
+
+        let mut db = Self { conn, hash_generation: AtomicU64::new(0) };
+        db.migrate()?;
+        Ok(db)
+    }
+
+    fn migrate(&mut self) -> Result<(), String> {
+        let version: usize = self.conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read library schema version: {}", e))?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction().map_err(|e| format!("Failed to start migration: {}", e))?;
+            tx.execute_batch(migration)
+                .and_then(|_| tx.pragma_update(None, "user_version", index + 1))
+                .map_err(|e| format!("Failed to migrate library database: {}", e))?;
+            tx.commit().map_err(|e| format!("Failed to commit migration: {}", e))?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> Result<bool, String> {
+        self.conn
+            .query_row("SELECT NOT EXISTS (SELECT 1 FROM wallpapers)", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to query library: {}", e))
+    }
+
+    pub fn list_wallpapers(&self) -> Result<Vec<WallpaperInfo>, String> {
+        self.select_wallpapers("ORDER BY w.position", Vec::new())
+    }
+
+    pub fn get_wallpaper(&self, path: &str) -> Result<Option<WallpaperInfo>, String> {
+        self.conn
+            .query_row(&format!("{} WHERE w.path = ?1", SELECT_WALLPAPERS), [path], read_row)
+            .optional()
+            .map_err(|e| format!("Failed to query library: {}", e))
+    }
+
+    // Wallpapers the slideshow may show, in library order or the collection's order when one is
+    // given. Blocked entries are never included.
+    pub fn filter_wallpapers(
+        &self,
+        filter: &SlideshowFilter,
+        collection_id: Option<i64>,
+    ) -> Result<Vec<WallpaperInfo>, String> {
+        let mut conditions = vec!["w.blocked = 0".to_string()];
+        let mut values = Vec::new();
+
+        if let Some(collection_id) = collection_id {
+            conditions.push("ci.collection_id = ?".to_string());
+            values.push(Value::Integer(collection_id));
+        }
+        push_attribute_conditions(
+            &mut conditions,
+            &mut values,
+            &filter.tags,
+            filter.favorites_only,
+            filter.min_rating,
+        );
+        push_color_conditions(
+            &mut conditions,
+            &mut values,
+            filter.color.as_deref(),
+            filter.color_distance,
+            (filter.min_hue, filter.max_hue),
+        )?;
+
+        let clause = format!("WHERE {}", conditions.join(" AND "));
+        match collection_id {
+            Some(_) => self.select_wallpapers(&format!("{} {} ORDER BY ci.position", COLLECTION_JOIN, clause), values),
+            None => self.select_wallpapers(&format!("{} ORDER BY w.position", clause), values),
+        }
+    }
+
+    // One page of the library matching `query`, plus the number of matches across all pages.
+    // `monitor` is the physical size used for `fits_monitor`.
+    pub fn query_wallpapers(
+        &self,
+        query: &WallpaperQuery,
+        monitor: Option<(u32, u32)>,
+    ) -> Result<(Vec<WallpaperInfo>, usize), String> {
+        let aspect_ratio = format!("(CAST({} AS REAL) / NULLIF({}, 0))", DISPLAY_WIDTH, DISPLAY_HEIGHT);
+        let mut conditions: Vec<String> = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(name) = query.name.as_deref().map(str::trim).filter(|name| !name.is_empty()) {
+            conditions.push("w.name LIKE ? ESCAPE '\\'".to_string());
+            values.push(Value::Text(format!("%{}%", escape_like(name))));
+        }
+        if !query.kinds.is_empty() {
+            conditions.push(format!("w.media_kind IN ({})", placeholders(query.kinds.len())));
+            values.extend(query.kinds.iter().map(|kind| Value::Text(media_kind_name(*kind))));
+        }
+        push_attribute_conditions(
+            &mut conditions,
+            &mut values,
+            &query.tags,
+            query.favorites_only,
+            query.min_rating,
+        );
+        push_color_conditions(
+            &mut conditions,
+            &mut values,
+            query.color.as_deref(),
+            query.color_distance,
+            (query.min_hue, query.max_hue),
+        )?;
+
+        let integer_bounds = [
+            ("w.size", ">=", query.min_size.map(|size| size as i64)),
+            ("w.size", "<=", query.max_size.map(|size| size as i64)),
+            (DISPLAY_WIDTH, ">=", query.min_width.map(i64::from)),
+            (DISPLAY_WIDTH, "<=", query.max_width.map(i64::from)),
+            (DISPLAY_HEIGHT, ">=", query.min_height.map(i64::from)),
+            (DISPLAY_HEIGHT, "<=", query.max_height.map(i64::from)),
+        ];
+        for (column, operator, bound) in integer_bounds {
+            if let Some(bound) = bound {
+                conditions.push(format!("{} {} ?", column, operator));
+                values.push(Value::Integer(bound));
+            }
+        }
+
+        let mut aspect_bounds = vec![
+            (">=", query.min_aspect_ratio),
+            ("<=", query.max_aspect_ratio),
+        ];
+        match query.orientation {
+            Some(ImageOrientation::Landscape) => aspect_bounds.push((">", Some(1.0 + SQUARE_TOLERANCE))),
+            Some(ImageOrientation::Portrait) => aspect_bounds.push(("<", Some(1.0 - SQUARE_TOLERANCE))),
+            Some(ImageOrientation::Square) => {
+                aspect_bounds.push((">=", Some(1.0 - SQUARE_TOLERANCE)));
+                aspect_bounds.push(("<=", Some(1.0 + SQUARE_TOLERANCE)));
+            }
+            None => {}
+        }
+        if query.fits_monitor {
+            let (width, height) = monitor.ok_or_else(|| "Could not detect the current monitor".to_string())?;
+            let monitor_ratio = width as f64 / height.max(1) as f64;
+            conditions.push(format!("{} >= ? AND {} >= ?", DISPLAY_WIDTH, DISPLAY_HEIGHT));
+            values.extend([Value::Integer(width as i64), Value::Integer(height as i64)]);
+            aspect_bounds.push((">=", Some(monitor_ratio * (1.0 - FIT_ASPECT_TOLERANCE))));
+            aspect_bounds.push(("<=", Some(monitor_ratio * (1.0 + FIT_ASPECT_TOLERANCE))));
+        }
+        for (operator, bound) in aspect_bounds {
+            if let Some(bound) = bound {
+                conditions.push(format!("{} {} ?", aspect_ratio, operator));
+                values.push(Value::Real(bound));
+            }
+        }
+
+        for (operator, date) in [(">=", &query.date_from), ("<=", &query.date_to)] {
+            if let Some(date) = date {
+                if !is_iso_date(date) {
+                    return Err(format!("Dates must look like 2024-01-31, got {}", date));
+                }
+                conditions.push(format!("{} {} ?", FILE_DATE, operator));
+                values.push(Value::Text(date.clone()));
+            }
+        }
+
+        let clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+
+        let total: i64 = self.conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM wallpapers w {}", clause),
+                params_from_iter(values.iter()),
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to query library: {}", e))?;
+
+        let order = match query.sort_by {
+            LibrarySort::Added => "w.position",
+            LibrarySort::Name => "w.name COLLATE NOCASE",
+            LibrarySort::Modified => "w.modified_at",
+            LibrarySort::CaptureDate => "w.captured_at",
+            LibrarySort::Size => "w.size",
+            LibrarySort::Rating => "w.rating",
+            LibrarySort::Usage => "COALESCE(u.times_shown, 0)",
+        };
+        let direction = if query.descending { "DESC" } else { "ASC" };
+
+        // Entries without a value go last either way; library order breaks ties
+        values.push(Value::Integer(query.limit.map(|limit| limit as i64).unwrap_or(-1)));
+        values.push(Value::Integer(query.offset as i64));
+        let items = self.select_wallpapers(
+            &format!(
+                "LEFT JOIN usage_stats u ON u.wallpaper_id = w.id {} ORDER BY {} IS NULL, {} {}, w.position LIMIT ? OFFSET ?",
+                clause, order, order, direction,
+            ),
+            values,
+        )?;
+
+        Ok((items, total as usize))
+    }
+
+    // Replace the dominant colors of a wallpaper and mark it as indexed
+    pub fn set_palette(&mut self, path: &str, palette: &[PaletteColor]) -> Result<(), String> {
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        {
+            tx.execute(
+                "DELETE FROM wallpaper_colors WHERE wallpaper_id = (SELECT id FROM wallpapers WHERE path = ?1)",
+                [path],
+            )
+            .map_err(|e| format!("Failed to save colors: {}", e))?;
+
+            let mut insert = tx
+                .prepare_cached(
+                    "INSERT INTO wallpaper_colors
+                         (wallpaper_id, rank, red, green, blue, weight, lab_l, lab_a, lab_b, hue)
+                     SELECT id, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10 FROM wallpapers WHERE path = ?1",
+                )
+                .map_err(|e| format!("Failed to save colors: {}", e))?;
+            for (rank, color) in palette.iter().enumerate() {
+                let [r, g, b] = color.rgb;
+                let [l, a, lab_b] = rgb_to_lab(color.rgb);
+                insert
+                    .execute(params![path, rank as i64, r, g, b, color.weight, l, a, lab_b, hue_degrees(color.rgb)])
+                    .map_err(|e| format!("Failed to save colors: {}", e))?;
+            }
+
+            tx.execute("UPDATE wallpapers SET colors_indexed = 1 WHERE path = ?1", [path])
+                .map_err(|e| format!("Failed to save colors: {}", e))?;
+        }
+        tx.commit().map_err(|e| format!("Failed to save library: {}", e))
+    }
+
+    pub fn set_colors_failed(&self, path: &str) -> Result<(), String> {
+        self.conn
+            .execute("UPDATE wallpapers SET colors_failed = 1 WHERE path = ?1", [path])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to update wallpaper: {}", e))
+    }
+
+    // Fill in the modification time for entries stored before it was recorded
+    pub fn set_modified_at(&self, path: &str, modified_at: i64) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE wallpapers SET modified_at = ?2 WHERE path = ?1",
+                params![path, modified_at],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to update wallpaper: {}", e))
+    }
+
+    // `tail` is appended to the shared SELECT and may join, filter and order
+    fn select_wallpapers(&self, tail: &str, values: Vec<Value>) -> Result<Vec<WallpaperInfo>, String> {
+        let mut statement = self.conn
+            .prepare_cached(&format!("{} {}", SELECT_WALLPAPERS, tail))
+            .map_err(|e| format!("Failed to query library: {}", e))?;
+
+        let rows = statement
+            .query_map(params_from_iter(values), read_row)
+            .map_err(|e| format!("Failed to query library: {}", e))?;
+
+        // A row that can no longer be read is skipped rather than failing the whole library
+        Ok(rows
+            .filter_map(|row| row.ok())
+            .collect())
+    }
+
+    // Apply one edit to many wallpapers at once; unknown paths are ignored
+    pub fn update_attributes(&mut self, paths: &[String], update: &WallpaperAttributeUpdate) -> Result<(), String> {
+        if let Some(rating) = update.rating.filter(|rating| *rating > 5) {
+            return Err(format!("Rating must be between 1 and 5, got {}", rating));
+        }
+        let add_tags = normalize_tags(&update.add_tags);
+        let remove_tags = normalize_tags(&update.remove_tags);
+
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        {
+            let mut insert_tag = tx
+                .prepare_cached("INSERT OR IGNORE INTO tags (name) VALUES (?1)")
+                .map_err(|e| format!("Failed to save tags: {}", e))?;
+            for tag in &add_tags {
+                insert_tag.execute([tag]).map_err(|e| format!("Failed to save tags: {}", e))?;
+            }
+
+            for path in paths {
+                if let Some(favorite) = update.favorite {
+                    tx.execute("UPDATE wallpapers SET favorite = ?2 WHERE path = ?1", params![path, favorite])
+                        .map_err(|e| format!("Failed to update wallpaper: {}", e))?;
+                }
+                // Zero stars clears the rating
+                if let Some(rating) = update.rating {
+                    tx.execute(
+                        "UPDATE wallpapers SET rating = NULLIF(?2, 0) WHERE path = ?1",
+                        params![path, rating],
+                    )
+                    .map_err(|e| format!("Failed to update wallpaper: {}", e))?;
+                }
+                if let Some(blocked) = update.blocked {
+                    tx.execute("UPDATE wallpapers SET blocked = ?2 WHERE path = ?1", params![path, blocked])
+                        .map_err(|e| format!("Failed to update wallpaper: {}", e))?;
+                }
+                for tag in &add_tags {
+                    tx.execute(
+                        "INSERT OR IGNORE INTO wallpaper_tags (wallpaper_id, tag_id)
+                         SELECT w.id, t.id FROM wallpapers w, tags t WHERE w.path = ?1 AND t.name = ?2",
+                        params![path, tag],
+                    )
+                    .map_err(|e| format!("Failed to save tags: {}", e))?;
+                }
+                for tag in &remove_tags {
+                    tx.execute(
+                        "DELETE FROM wallpaper_tags
+                         WHERE wallpaper_id = (SELECT id FROM wallpapers WHERE path = ?1)
+                             AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+                        params![path, tag],
+                    )
+                    .map_err(|e| format!("Failed to save tags: {}", e))?;
+                }
+            }
+
+            // Tags nobody uses any more would only clutter the tag list
+            tx.execute("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM wallpaper_tags)", [])
+                .map_err(|e| format!("Failed to save tags: {}", e))?;
+        }
+        tx.commit().map_err(|e| format!("Failed to save library: {}", e))
+    }
+
+    pub fn list_tags(&self) -> Result<Vec<TagCount>, String> {
+        let mut statement = self.conn
+            .prepare_cached(
+                "SELECT t.name, COUNT(wt.wallpaper_id) FROM tags t
+                 LEFT JOIN wallpaper_tags wt ON wt.tag_id = t.id
+                 GROUP BY t.id ORDER BY t.name COLLATE NOCASE",
+            )
+            .map_err(|e| format!("Failed to query tags: {}", e))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(TagCount {
+                    name: row.get(0)?,
+                    count: row.get::<_, i64>(1)? as usize,
+                })
+            })
+            .map_err(|e| format!("Failed to query tags: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query tags: {}", e))
+    }
+
+    pub fn list_entries(&self) -> Result<Vec<StoredEntry>, String> {
+        let mut statement = self.conn
+            .prepare_cached(
+                "SELECT path, size, content_hash, perceptual_hash, missing, modified_at, colors_indexed,
+                     perceptual_hash_failed, colors_failed
+                 FROM wallpapers ORDER BY position",
+            )
+            .map_err(|e| format!("Failed to query library: {}", e))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(StoredEntry {
+                    path: row.get(0)?,
+                    size: row.get::<_, i64>(1)? as u64,
+                    content_hash: row.get(2)?,
+                    // SQLite integers are signed, so the 64 bits are stored as i64
+                    perceptual_hash: row.get::<_, Option<i64>>(3)?.map(|hash| hash as u64),
+                    missing: row.get(4)?,
+                    modified_at: row.get(5)?,
+                    colors_indexed: row.get(6)?,
+                    perceptual_hash_failed: row.get(7)?,
+                    colors_failed: row.get(8)?,
+                })
+            })
+            .map_err(|e| format!("Failed to query library: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query library: {}", e))
+    }
+
+    pub fn list_collections(&self) -> Result<Vec<Collection>, String> {
+        let mut statement = self.conn
+            .prepare_cached(&format!("{} ORDER BY c.name COLLATE NOCASE", SELECT_COLLECTIONS))
+            .map_err(|e| format!("Failed to query collections: {}", e))?;
+        let rows = statement
+            .query_map([], read_collection)
+            .map_err(|e| format!("Failed to query collections: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query collections: {}", e))
+    }
+
+    pub fn get_collection(&self, id: i64) -> Result<Option<Collection>, String> {
+        self.conn
+            .query_row(&format!("{} WHERE c.id = ?1", SELECT_COLLECTIONS), [id], read_collection)
+            .optional()
+            .map_err(|e| format!("Failed to query collections: {}", e))
+    }
+
+    pub fn create_collection(&self, name: &str) -> Result<Collection, String> {
+        let name = collection_name(name)?;
+        if self.collection_name_taken(name, None)? {
+            return Err(format!("A collection named \"{}\" already exists", name));
+        }
+        self.conn
+            .execute("INSERT INTO collections (name, created_at) VALUES (?1, ?2)", params![name, unix_now()])
+            .map_err(|e| format!("Failed to create collection: {}", e))?;
+        Ok(Collection {
+            id: self.conn.last_insert_rowid(),
+            name: name.to_string(),
+            item_count: 0,
+            settings: None,
+        })
+    }
+
+    pub fn rename_collection(&self, id: i64, name: &str) -> Result<(), String> {
+        let name = collection_name(name)?;
+        if self.collection_name_taken(name, Some(id))? {
+            return Err(format!("A collection named \"{}\" already exists", name));
+        }
+        self.conn
+            .execute("UPDATE collections SET name = ?2 WHERE id = ?1", params![id, name])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to rename collection: {}", e))
+    }
+
+    pub fn delete_collection(&self, id: i64) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM collections WHERE id = ?1", [id])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to delete collection: {}", e))
+    }
+
+    // None makes the collection follow the global slideshow settings again
+    pub fn set_collection_settings(&self, id: i64, settings: Option<&WallpaperSettings>) -> Result<(), String> {
+        let json = settings
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        self.conn
+            .execute("UPDATE collections SET settings = ?2 WHERE id = ?1", params![id, json])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to save collection settings: {}", e))
+    }
+
+    pub fn collection_wallpapers(&self, id: i64) -> Result<Vec<WallpaperInfo>, String> {
+        self.select_wallpapers(
+            &format!("{} WHERE ci.collection_id = ? ORDER BY ci.position", COLLECTION_JOIN),
+            vec![Value::Integer(id)],
+        )
+    }
+
+    // Append library wallpapers to a collection; ones already in it or not in the library are skipped
+    pub fn add_to_collection(&mut self, id: i64, paths: &[String]) -> Result<usize, String> {
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let mut added = 0;
+        {
+            let mut insert = tx
+                .prepare_cached(
+                    "INSERT OR IGNORE INTO collection_items (collection_id, wallpaper_id, position)
+                     SELECT ?1, w.id,
+                         (SELECT COALESCE(MAX(position) + 1, 0) FROM collection_items WHERE collection_id = ?1)
+                     FROM wallpapers w WHERE w.path = ?2",
+                )
+                .map_err(|e| format!("Failed to update collection: {}", e))?;
+            for path in paths {
+                added += insert.execute(params![id, path]).map_err(|e| format!("Failed to update collection: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("Failed to save collection: {}", e))?;
+        Ok(added)
+    }
+
+    pub fn remove_from_collection(&mut self, id: i64, paths: &[String]) -> Result<usize, String> {
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let mut removed = 0;
+        {
+            let mut delete = tx
+                .prepare_cached(
+                    "DELETE FROM collection_items
+                     WHERE collection_id = ?1 AND wallpaper_id = (SELECT id FROM wallpapers WHERE path = ?2)",
+                )
+                .map_err(|e| format!("Failed to update collection: {}", e))?;
+            for path in paths {
+                removed += delete.execute(params![id, path]).map_err(|e| format!("Failed to update collection: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("Failed to save collection: {}", e))?;
+        Ok(removed)
+    }
+
+    // Put the given items first, in that order; items left out keep their relative order after them
+    pub fn reorder_collection(&mut self, id: i64, paths: &[String]) -> Result<(), String> {
+        let mut order: Vec<String> = paths.to_vec();
+        let listed: HashSet<&str> = paths.iter().map(String::as_str).collect();
+        order.extend(
+            self.collection_paths(id)?
+                .into_iter()
+                .filter(|path| !listed.contains(path.as_str())),
+        );
+
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        {
+            let mut update = tx
+                .prepare_cached(
+                    "UPDATE collection_items SET position = ?3
+                     WHERE collection_id = ?1 AND wallpaper_id = (SELECT id FROM wallpapers WHERE path = ?2)",
+                )
+                .map_err(|e| format!("Failed to update collection: {}", e))?;
+            for (position, path) in order.iter().enumerate() {
+                update.execute(params![id, path, position as i64])
+                    .map_err(|e| format!("Failed to update collection: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("Failed to save collection: {}", e))
+    }
+
+    pub fn collection_paths(&self, id: i64) -> Result<Vec<String>, String> {
+        let mut statement = self.conn
+            .prepare_cached(
+                "SELECT w.path FROM collection_items ci JOIN wallpapers w ON w.id = ci.wallpaper_id
+                 WHERE ci.collection_id = ?1 ORDER BY ci.position",
+            )
+            .map_err(|e| format!("Failed to query collection: {}", e))?;
+        let rows = statement
+            .query_map([id], |row| row.get(0))
+            .map_err(|e| format!("Failed to query collection: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query collection: {}", e))
+    }
+
+    pub fn list_solar_sets(&self) -> Result<Vec<SolarSet>, String> {
+        let mut statement = self.conn
+            .prepare_cached(&format!("{} ORDER BY name COLLATE NOCASE", SELECT_SOLAR_SETS))
+            .map_err(|e| format!("Failed to query solar sets: {}", e))?;
+        let rows = statement
+            .query_map([], read_solar_set)
+            .map_err(|e| format!("Failed to query solar sets: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query solar sets: {}", e))
+    }
+
+    pub fn get_solar_set(&self, id: i64) -> Result<Option<SolarSet>, String> {
+        self.conn
+            .query_row(&format!("{} WHERE id = ?1", SELECT_SOLAR_SETS), [id], read_solar_set)
+            .optional()
+            .map_err(|e| format!("Failed to query solar sets: {}", e))
+    }
+
+    // The id of `set` is ignored; the stored set is returned with its new id
+    pub fn create_solar_set(&self, set: &SolarSet) -> Result<SolarSet, String> {
+        let name = check_solar_set(set)?;
+        let variants = &set.variants;
+        self.conn
+            .execute(
+                "INSERT INTO solar_sets (name, latitude, longitude, location, day_path, golden_hour_path, dusk_path, night_path, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    name, set.latitude, set.longitude, set.location,
+                    variants.day, variants.golden_hour, variants.dusk, variants.night, unix_now(),
+                ],
+            )
+            .map_err(|e| format!("Failed to create solar set: {}", e))?;
+        Ok(SolarSet {
+            id: self.conn.last_insert_rowid(),
+            name: name.to_string(),
+            ..set.clone()
+        })
+    }
+
+    pub fn update_solar_set(&self, set: &SolarSet) -> Result<(), String> {
+        let name = check_solar_set(set)?;
+        let variants = &set.variants;
+        let updated = self.conn
+            .execute(
+                "UPDATE solar_sets SET name = ?2, latitude = ?3, longitude = ?4, location = ?5,
+                    day_path = ?6, golden_hour_path = ?7, dusk_path = ?8, night_path = ?9
+                 WHERE id = ?1",
+                params![
+                    set.id, name, set.latitude, set.longitude, set.location,
+                    variants.day, variants.golden_hour, variants.dusk, variants.night,
+                ],
+            )
+            .map_err(|e| format!("Failed to update solar set: {}", e))?;
+        if updated == 0 {
+            return Err(format!("Solar set {} does not exist", set.id));
+        }
+        Ok(())
+    }
+
+    pub fn delete_solar_set(&self, id: i64) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM solar_sets WHERE id = ?1", [id])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to delete solar set: {}", e))
+    }
+
+    pub fn list_dynamic_sets(&self) -> Result<Vec<DynamicSet>, String> {
+        let mut statement = self.conn
+            .prepare_cached(&format!("{} ORDER BY name COLLATE NOCASE", SELECT_DYNAMIC_SETS))
+            .map_err(|e| format!("Failed to query dynamic sets: {}", e))?;
+        let rows = statement
+            .query_map([], read_dynamic_set)
+            .map_err(|e| format!("Failed to query dynamic sets: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query dynamic sets: {}", e))
+    }
+
+    pub fn get_dynamic_set(&self, id: i64) -> Result<Option<DynamicSet>, String> {
+        self.conn
+            .query_row(&format!("{} WHERE id = ?1", SELECT_DYNAMIC_SETS), [id], read_dynamic_set)
+            .optional()
+            .map_err(|e| format!("Failed to query dynamic sets: {}", e))
+    }
+
+    // The id of `set` is ignored; the stored set is returned with its new id and frames in order
+    pub fn create_dynamic_set(&self, set: &DynamicSet) -> Result<DynamicSet, String> {
+        let set = check_dynamic_set(set)?;
+        let frames = serde_json::to_string(&set.frames)
+            .map_err(|e| format!("Failed to serialize frames: {}", e))?;
+        self.conn
+            .execute(
+                "INSERT INTO dynamic_sets (name, period, frames, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![set.name, set.period, frames, unix_now()],
+            )
+            .map_err(|e| format!("Failed to create dynamic set: {}", e))?;
+        Ok(DynamicSet {
+            id: self.conn.last_insert_rowid(),
+            ..set
+        })
+    }
+
+    pub fn update_dynamic_set(&self, set: &DynamicSet) -> Result<DynamicSet, String> {
+        let set = check_dynamic_set(set)?;
+        let frames = serde_json::to_string(&set.frames)
+            .map_err(|e| format!("Failed to serialize frames: {}", e))?;
+        let updated = self.conn
+            .execute(
+                "UPDATE dynamic_sets SET name = ?2, period = ?3, frames = ?4 WHERE id = ?1",
+                params![set.id, set.name, set.period, frames],
+            )
+            .map_err(|e| format!("Failed to update dynamic set: {}", e))?;
+        if updated == 0 {
+            return Err(format!("Dynamic set {} does not exist", set.id));
+        }
+        Ok(set)
+    }
+
+    pub fn delete_dynamic_set(&self, id: i64) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM dynamic_sets WHERE id = ?1", [id])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to delete dynamic set: {}", e))
+    }
+
+    fn collection_name_taken(&self, name: &str, except: Option<i64>) -> Result<bool, String> {
+        self.conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM collections WHERE name = ?1 COLLATE NOCASE AND id IS NOT ?2)",
+                params![name, except],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to query collections: {}", e))
+    }
+
+    pub fn hash_generation(&self) -> u64 {
+        self.hash_generation.load(Ordering::Relaxed)
+    }
+
+    pub fn set_content_hash(&self, path: &str, content_hash: &str) -> Result<(), String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        self.conn
+            .execute("UPDATE wallpapers SET content_hash = ?2 WHERE path = ?1", params![path, content_hash])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to save content hash: {}", e))
+    }
+
+    // The stored content hash, as long as the file still has the size and time it was hashed at
+    pub fn stored_content_hash(&self, path: &str, size: u64, modified_at: Option<i64>) -> Result<Option<String>, String> {
+        self.conn
+            .query_row(
+                "SELECT content_hash FROM wallpapers WHERE path = ?1 AND size = ?2 AND modified_at IS ?3",
+                params![path, size as i64, modified_at],
+                |row| row.get(0),
+            )
+            .optional()
+            .map(Option::flatten)
+            .map_err(|e| format!("Failed to query library: {}", e))
+    }
+
+    // The file was rewritten in place: store what is read from it now along with its new content
+    // hash, and drop what was derived from the old contents so it is indexed again
+    pub fn set_changed_contents(&mut self, info: &WallpaperInfo, content_hash: &str) -> Result<(), String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let next_position: i64 = tx
+            .query_row("SELECT COALESCE(MAX(position) + 1, 0) FROM wallpapers", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to query library: {}", e))?;
+        write_wallpaper(&tx, info, next_position, false)?;
+        tx.execute(
+            "UPDATE wallpapers SET
+                 content_hash = ?2, perceptual_hash = NULL, perceptual_hash_failed = 0,
+                 colors_indexed = 0, colors_failed = 0
+             WHERE path = ?1",
+            params![info.path, content_hash],
+        )
+        .map_err(|e| format!("Failed to update wallpaper: {}", e))?;
+        tx.commit().map_err(|e| format!("Failed to save library: {}", e))
+    }
+
+    pub fn set_perceptual_hash(&self, path: &str, perceptual_hash: u64) -> Result<(), String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        self.conn
+            .execute(
+                "UPDATE wallpapers SET perceptual_hash = ?2 WHERE path = ?1",
+                params![path, perceptual_hash as i64],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to save perceptual hash: {}", e))
+    }
+
+    pub fn set_perceptual_hash_failed(&self, path: &str) -> Result<(), String> {
+        self.conn
+            .execute("UPDATE wallpapers SET perceptual_hash_failed = 1 WHERE path = ?1", [path])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to update wallpaper: {}", e))
+    }
+
+    // Fold duplicates into the entry being kept: it inherits their tags, collection memberships,
+    // usage, favorite and block flags and the highest rating, then the duplicates are removed
+    pub fn merge_into(&mut self, keep: &str, others: &[String]) -> Result<(), String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        for other in others.iter().filter(|other| other.as_str() != keep) {
+            tx
+                .execute(
+                    "INSERT OR IGNORE INTO wallpaper_tags (wallpaper_id, tag_id)
+                     SELECT keep.id, tags.tag_id
+                     FROM wallpaper_tags tags, wallpapers keep, wallpapers other
+                     WHERE keep.path = ?1 AND other.path = ?2 AND tags.wallpaper_id = other.id",
+                    params![keep, other],
+                )
+                .and_then(|_| tx.execute(
+                    "INSERT OR IGNORE INTO collection_items (collection_id, wallpaper_id, position)
+                     SELECT items.collection_id, keep.id, items.position
+                     FROM collection_items items, wallpapers keep, wallpapers other
+                     WHERE keep.path = ?1 AND other.path = ?2 AND items.wallpaper_id = other.id",
+                    params![keep, other],
+                ))
+                .and_then(|_| tx.execute(
+                    "INSERT INTO usage_stats (wallpaper_id, times_shown, last_shown_at)
+                     SELECT keep.id, stats.times_shown, stats.last_shown_at
+                     FROM usage_stats stats, wallpapers keep, wallpapers other
+                     WHERE keep.path = ?1 AND other.path = ?2 AND stats.wallpaper_id = other.id
+                     ON CONFLICT(wallpaper_id) DO UPDATE SET
+                         times_shown = times_shown + excluded.times_shown,
+                         last_shown_at = MAX(COALESCE(last_shown_at, 0), COALESCE(excluded.last_shown_at, 0))",
+                    params![keep, other],
+                ))
+                .and_then(|_| tx.execute(
+                    "UPDATE wallpapers SET
+                         favorite = MAX(favorite, (SELECT favorite FROM wallpapers WHERE path = ?2)),
+                         blocked = MAX(blocked, (SELECT blocked FROM wallpapers WHERE path = ?2)),
+                         rating = NULLIF(MAX(
+                             COALESCE(rating, 0),
+                             COALESCE((SELECT rating FROM wallpapers WHERE path = ?2), 0)
+                         ), 0)
+                     WHERE path = ?1 AND EXISTS (SELECT 1 FROM wallpapers WHERE path = ?2)",
+                    params![keep, other],
+                ))
+                .and_then(|_| tx.execute("DELETE FROM wallpapers WHERE path = ?1", [other]))
+                .map_err(|e| format!("Failed to merge {}: {}", other, e))?;
+        }
+        tx.commit().map_err(|e| format!("Failed to save library: {}", e))
+    }
+
+    pub fn set_missing(&self, path: &str, missing: bool) -> Result<(), String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        self.conn
+            .execute("UPDATE wallpapers SET missing = ?2 WHERE path = ?1", params![path, missing])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to update wallpaper: {}", e))
+    }
+
+    // Point an existing row at a file's new location, keeping its ID so tags, collections and stats follow it
+    pub fn relink_wallpaper(&self, old_path: &str, info: &WallpaperInfo) -> Result<(), String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        let assignments: Vec<String> = FILE_COLUMNS.iter()
+            .enumerate()
+            .map(|(index, column)| format!("{} = ?{}", column, index + 3))
+            .collect();
+        let mut values: Vec<Value> = vec![old_path.to_string().into(), info.path.clone().into()];
+        values.extend(file_values(info));
+
+        self.conn
+            .execute(
+                &format!("UPDATE wallpapers SET path = ?2, missing = 0, {} WHERE path = ?1", assignments.join(", ")),
+                params_from_iter(values),
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to relink wallpaper: {}", e))
+    }
+
+    // Insert new wallpapers at the end and refresh existing ones in place.
+    // Returns the paths that were newly inserted.
+    pub fn upsert_wallpapers(&mut self, wallpapers: &[WallpaperInfo]) -> Result<HashSet<String>, String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let mut inserted = HashSet::new();
+        {
+            let mut next_position: i64 = tx
+                .query_row("SELECT COALESCE(MAX(position) + 1, 0) FROM wallpapers", [], |row| row.get(0))
+                .map_err(|e| format!("Failed to query library: {}", e))?;
+            let mut exists = tx
+                .prepare_cached("SELECT 1 FROM wallpapers WHERE path = ?1")
+                .map_err(|e| format!("Failed to query library: {}", e))?;
+
+            for info in wallpapers {
+                let is_new = !exists.exists([&info.path]).map_err(|e| format!("Failed to query library: {}", e))?;
+                write_wallpaper(&tx, info, next_position, false)?;
+                if is_new {
+                    inserted.insert(info.path.clone());
+                    next_position += 1;
+                }
+            }
+        }
+        tx.commit().map_err(|e| format!("Failed to save library: {}", e))?;
+        Ok(inserted)
+    }
+
+    pub fn remove_wallpapers(&mut self, paths: &[String]) -> Result<usize, String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let mut removed = 0;
+        {
+            let mut delete = tx
+                .prepare_cached("DELETE FROM wallpapers WHERE path = ?1")
+                .map_err(|e| format!("Failed to remove wallpapers: {}", e))?;
+            for path in paths {
+                removed += delete.execute([path]).map_err(|e| format!("Failed to remove wallpapers: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("Failed to save library: {}", e))?;
+        Ok(removed)
+    }
+
+    // Remove every wallpaper at or below a path, e.g. when a watched directory is deleted
+    pub fn remove_wallpapers_under(&mut self, root: &Path) -> Result<Vec<String>, String> {
+        self.hash_generation.fetch_add(1, Ordering::Relaxed);
+        let paths: Vec<String> = self.all_paths()?
+            .into_iter()
+            .filter(|path| Path::new(path).starts_with(root))
+            .collect();
+        self.remove_wallpapers(&paths)?;
+        Ok(paths)
+    }
+
+    pub fn record_shown(&self, path: &str) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO usage_stats (wallpaper_id, times_shown, last_shown_at)
+                 SELECT id, 1, ?2 FROM wallpapers WHERE path = ?1
+                 ON CONFLICT(wallpaper_id) DO UPDATE SET
+                     times_shown = times_shown + 1,
+                     last_shown_at = excluded.last_shown_at",
+                params![path, unix_now()],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record wallpaper usage: {}", e))
+    }
+
+    // By path, for the wallpapers that have been shown at least once
+    pub fn usage_stats(&self) -> Result<HashMap<String, UsageStats>, String> {
+        let mut statement = self.conn
+            .prepare_cached(
+                "SELECT w.path, u.times_shown, u.last_shown_at
+                 FROM usage_stats u JOIN wallpapers w ON w.id = u.wallpaper_id",
+            )
+            .map_err(|e| format!("Failed to query usage stats: {}", e))?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, UsageStats {
+                    times_shown: row.get::<_, i64>(1)? as u32,
+                    last_shown_at: row.get(2)?,
+                }))
+            })
+            .map_err(|e| format!("Failed to query usage stats: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query usage stats: {}", e))
+    }
+
+    // The shuffled pass a slideshow rotation is working through; `scope` tells the shared
+    // slideshow and each screen's own apart
+    pub fn rotation_order(&self, scope: &str) -> Result<Vec<String>, String> {
+        let mut statement = self.conn
+            .prepare_cached("SELECT path FROM rotation WHERE scope = ?1 ORDER BY position")
+            .map_err(|e| format!("Failed to query rotation: {}", e))?;
+        let rows = statement
+            .query_map([scope], |row| row.get(0))
+            .map_err(|e| format!("Failed to query rotation: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query rotation: {}", e))
+    }
+
+    // Most steps only move the cursor, so the order is rewritten only when it actually changed
+    pub fn set_rotation_order(&mut self, scope: &str, order: &[String]) -> Result<(), String> {
+        if self.rotation_order(scope)? == order {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction().map_err(|e| format!("Failed to start transaction: {}", e))?;
+        tx.execute("DELETE FROM rotation WHERE scope = ?1", [scope])
+            .map_err(|e| format!("Failed to save rotation: {}", e))?;
+        {
+            let mut insert = tx
+                .prepare_cached("INSERT INTO rotation (scope, position, path) VALUES (?1, ?2, ?3)")
+                .map_err(|e| format!("Failed to save rotation: {}", e))?;
+            for (position, path) in order.iter().enumerate() {
+                insert
+                    .execute(params![scope, position as i64, path])
+                    .map_err(|e| format!("Failed to save rotation: {}", e))?;
+            }
+        }
+        tx.commit().map_err(|e| format!("Failed to save rotation: {}", e))
+    }
+
+    pub fn record_scan(&self, folder: &str, file_count: usize, duration_ms: u64) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO scan_metadata (folder, last_scan_at, file_count, duration_ms)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT(folder) DO UPDATE SET
+                     last_scan_at = excluded.last_scan_at,
+                     file_count = excluded.file_count,
+                     duration_ms = excluded.duration_ms",
+                params![folder, unix_now(), file_count as i64, duration_ms as i64],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record scan: {}", e))
+    }
+
+    fn all_paths(&self) -> Result<Vec<String>, String> {
+        let mut statement = self.conn
+            .prepare_cached("SELECT path FROM wallpapers")
+            .map_err(|e| format!("Failed to query library: {}", e))?;
+        let rows = statement
+            .query_map([], |row| row.get(0))
+            .map_err(|e| format!("Failed to query library: {}", e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| format!("Failed to query library: {}", e))
+    }
+}
+
+// Insert or update one row. Unchanged rows are left alone, so re-saving the whole list stays cheap.
+// New rows take `position`; existing rows only move to it when `reposition` is set.
+// User-set attributes are not touched here; they are changed only through `update_attributes`.
+fn write_wallpaper(conn: &Connection, info: &WallpaperInfo, position: i64, reposition: bool) -> Result<(), String> {
+    let placeholders: Vec<String> = (0..FILE_COLUMNS.len())
+        .map(|index| format!("?{}", index + 6))
+        .collect();
+    let updates: Vec<String> = FILE_COLUMNS.iter()
+        .map(|column| match *column {
+            "modified_at" => "modified_at = COALESCE(excluded.modified_at, modified_at)".to_string(),
+            column => format!("{0} = excluded.{0}", column),
+        })
+        .collect();
+    let changes: Vec<String> = FILE_COLUMNS.iter()
+        .map(|column| format!("{0} IS NOT excluded.{0}", column))
+        .collect();
+
+    // A size change means new contents, so the stored hashes and colors no longer apply
+    let sql = format!(
+        "INSERT INTO wallpapers (path, position, added_at, missing, {columns})
+         VALUES (?1, ?2, ?3, ?4, {placeholders})
+         ON CONFLICT(path) DO UPDATE SET
+             content_hash = CASE WHEN size = excluded.size THEN content_hash ELSE NULL END,
+             perceptual_hash = CASE WHEN size = excluded.size THEN perceptual_hash ELSE NULL END,
+             perceptual_hash_failed = CASE WHEN size = excluded.size THEN perceptual_hash_failed ELSE 0 END,
+             colors_indexed = CASE WHEN size = excluded.size THEN colors_indexed ELSE 0 END,
+             colors_failed = CASE WHEN size = excluded.size THEN colors_failed ELSE 0 END,
+             missing = excluded.missing,
+             position = CASE WHEN ?5 THEN excluded.position ELSE position END,
+             {updates}
+         WHERE {changes} OR missing != excluded.missing
+             OR (?5 AND position != excluded.position)",
+        columns = FILE_COLUMNS.join(", "),
+        placeholders = placeholders.join(", "),
+        updates = updates.join(", "),
+        changes = changes.join(" OR "),
+    );
+
+    let mut values: Vec<Value> = vec![
+        info.path.clone().into(),
+        position.into(),
+        unix_now().into(),
+        info.is_missing.into(),
+        reposition.into(),
+    ];
+    values.extend(file_values(info));
+
+    conn.prepare_cached(&sql)
+        .and_then(|mut statement| statement.execute(params_from_iter(values)))
+        .map(|_| ())
+        .map_err(|e| format!("Failed to save wallpaper: {}", e))
+}
+
+// Values for `FILE_COLUMNS`, in the same order
+fn file_values(info: &WallpaperInfo) -> Vec<Value> {
+    let video = info.video.as_ref();
+    vec![
+        info.name.clone().into(),
+        info.file_type.clone().into(),
+        media_kind_name(info.media_kind).into(),
+        (info.size as i64).into(),
+        info.width.into(),
+        info.height.into(),
+        info.captured_at.clone().into(),
+        info.is_valid.into(),
+        info.modified_at.into(),
+        info.orientation.into(),
+        info.mime_type.clone().into(),
+        info.invalid_reason.clone().into(),
+        info.camera_model.clone().into(),
+        info.gps_location.map(|location| location.latitude).into(),
+        info.gps_location.map(|location| location.longitude).into(),
+        video.and_then(|video| video.duration_secs).into(),
+        video.and_then(|video| video.width).into(),
+        video.and_then(|video| video.height).into(),
+        video.and_then(|video| video.frame_rate).into(),
+        video.and_then(|video| video.codec.clone()).into(),
+        video.map(|video| video.has_audio).into(),
+        video.and_then(|video| video.frame_count).map(|count| count as i64).into(),
+    ]
+}
+
+fn collection_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Collection name cannot be empty".to_string());
+    }
+    Ok(name)
+}
+
+// Returns the trimmed name
+fn check_solar_set(set: &SolarSet) -> Result<&str, String> {
+    let name = set.name.trim();
+    if name.is_empty() {
+        return Err("Solar set name cannot be empty".to_string());
+    }
+    if !(-90.0..=90.0).contains(&set.latitude) || !(-180.0..=180.0).contains(&set.longitude) {
+        return Err(format!("Invalid coordinates: {}, {}", set.latitude, set.longitude));
+    }
+    Ok(name)
+}
+
+fn read_solar_set(row: &Row) -> rusqlite::Result<SolarSet> {
+    Ok(SolarSet {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        latitude: row.get(2)?,
+        longitude: row.get(3)?,
+        location: row.get(4)?,
+        variants: SolarVariants {
+            day: row.get(5)?,
+            golden_hour: row.get(6)?,
+            dusk: row.get(7)?,
+            night: row.get(8)?,
+        },
+    })
+}
+
+// A copy with the name trimmed and the frames sorted by keyframe
+fn check_dynamic_set(set: &DynamicSet) -> Result<DynamicSet, String> {
+    let name = set.name.trim();
+    if name.is_empty() {
+        return Err("Dynamic set name cannot be empty".to_string());
+    }
+    if set.period == 0 {
+        return Err("A dynamic set's cycle cannot be empty".to_string());
+    }
+
+    let mut frames = set.frames.clone();
+    frames.sort_by_key(|frame| frame.at);
+    for (index, frame) in frames.iter().enumerate() {
+        if frame.path.trim().is_empty() {
+            return Err("Every frame of a dynamic set needs a picture".to_string());
+        }
+        if frame.at >= set.period {
+            return Err(format!("Keyframe at {}s is past the end of the {}s cycle", frame.at, set.period));
+        }
+        // The fade into a frame has to fit after the previous keyframe, wrapping around the cycle
+        let previous = if index == 0 {
+            frames.last().map(|last| last.at as i64 - set.period as i64)
+        } else {
+            Some(frames[index - 1].at as i64)
+        };
+        if let Some(previous) = previous.filter(|_| frames.len() > 1) {
+            if previous == frame.at as i64 {
+                return Err(format!("Two frames share the keyframe at {}s", frame.at));
+            }
+            if (frame.at as i64 - previous) < frame.transition as i64 {
+                return Err(format!("The fade into the frame at {}s starts before the previous keyframe", frame.at));
+            }
+        }
+    }
+
+    Ok(DynamicSet {
+        name: name.to_string(),
+        frames,
+        ..set.clone()
+    })
+}
+
+fn read_dynamic_set(row: &Row) -> rusqlite::Result<DynamicSet> {
+    Ok(DynamicSet {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        period: row.get(2)?,
+        frames: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+    })
+}
+
+// Unreadable settings fall back to the global ones rather than hiding the collection
+fn read_collection(row: &Row) -> rusqlite::Result<Collection> {
+    Ok(Collection {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        item_count: row.get::<_, i64>(2)? as usize,
+        settings: row.get::<_, Option<String>>(3)?
+            .and_then(|json| serde_json::from_str(&json).ok()),
+    })
+}
+
+// Reads the columns in the order `SELECT_WALLPAPERS` lists them
+fn read_row(row: &Row) -> rusqlite::Result<WallpaperInfo> {
+    let latitude: Option<f64> = row.get(15)?;
+    let longitude: Option<f64> = row.get(16)?;
+    // `has_audio` is always written for videos, so it tells whether there is video metadata at all
+    let video = match row.get::<_, Option<bool>>(22)? {
+        Some(has_audio) => Some(VideoMetadata {
+            duration_secs: row.get(17)?,
+            width: row.get(18)?,
+            height: row.get(19)?,
+            frame_rate: row.get(20)?,
+            codec: row.get(21)?,
+            has_audio,
+            frame_count: row.get::<_, Option<i64>>(23)?.map(|count| count as u64),
+        }),
+        None => None,
+    };
+
+    let mut tags: Vec<String> = row.get::<_, Option<String>>(27)?
+        .map(|tags| tags.split('\u{1f}').map(str::to_string).collect())
+        .unwrap_or_default();
+    tags.sort_by_key(|tag| tag.to_lowercase());
+
+    Ok(WallpaperInfo {
+        path: row.get(0)?,
+        name: row.get(1)?,
+        file_type: row.get(2)?,
+        size: row.get::<_, i64>(3)? as u64,
+        modified_at: row.get(4)?,
+        mime_type: row.get(5)?,
+        media_kind: parse_media_kind(&row.get::<_, String>(6)?),
+        is_valid: row.get(7)?,
+        invalid_reason: row.get(8)?,
+        is_missing: row.get(9)?,
+        width: row.get(10)?,
+        height: row.get(11)?,
+        orientation: row.get(12)?,
+        captured_at: row.get(13)?,
+        camera_model: row.get(14)?,
+        gps_location: latitude.zip(longitude).map(|(latitude, longitude)| GpsLocation { latitude, longitude }),
+        video,
+        tags,
+        favorite: row.get(24)?,
+        rating: row.get(25)?,
+        blocked: row.get(26)?,
+        palette: row.get::<_, Option<String>>(28)?
+            .map(|palette| palette.split(',').map(str::to_string).collect())
+            .unwrap_or_default(),
+    })
+}
+
+// Favorite, rating and tag conditions shared by the slideshow filter and library queries
+fn push_attribute_conditions(
+    conditions: &mut Vec<String>,
+    values: &mut Vec<Value>,
+    tags: &[String],
+    favorites_only: bool,
+    min_rating: Option<u8>,
+) {
+    if favorites_only {
+        conditions.push("w.favorite = 1".to_string());
+    }
+    if let Some(min_rating) = min_rating {
+        conditions.push("w.rating >= ?".to_string());
+        values.push(Value::Integer(min_rating as i64));
+    }
+    let tags = normalize_tags(tags);
+    if !tags.is_empty() {
+        // Matching any of the selected tags, so picking several widens the selection
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM wallpaper_tags wt JOIN tags t ON t.id = wt.tag_id
+             WHERE wt.wallpaper_id = w.id AND t.name IN ({}))",
+            placeholders(tags.len()),
+        ));
+        values.extend(tags.into_iter().map(Value::Text));
+    }
+}
+
+// Color and hue conditions shared by the slideshow filter and library queries. Only palette
+// colors that cover a fair part of the picture count, so a small red sign does not make a beach red.
+fn push_color_conditions(
+    conditions: &mut Vec<String>,
+    values: &mut Vec<Value>,
+    color: Option<&str>,
+    max_distance: Option<f64>,
+    hue_range: (Option<f64>, Option<f64>),
+) -> Result<(), String> {
+    if let Some(color) = color {
+        let [l, a, b] = rgb_to_lab(parse_hex_color(color)?);
+        let distance = max_distance.unwrap_or(DEFAULT_COLOR_DISTANCE);
+        // Compared squared so SQLite does not need a square root
+        conditions.push(
+            "EXISTS (SELECT 1 FROM wallpaper_colors c WHERE c.wallpaper_id = w.id AND c.weight >= ?
+             AND (c.lab_l - ?) * (c.lab_l - ?) + (c.lab_a - ?) * (c.lab_a - ?) + (c.lab_b - ?) * (c.lab_b - ?) <= ?)"
+                .to_string(),
+        );
+        values.push(Value::Real(MIN_COLOR_WEIGHT));
+        for component in [l, a, b] {
+            values.extend([Value::Real(component), Value::Real(component)]);
+        }
+        values.push(Value::Real(distance * distance));
+    }
+
+    let hue_range = (hue_range.0.map(|hue| hue.clamp(0.0, 360.0)), hue_range.1.map(|hue| hue.clamp(0.0, 360.0)));
+    let hue_condition = match hue_range {
+        (Some(min), Some(max)) if min > max => Some("(c.hue >= ? OR c.hue <= ?)"),
+        (Some(_), Some(_)) => Some("c.hue BETWEEN ? AND ?"),
+        (Some(_), None) => Some("c.hue >= ?"),
+        (None, Some(_)) => Some("c.hue <= ?"),
+        (None, None) => None,
+    };
+    if let Some(hue_condition) = hue_condition {
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM wallpaper_colors c WHERE c.wallpaper_id = w.id AND c.weight >= ? AND {})",
+            hue_condition,
+        ));
+        values.push(Value::Real(MIN_COLOR_WEIGHT));
+        values.extend([hue_range.0, hue_range.1].into_iter().flatten().map(Value::Real));
+    }
+    Ok(())
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+// LIKE treats % and _ as wildcards, so escape them along with the escape character itself
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+fn is_iso_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(index, byte)| match index {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        })
+}
+
+// Trimmed, non-empty and without case-insensitive repeats, matching the tags table's collation
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()))
+        .map(str::to_string)
+        .collect()
+}
+
+fn media_kind_name(kind: MediaKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|value| value.as_str().map(|kind| kind.to_string()))
+        .unwrap_or_default()
+}
+
+fn parse_media_kind(name: &str) -> MediaKind {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).unwrap_or_default()
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn open_library_db(app: &AppHandle<Wry>) -> Result<(), String> {
+    let path = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))?
+        .join(LIBRARY_DB_FILE);
+    let mut db = LibraryDb::open(&path)?;
+
+    // Done before any command or background task can read the library without the old list
+    if let Err(_e) = migrate_wallpaper_list(app, &mut db) {
+        #[cfg(debug_assertions)]
+        eprintln!("Failed to migrate wallpaper list: {}", _e);
+    }
+
+    *app.state::<AppState>().library_db.lock().unwrap() = Some(db);
+    Ok(())
+}
+
+// Run `f` against the library database opened at startup
+pub fn with_library_db<T>(
+    app: &AppHandle<Wry>,
+    f: impl FnOnce(&mut LibraryDb) -> Result<T, String>,
+) -> Result<T, String> {
+    let app_state = app.state::<AppState>();
+    let mut library_db = app_state.library_db.lock().unwrap();
+    let db = library_db.as_mut().ok_or_else(|| "Library database is not open".to_string())?;
+    f(db)
+}
+
+// Move the wallpaper list out of the settings store the first time the database is used
+fn migrate_wallpaper_list(app: &AppHandle<Wry>, db: &mut LibraryDb) -> Result<(), String> {
+    let mut current_state = read_app_state(app)?;
+    if current_state.wallpaper_list.is_empty() {
+        return Ok(());
+    }
+
+    if db.is_empty()? {
+        db.upsert_wallpapers(&current_state.wallpaper_list)?;
+    }
+
+    current_state.wallpaper_list.clear();
+    write_app_state(app, &current_state)
+}
\ No newline at end of file
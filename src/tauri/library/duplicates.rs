@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tauri::{AppHandle, Manager, Wry};
+use crate::library::db::with_library_db;
+use crate::state::AppState;
+use crate::types::{DuplicateGroup, DuplicateItem, DuplicateKind, WallpaperInfo};
+use crate::utils::file_utils::content_hash;
+use crate::utils::perceptual_hash::{difference_hash, hamming_distance};
+use crate::utils::thumbnails::get_or_create_cached_thumbnail;
+
+// Hashes this many bits apart or fewer count as the same picture
+pub const DEFAULT_MAX_DISTANCE: u32 = 6;
+
+// Hashing works from the smallest thumbnail, which is cheap to decode and exists for videos too
+const HASH_THUMBNAIL_SIZE: u32 = 128;
+
+// Groups found for a database hash generation, so repeated lookups skip grouping again
+pub struct DuplicateCache {
+    generation: u64,
+    max_distance: u32,
+    groups: Vec<DuplicateGroup>,
+}
+
+struct HashedWallpaper {
+    info: WallpaperInfo,
+    content_hash: Option<String>,
+    perceptual_hash: Option<u64>,
+}
+
+// Fill in content and perceptual hashes for entries that do not have them yet; returns how many were hashed
+pub fn index_library_hashes(app: &AppHandle<Wry>, thumbnail_root: &Path) -> Result<usize, String> {
+    let entries = with_library_db(app, |db| db.list_entries())?;
+    let mut hashed = 0;
+
+    // The database lock is only taken per write so the UI is not blocked while hashing
+    for entry in entries.into_iter().filter(|entry| !entry.missing) {
+        let path = Path::new(&entry.path);
+        if !path.is_file() {
+            continue;
+        }
+
+        let new_content_hash = match entry.content_hash {
+            Some(_) => None,
+            None => content_hash(path).ok(),
+        };
+        if let Some(hash) = new_content_hash {
+            with_library_db(app, |db| db.set_content_hash(&entry.path, &hash))?;
+        }
+
+        // Files that could not be hashed before are skipped until their contents change
+        if entry.perceptual_hash.is_some() || entry.perceptual_hash_failed {
+            continue;
+        }
+        match get_or_create_cached_thumbnail(thumbnail_root, path, HASH_THUMBNAIL_SIZE)
+            .and_then(|thumbnail| difference_hash(&thumbnail))
+        {
+            Ok(hash) => {
+                with_library_db(app, |db| db.set_perceptual_hash(&entry.path, hash))?;
+                hashed += 1;
+            }
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("Failed to hash {}: {}", entry.path, _e);
+
+                with_library_db(app, |db| db.set_perceptual_hash_failed(&entry.path))?;
+            }
+        }
+    }
+
+    Ok(hashed)
+}
+
+// Group library entries that are exact or near copies of each other, using the stored hashes.
+// The best remaining entry starts each group and only entries close to it join, so a chain of
+// slightly different pictures does not end up as one group.
+pub fn find_duplicate_groups(app: &AppHandle<Wry>, max_distance: u32) -> Result<Vec<DuplicateGroup>, String> {
+    let wallpapers = load_hashed_wallpapers(app)?;
+
+    let mut by_content: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut tree = HashTree::default();
+    for (index, wallpaper) in wallpapers.iter().enumerate() {
+        if let Some(hash) = wallpaper.content_hash.as_deref() {
+            by_content.entry(hash).or_default().push(index);
+        }
+        if let Some(hash) = wallpaper.perceptual_hash {
+            tree.insert(hash, index);
+        }
+    }
+
+    let mut order: Vec<usize> = (0..wallpapers.len()).collect();
+    order.sort_by(|&a, &b| {
+        quality_key(&wallpapers[b].info).cmp(&quality_key(&wallpapers[a].info))
+            .then_with(|| wallpapers[a].info.path.cmp(&wallpapers[b].info.path))
+    });
+
+    let mut grouped = vec![false; wallpapers.len()];
+    let mut groups = Vec::new();
+    for representative in order {
+        if grouped[representative] {
+            continue;
+        }
+        grouped[representative] = true;
+
+        let mut indices = vec![representative];
+        if let Some(hash) = wallpapers[representative].perceptual_hash {
+            for index in tree.within(hash, max_distance) {
+                if !grouped[index] {
+                    grouped[index] = true;
+                    indices.push(index);
+                }
+            }
+        }
+
+        // Byte-identical copies of a member are exactly as close to the representative as it is
+        let mut next = 0;
+        while next < indices.len() {
+            let copies = wallpapers[indices[next]].content_hash.as_deref().and_then(|hash| by_content.get(hash));
+            for &index in copies.into_iter().flatten() {
+                if !grouped[index] {
+                    grouped[index] = true;
+                    indices.push(index);
+                }
+            }
+            next += 1;
+        }
+
+        if indices.len() > 1 {
+            groups.push(build_group(&wallpapers, &indices));
+        }
+    }
+
+    groups.sort_by(|a, b| b.similarity.total_cmp(&a.similarity).then_with(|| a.best.cmp(&b.best)));
+    Ok(groups)
+}
+
+// Same as find_duplicate_groups, but reuses the last result until a hash or library entry changes
+pub fn cached_duplicate_groups(app: &AppHandle<Wry>, max_distance: u32) -> Result<Vec<DuplicateGroup>, String> {
+    let generation = with_library_db(app, |db| Ok(db.hash_generation()))?;
+    let state = app.state::<AppState>();
+    if let Some(cache) = state.duplicate_groups.lock().unwrap().as_ref()
+        && cache.generation == generation
+        && cache.max_distance == max_distance
+    {
+        return Ok(cache.groups.clone());
+    }
+
+    let groups = find_duplicate_groups(app, max_distance)?;
+    *state.duplicate_groups.lock().unwrap() = Some(DuplicateCache {
+        generation,
+        max_distance,
+        groups: groups.clone(),
+    });
+    Ok(groups)
+}
+
+// Keep only the best of the given wallpapers in every duplicate group, preserving the original order.
+// Copies that are not among them, such as ones outside the active collection, do not count.
+pub fn collapse_duplicates(wallpapers: Vec<WallpaperInfo>, groups: &[DuplicateGroup]) -> Vec<WallpaperInfo> {
+    let hidden: HashSet<String> = {
+        let candidates: HashMap<&str, &WallpaperInfo> = wallpapers.iter()
+            .map(|wallpaper| (wallpaper.path.as_str(), wallpaper))
+            .collect();
+
+        groups.iter()
+            .flat_map(|group| {
+                let present: Vec<&WallpaperInfo> = group.items.iter()
+                    .filter_map(|item| candidates.get(item.wallpaper.path.as_str()).copied())
+                    .collect();
+                let best = best_wallpaper(present.iter().copied()).map(|wallpaper| wallpaper.path.clone());
+                present.into_iter()
+                    .filter(move |wallpaper| Some(&wallpaper.path) != best.as_ref())
+                    .map(|wallpaper| wallpaper.path.clone())
+            })
+            .collect()
+    };
+
+    wallpapers.into_iter()
+        .filter(|wallpaper| !hidden.contains(&wallpaper.path))
+        .collect()
+}
+
+pub fn best_wallpaper<'a>(wallpapers: impl IntoIterator<Item = &'a WallpaperInfo>) -> Option<&'a WallpaperInfo> {
+    wallpapers.into_iter().max_by_key(|wallpaper| quality_key(wallpaper))
+}
+
+// Highest resolution wins, then the larger file since it is likely the less compressed one
+fn quality_key(wallpaper: &WallpaperInfo) -> (u64, u64) {
+    let pixels = wallpaper.width.unwrap_or(0) as u64 * wallpaper.height.unwrap_or(0) as u64;
+    (pixels, wallpaper.size)
+}
+
+fn load_hashed_wallpapers(app: &AppHandle<Wry>) -> Result<Vec<HashedWallpaper>, String> {
+    let (wallpapers, entries) = with_library_db(app, |db| Ok((db.list_wallpapers()?, db.list_entries()?)))?;
+    let mut hashes: HashMap<String, (Option<String>, Option<u64>)> = entries.into_iter()
+        .map(|entry| (entry.path, (entry.content_hash, entry.perceptual_hash)))
+        .collect();
+
+    Ok(wallpapers.into_iter()
+        .filter(|info| info.is_valid && !info.is_missing)
+        .map(|info| {
+            let (content_hash, perceptual_hash) = hashes.remove(&info.path).unwrap_or_default();
+            HashedWallpaper { info, content_hash, perceptual_hash }
+        })
+        .collect())
+}
+
+// `indices` starts with the group's representative, which is also its best copy
+fn build_group(wallpapers: &[HashedWallpaper], indices: &[usize]) -> DuplicateGroup {
+    let best = &wallpapers[indices[0]];
+
+    let items: Vec<DuplicateItem> = indices.iter()
+        .map(|&index| {
+            let wallpaper = &wallpapers[index];
+            let exact = wallpaper.content_hash.is_some() && wallpaper.content_hash == best.content_hash;
+            let similarity = match (wallpaper.perceptual_hash, best.perceptual_hash) {
+                _ if exact => 1.0,
+                (Some(a), Some(b)) => 1.0 - hamming_distance(a, b) as f64 / 64.0,
+                _ => 0.0,
+            };
+            DuplicateItem {
+                wallpaper: wallpaper.info.clone(),
+                similarity,
+            }
+        })
+        .collect();
+
+    let exact = indices.iter()
+        .all(|&index| wallpapers[index].content_hash.is_some() && wallpapers[index].content_hash == best.content_hash);
+
+    DuplicateGroup {
+        kind: if exact { DuplicateKind::Exact } else { DuplicateKind::Similar },
+        best: best.info.path.clone(),
+        similarity: items.iter().map(|item| item.similarity).fold(1.0, f64::min),
+        items,
+    }
+}
+
+// BK-tree over perceptual hashes: children are keyed by their distance to the parent, so a search
+// only descends into branches the triangle inequality leaves open instead of comparing every pair
+#[derive(Default)]
+struct HashTree {
+    nodes: Vec<HashNode>,
+}
+
+struct HashNode {
+    hash: u64,
+    index: usize,
+    children: Vec<(u32, usize)>,
+}
+
+impl HashTree {
+    fn insert(&mut self, hash: u64, index: usize) {
+        let node = HashNode { hash, index, children: Vec::new() };
+        if self.nodes.is_empty() {
+            self.nodes.push(node);
+            return;
+        }
+
+        let mut current = 0;
+        loop {
+            let distance = hamming_distance(self.nodes[current].hash, hash);
+            match self.nodes[current].children.iter().find(|(child_distance, _)| *child_distance == distance) {
+                Some(&(_, child)) => current = child,
+                None => {
+                    let added = self.nodes.len();
+                    self.nodes[current].children.push((distance, added));
+                    self.nodes.push(node);
+                    return;
+                }
+            }
+        }
+    }
+
+    // Indices of every hash at most `max_distance` bits from `hash`
+    fn within(&self, hash: u64, max_distance: u32) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut pending = if self.nodes.is_empty() { Vec::new() } else { vec![0] };
+
+        while let Some(current) = pending.pop() {
+            let node = &self.nodes[current];
+            let distance = hamming_distance(node.hash, hash);
+            if distance <= max_distance {
+                found.push(node.index);
+            }
+            pending.extend(node.children.iter()
+                .filter(|(child_distance, _)| child_distance.abs_diff(distance) <= max_distance)
+                .map(|&(_, child)| child));
+        }
+        found
+    }
+}
\ No newline at end of file
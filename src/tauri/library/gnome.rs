@@ -0,0 +1,407 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use chrono::DateTime;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use walkdir::WalkDir;
+use crate::commands::load_app_state;
+use crate::library::collections::unique_collection_name;
+use crate::library::db::with_library_db;
+use crate::library::watcher::{merge_wallpapers, LIBRARY_CHANGED_EVENT};
+use crate::types::{DynamicFrame, DynamicSet, GnomeImport, LibraryChange, MediaKind, WallpaperSettings};
+use crate::utils::file_utils::has_supported_extension;
+use crate::utils::scanner::build_wallpaper_info;
+
+// Where distributions and users keep slideshows and the lists GNOME Settings reads backgrounds from
+const SYSTEM_BACKGROUNDS_DIR: &str = "/usr/share/backgrounds";
+const SYSTEM_PROPERTIES_DIR: &str = "/usr/share/gnome-background-properties";
+const PROPERTIES_DIR: &str = "gnome-background-properties";
+
+// Exported slideshows start on this day, at local midnight
+const EXPORT_EPOCH: i64 = 946684800;
+
+// Crossfade used between collection wallpapers when exporting
+pub const DEFAULT_EXPORT_TRANSITION: u32 = 5;
+
+// Just enough of an XML tree to read GNOME's background files
+#[derive(Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.child(name).map(|child| child.text.trim()).filter(|text| !text.is_empty())
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+// GNOME slideshows and background lists found in the standard places, for the import dialog
+pub fn find_gnome_backgrounds(app: &AppHandle<Wry>) -> Vec<String> {
+    let mut roots = vec![
+        (PathBuf::from(SYSTEM_BACKGROUNDS_DIR), 2),
+        (PathBuf::from(SYSTEM_PROPERTIES_DIR), 1),
+    ];
+    if let Ok(data_dir) = app.path().data_dir() {
+        roots.push((data_dir.join(PROPERTIES_DIR), 1));
+        roots.push((data_dir.join("backgrounds"), 2));
+    }
+
+    let mut found: Vec<String> = roots.into_iter()
+        .flat_map(|(root, depth)| WalkDir::new(root).max_depth(depth).into_iter().filter_map(|entry| entry.ok()))
+        .filter(|entry| entry.file_type().is_file() && is_xml(entry.path()))
+        .map(|entry| entry.path().to_string_lossy().to_string())
+        .collect();
+    found.sort();
+    found
+}
+
+// Import a slideshow as a dynamic set, or every background listed in a gnome-background-properties
+// file: slideshows become dynamic sets and plain pictures are added to the library and gathered
+// into a collection named after the file
+pub fn import_gnome_backgrounds(app: &AppHandle<Wry>, path: &Path) -> Result<GnomeImport, String> {
+    let root = read_xml(path)?;
+    let mut import = GnomeImport::default();
+
+    match root.name.as_str() {
+        "background" => {
+            let set = slideshow_to_dynamic_set(&root, path, &file_stem(path))?;
+            import.dynamic_sets.push(with_library_db(app, |db| db.create_dynamic_set(&set))?);
+        }
+        "wallpapers" => {
+            let mut pictures = Vec::new();
+            for wallpaper in root.children.iter().filter(|child| child.name == "wallpaper") {
+                if wallpaper.attribute("deleted") == Some("true") {
+                    continue;
+                }
+                let name = wallpaper.child_text("name").map(str::to_string).unwrap_or_else(|| file_stem(path));
+
+                // Newer entries pair a light picture with a dark one
+                for filename in ["filename", "filename-dark"].iter().filter_map(|tag| wallpaper.child_text(tag)) {
+                    let file = resolve(path, filename);
+                    if !is_xml(&file) {
+                        pictures.push(file);
+                        continue;
+                    }
+                    let set = read_xml(&file).and_then(|slideshow| slideshow_to_dynamic_set(&slideshow, &file, &name));
+                    match set {
+                        Ok(set) => import.dynamic_sets.push(with_library_db(app, |db| db.create_dynamic_set(&set))?),
+                        Err(_) => import.skipped.push(file.to_string_lossy().to_string()),
+                    }
+                }
+            }
+
+            let (paths, skipped) = add_pictures(app, pictures)?;
+            import.skipped.extend(skipped);
+            if !paths.is_empty() {
+                import.collection = Some(with_library_db(app, |db| {
+                    let name = unique_collection_name(&db.list_collections()?, &file_stem(path));
+                    let collection = db.create_collection(&name)?;
+                    db.add_to_collection(collection.id, &paths)?;
+                    db.get_collection(collection.id)?
+                        .ok_or_else(|| "Imported collection disappeared".to_string())
+                })?);
+            }
+        }
+        other => return Err(format!("{} is not a GNOME background file (found <{}>)", path.display(), other)),
+    }
+
+    Ok(import)
+}
+
+// A collection as a dynamic set that shows each picture for the slideshow interval in effect for it.
+// Videos and animations are left out since GNOME only shows still pictures.
+pub async fn collection_to_dynamic_set(app: &AppHandle<Wry>, id: i64, transition: u32) -> Result<DynamicSet, String> {
+    let (collection, wallpapers) = with_library_db(app, |db| Ok((db.get_collection(id)?, db.collection_wallpapers(id)?)))?;
+    let collection = collection.ok_or_else(|| format!("Collection {} does not exist", id))?;
+    let interval = collection.settings.clone()
+        .or(load_app_state(app.clone()).await?.wallpaper_settings)
+        .unwrap_or_else(WallpaperSettings::default)
+        .interval
+        .max(1);
+
+    let paths: Vec<String> = wallpapers.into_iter()
+        .filter(|info| info.is_valid && !info.is_missing && info.media_kind == MediaKind::Static)
+        .map(|info| info.path)
+        .collect();
+    if paths.is_empty() {
+        return Err(format!("{} has no still pictures to export", collection.name));
+    }
+
+    Ok(DynamicSet {
+        id: collection.id,
+        name: collection.name,
+        period: interval * paths.len() as u32,
+        frames: paths.into_iter()
+            .enumerate()
+            .map(|(index, path)| DynamicFrame {
+                path,
+                at: index as u32 * interval,
+                transition: transition.min(interval),
+            })
+            .collect(),
+    })
+}
+
+// Write a dynamic set as a GNOME slideshow. With `register` it is also listed in the user's
+// gnome-background-properties so it can be picked in GNOME Settings.
+pub fn export_gnome_slideshow(app: &AppHandle<Wry>, set: &DynamicSet, path: &Path, register: bool) -> Result<(), String> {
+    std::fs::write(path, slideshow_xml(set)?).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    if !register {
+        return Ok(());
+    }
+
+    let properties_dir = app.path().data_dir()
+        .map_err(|e| format!("Failed to resolve data directory: {}", e))?
+        .join(PROPERTIES_DIR);
+    std::fs::create_dir_all(&properties_dir)
+        .map_err(|e| format!("Failed to create {}: {}", properties_dir.display(), e))?;
+    let properties_path = properties_dir.join(format!("wallora-{}.xml", file_stem(path)));
+
+    let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let contents = format!(
+        "<?xml version=\"1.0\"?>\n<!DOCTYPE wallpapers SYSTEM \"gnome-wp-list.dtd\">\n<wallpapers>\n  <wallpaper deleted=\"false\">\n    <name>{}</name>\n    <filename>{}</filename>\n    <options>zoom</options>\n  </wallpaper>\n</wallpapers>\n",
+        escape(&set.name),
+        escape(absolute.to_string_lossy()),
+    );
+    std::fs::write(&properties_path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", properties_path.display(), e))
+}
+
+// GNOME plays a slideshow as a list of pictures each held for a while and then blended into the
+// next, repeating from a start time; keyframes fall where each picture is fully shown
+fn slideshow_to_dynamic_set(root: &Element, path: &Path, name: &str) -> Result<DynamicSet, String> {
+    if root.name != "background" {
+        return Err(format!("{} is not a GNOME slideshow", path.display()));
+    }
+
+    let mut frames: Vec<DynamicFrame> = Vec::new();
+    let mut elapsed = 0.0_f64;
+    let mut pending_transition = 0.0_f64;
+    for entry in &root.children {
+        let duration: f64 = entry.child_text("duration").and_then(|text| text.parse().ok()).unwrap_or(0.0);
+        match entry.name.as_str() {
+            "static" => {
+                let file = static_file(entry).ok_or_else(|| format!("{}: a picture has no file", path.display()))?;
+                frames.push(DynamicFrame {
+                    path: resolve(path, file).to_string_lossy().to_string(),
+                    at: elapsed.round() as u32,
+                    transition: pending_transition.round() as u32,
+                });
+                pending_transition = 0.0;
+            }
+            "transition" => pending_transition = duration,
+            _ => continue,
+        }
+        elapsed += duration.max(0.0);
+    }
+
+    let period = elapsed.round() as u32;
+    if frames.is_empty() || period == 0 {
+        return Err(format!("{} has no pictures to show", path.display()));
+    }
+    // The closing transition leads back into the first picture
+    if frames.len() > 1 && pending_transition > 0.0 {
+        frames[0].transition = pending_transition.round() as u32;
+    }
+
+    // Line the cycle up with the slideshow's start time, read as local time like GNOME does
+    let start = root.child("starttime").map(|start| {
+        let field = |tag: &str| start.child_text(tag).and_then(|text| text.parse::<i64>().ok()).unwrap_or(0);
+        chrono::NaiveDate::from_ymd_opt(field("year") as i32, field("month") as u32, field("day") as u32)
+            .and_then(|date| date.and_hms_opt(field("hour") as u32, field("minute") as u32, field("second") as u32))
+            .map(|time| time.and_utc().timestamp())
+            .unwrap_or(0)
+    });
+    let offset = start.unwrap_or(0).rem_euclid(period as i64) as u32;
+    for frame in &mut frames {
+        frame.at = (frame.at + offset) % period;
+    }
+
+    Ok(DynamicSet {
+        id: 0,
+        name: name.to_string(),
+        period,
+        frames,
+    })
+}
+
+fn slideshow_xml(set: &DynamicSet) -> Result<String, String> {
+    let frames = &set.frames;
+    let Some(first) = frames.first() else {
+        return Err(format!("{} has no pictures to export", set.name));
+    };
+
+    // The slideshow starts with the first picture fully shown, so start at one of its keyframes
+    let period = set.period as i64;
+    let start = EXPORT_EPOCH + (first.at as i64 - EXPORT_EPOCH).rem_euclid(period);
+    let start = DateTime::from_timestamp(start, 0)
+        .ok_or_else(|| "Invalid slideshow start time".to_string())?
+        .naive_utc();
+
+    let mut xml = format!(
+        "<background>\n  <starttime>\n    <year>{}</year>\n    <month>{}</month>\n    <day>{}</day>\n    <hour>{}</hour>\n    <minute>{}</minute>\n    <second>{}</second>\n  </starttime>\n",
+        start.format("%Y"), start.format("%m"), start.format("%d"),
+        start.format("%H"), start.format("%M"), start.format("%S"),
+    );
+    for (index, frame) in frames.iter().enumerate() {
+        let next = &frames[(index + 1) % frames.len()];
+        let gap = (next.at as i64 - frame.at as i64).rem_euclid(period);
+        let gap = if gap == 0 { period } else { gap };
+        let transition = if frames.len() > 1 { (next.transition as i64).min(gap) } else { 0 };
+        let file = escape(&frame.path);
+
+        xml.push_str(&format!(
+            "  <static>\n    <duration>{}.0</duration>\n    <file>{}</file>\n  </static>\n",
+            gap - transition,
+            file,
+        ));
+        if transition > 0 {
+            xml.push_str(&format!(
+                "  <transition type=\"overlay\">\n    <duration>{}.0</duration>\n    <from>{}</from>\n    <to>{}</to>\n  </transition>\n",
+                transition,
+                file,
+                escape(&next.path),
+            ));
+        }
+    }
+    xml.push_str("</background>\n");
+    Ok(xml)
+}
+
+// A picture is either a file name or a list of sizes to choose from; take the largest
+fn static_file(entry: &Element) -> Option<&str> {
+    let file = entry.child("file")?;
+    let largest = file.children.iter()
+        .filter(|child| child.name == "size")
+        .max_by_key(|size| {
+            let dimension = |name| size.attribute(name).and_then(|value| value.parse::<u64>().ok()).unwrap_or(0);
+            dimension("width") * dimension("height")
+        })
+        .map(|size| size.text.trim());
+    largest.or(Some(file.text.trim())).filter(|text| !text.is_empty())
+}
+
+// Library paths for pictures, adding those not in the library yet, and the ones that were skipped
+fn add_pictures(app: &AppHandle<Wry>, pictures: Vec<PathBuf>) -> Result<(Vec<String>, Vec<String>), String> {
+    let known: HashSet<String> = with_library_db(app, |db| db.list_entries())?
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect();
+
+    let mut paths = Vec::new();
+    let mut new_wallpapers = Vec::new();
+    let mut skipped = Vec::new();
+    for picture in pictures {
+        let key = picture.to_string_lossy().to_string();
+        if paths.contains(&key) {
+            continue;
+        }
+        if known.contains(&key) {
+            paths.push(key);
+            continue;
+        }
+
+        match picture.is_file().then(|| build_wallpaper_info(&picture)) {
+            Some(info) if info.is_valid || has_supported_extension(&picture) => {
+                paths.push(key);
+                new_wallpapers.push(info);
+            }
+            _ => skipped.push(key),
+        }
+    }
+
+    if !new_wallpapers.is_empty() {
+        let mut change = LibraryChange::default();
+        merge_wallpapers(app, new_wallpapers, &mut change)?;
+        let _ = app.emit(LIBRARY_CHANGED_EVENT, change);
+    }
+    Ok((paths, skipped))
+}
+
+fn read_xml(path: &Path) -> Result<Element, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_xml(&contents).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+// The root element with everything below it; comments, declarations and the doctype are skipped
+fn parse_xml(contents: &str) -> Result<Element, String> {
+    let mut reader = Reader::from_str(contents);
+    reader.config_mut().trim_text(true);
+    let mut stack: Vec<Element> = Vec::new();
+
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(start) => stack.push(start_element(&start)?),
+            Event::Empty(start) => {
+                let element = start_element(&start)?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+            Event::Text(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text.unescape().map_err(|e| e.to_string())?);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&data));
+                }
+            }
+            Event::End(_) => {
+                let element = stack.pop().ok_or_else(|| "Unbalanced closing tag".to_string())?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+            Event::Eof => return Err("No root element".to_string()),
+            _ => {}
+        }
+    }
+}
+
+fn start_element(start: &quick_xml::events::BytesStart) -> Result<Element, String> {
+    let attributes = start.attributes()
+        .map(|attribute| {
+            let attribute = attribute.map_err(|e| e.to_string())?;
+            let value = attribute.unescape_value().map_err(|e| e.to_string())?;
+            Ok((String::from_utf8_lossy(attribute.key.as_ref()).to_string(), value.to_string()))
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(Element {
+        name: String::from_utf8_lossy(start.name().as_ref()).to_string(),
+        attributes,
+        ..Default::default()
+    })
+}
+
+// Relative file names are relative to the XML file's folder
+fn resolve(xml_path: &Path, file: &str) -> PathBuf {
+    xml_path.parent().unwrap_or(Path::new("")).join(file)
+}
+
+fn is_xml(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case("xml"))
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Imported".to_string())
+}
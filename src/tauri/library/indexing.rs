@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Manager, Wry};
+use crate::commands::app_thumbnail_root;
+use crate::library::colors::index_library_colors;
+use crate::library::duplicates::index_library_hashes;
+use crate::state::AppState;
+
+// Hashes and palettes are only computed here, in the background; queries read what is stored
+#[derive(Default)]
+pub struct LibraryIndexer {
+    running: AtomicBool,
+    // Set whenever entries may need indexing, so a pass already running goes round again
+    pending: AtomicBool,
+}
+
+// Hash new or changed entries and extract their colors, after startup and whenever the library
+// gains files. A pass already under way picks the new entries up instead of a second one starting.
+pub fn schedule_library_indexing(app: &AppHandle<Wry>) {
+    let app_state = app.state::<AppState>();
+    let indexer = &app_state.library_indexer;
+    indexer.pending.store(true, Ordering::SeqCst);
+    if indexer.running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let thumbnail_root = match app_thumbnail_root(app) {
+        Ok(thumbnail_root) => thumbnail_root,
+        Err(_e) => {
+            indexer.running.store(false, Ordering::SeqCst);
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to index library: {}", _e);
+            return;
+        }
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let app_state = app.state::<AppState>();
+        let indexer = &app_state.library_indexer;
+        loop {
+            while indexer.pending.swap(false, Ordering::SeqCst) {
+                if let Err(_e) = index_library_hashes(&app, &thumbnail_root) {
+                    #[cfg(debug_assertions)]
+                    eprintln!("Failed to hash library: {}", _e);
+                }
+                if let Err(_e) = index_library_colors(&app, &thumbnail_root) {
+                    #[cfg(debug_assertions)]
+                    eprintln!("Failed to index library colors: {}", _e);
+                }
+            }
+
+            indexer.running.store(false, Ordering::SeqCst);
+            // A request that arrived between the last check and clearing `running` found it still
+            // set, so take it on here unless another pass has started since
+            if !indexer.pending.load(Ordering::SeqCst) || indexer.running.swap(true, Ordering::SeqCst) {
+                break;
+            }
+        }
+    });
+}
\ No newline at end of file
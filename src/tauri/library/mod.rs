@@ -0,0 +1,9 @@
+pub mod collections;
+pub mod colors;
+pub mod db;
+pub mod duplicates;
+pub mod gnome;
+pub mod indexing;
+pub mod reconcile;
+pub mod scan;
+pub mod watcher;
\ No newline at end of file
@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Wry};
+use crate::commands::{load_app_state, update_app_state};
+use crate::library::db::with_library_db;
+use crate::library::indexing::schedule_library_indexing;
+use crate::library::watcher::LIBRARY_CHANGED_EVENT;
+use crate::slideshow::{relink_history, wallpaper_history};
+use crate::types::{LibraryChange, LibraryFolder, ReconcileReport, RelinkedWallpaper};
+use crate::utils::file_utils::{content_hash, modified_secs};
+use crate::utils::scanner::{build_wallpaper_info, walk_files, ScanFilter};
+
+// Check library entries against the disk, relink moved files and mark the rest missing.
+// `only` limits the pass to the given paths; otherwise every entry is checked.
+pub async fn reconcile_library(app: AppHandle<Wry>, only: Option<Vec<String>>) -> Result<ReconcileReport, String> {
+    let folders: Vec<LibraryFolder> = load_app_state(app.clone()).await?
+        .library_folders
+        .into_iter()
+        .filter(|folder| folder.watched)
+        .collect();
+
+    let task_app = app.clone();
+    let (report, change) = tokio::task::spawn_blocking(move || {
+        reconcile_entries(&task_app, &folders, only.map(|paths| paths.into_iter().collect()))
+    })
+    .await
+    .map_err(|e| format!("Failed to run library check: {}", e))??;
+
+    // Keep startup restore and the history pointing at the files that moved
+    if !report.relinked.is_empty() {
+        relink_history(&app, &report.relinked);
+        let history = wallpaper_history(&app);
+        update_app_state(&app, |state| {
+            if let Some(relinked) = report.relinked.iter()
+                .find(|relinked| state.last_wallpaper_path.as_deref() == Some(relinked.from.as_str()))
+            {
+                state.last_wallpaper_path = Some(relinked.to.clone());
+            }
+            state.wallpaper_history = history;
+        })
+        .await?;
+    }
+
+    if !change.is_empty() {
+        // Rewritten files lost their hashes and colors
+        schedule_library_indexing(&app);
+        let _ = app.emit(LIBRARY_CHANGED_EVENT, change);
+    }
+    Ok(report)
+}
+
+// Remove entries whose files are still gone; returns the removed paths
+pub async fn prune_missing(app: AppHandle<Wry>, only: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    let only: Option<HashSet<String>> = only.map(|paths| paths.into_iter().collect());
+
+    let removed = with_library_db(&app, |db| {
+        let paths: Vec<String> = db.list_entries()?
+            .into_iter()
+            .filter(|entry| entry.missing && !Path::new(&entry.path).is_file())
+            .filter(|entry| only.as_ref().is_none_or(|only| only.contains(&entry.path)))
+            .map(|entry| entry.path)
+            .collect();
+        db.remove_wallpapers(&paths)?;
+        Ok(paths)
+    })?;
+
+    if !removed.is_empty() {
+        let _ = app.emit(LIBRARY_CHANGED_EVENT, LibraryChange {
+            removed: removed.clone(),
+            ..Default::default()
+        });
+    }
+    Ok(removed)
+}
+
+// First wallpaper in the library that still exists, used when the last one has disappeared
+pub fn fallback_wallpaper(app: &AppHandle<Wry>) -> Option<String> {
+    with_library_db(app, |db| db.list_wallpapers())
+        .ok()?
+        .into_iter()
+        .find(|info| info.is_valid && Path::new(&info.path).is_file())
+        .map(|info| info.path)
+}
+
+fn reconcile_entries(
+    app: &AppHandle<Wry>,
+    folders: &[LibraryFolder],
+    only: Option<HashSet<String>>,
+) -> Result<(ReconcileReport, LibraryChange), String> {
+    let all_entries = with_library_db(app, |db| db.list_entries())?;
+    let known_paths: HashSet<String> = all_entries.iter().map(|entry| entry.path.clone()).collect();
+    let entries: Vec<_> = all_entries
+        .into_iter()
+        .filter(|entry| only.as_ref().is_none_or(|only| only.contains(&entry.path)))
+        .collect();
+
+    let mut report = ReconcileReport {
+        checked: entries.len(),
+        ..Default::default()
+    };
+    let mut change = LibraryChange::default();
+    let mut missing = Vec::new();
+
+    // Hash files while they are still around, so they can be recognized after a move.
+    // The database lock is only taken per write so the UI is not blocked while hashing.
+    for entry in entries {
+        if !Path::new(&entry.path).is_file() {
+            missing.push(entry);
+            continue;
+        }
+
+        let metadata = std::fs::metadata(&entry.path).ok();
+        let size = metadata.as_ref().map(|metadata| metadata.len());
+        let modified_at = metadata.as_ref().and_then(modified_secs);
+
+        // A file rewritten since it was stored needs a fresh hash, or a later move would be
+        // matched against its old contents
+        let rewritten = size.is_some_and(|size| size != entry.size)
+            || (entry.modified_at.is_some() && modified_at.is_some() && modified_at != entry.modified_at);
+        if rewritten {
+            // Dimensions, dates and even the media type may differ, so read the file again
+            if let Ok(hash) = content_hash(Path::new(&entry.path)) {
+                let info = build_wallpaper_info(Path::new(&entry.path));
+                with_library_db(app, |db| db.set_changed_contents(&info, &hash))?;
+                if !entry.missing
+                    && let Some(info) = with_library_db(app, |db| db.get_wallpaper(&entry.path))?
+                {
+                    change.updated.push(info);
+                }
+            }
+        } else {
+            if entry.content_hash.is_none()
+                && let Ok(hash) = content_hash(Path::new(&entry.path))
+            {
+                let _ = with_library_db(app, |db| db.set_content_hash(&entry.path, &hash));
+            }
+            // Entries stored before modification times were recorded get theirs now
+            if entry.modified_at.is_none()
+                && let Some(modified_at) = modified_at
+            {
+                let _ = with_library_db(app, |db| db.set_modified_at(&entry.path, modified_at));
+            }
+        }
+        if entry.missing {
+            with_library_db(app, |db| db.set_missing(&entry.path, false))?;
+            if let Some(info) = with_library_db(app, |db| db.get_wallpaper(&entry.path))? {
+                change.updated.push(info);
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok((report, change));
+    }
+
+    // Only files of the same size can match, so group candidates by size and hash on demand
+    let mut candidates_by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    if missing.iter().any(|entry| entry.content_hash.is_some()) {
+        for folder in folders {
+            let Ok(filter) = ScanFilter::new(&folder.scan_options) else {
+                continue;
+            };
+            for (path, size) in walk_files(Path::new(&folder.path), &filter) {
+                if !known_paths.contains(path.to_string_lossy().as_ref()) {
+                    candidates_by_size.entry(size).or_default().push(path);
+                }
+            }
+        }
+    }
+
+    let mut candidate_hashes: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut claimed: HashSet<PathBuf> = HashSet::new();
+
+    for entry in missing {
+        let relink_target = entry.content_hash.as_ref().and_then(|hash| {
+            candidates_by_size.get(&entry.size)?
+                .iter()
+                .filter(|candidate| !claimed.contains(*candidate))
+                .find(|candidate| {
+                    candidate_hashes
+                        .entry((*candidate).clone())
+                        .or_insert_with(|| content_hash(candidate).ok())
+                        .as_ref() == Some(hash)
+                })
+                .cloned()
+        });
+
+        match relink_target {
+            Some(target) => {
+                let info = build_wallpaper_info(&target);
+                let info = with_library_db(app, |db| {
+                    db.relink_wallpaper(&entry.path, &info)?;
+                    Ok(db.get_wallpaper(&info.path)?.unwrap_or(info))
+                })?;
+                claimed.insert(target);
+
+                report.relinked.push(RelinkedWallpaper {
+                    from: entry.path.clone(),
+                    to: info.path.clone(),
+                });
+                change.removed.push(entry.path);
+                change.added.push(info);
+            }
+            None => {
+                if !entry.missing {
+                    with_library_db(app, |db| db.set_missing(&entry.path, true))?;
+                    if let Some(info) = with_library_db(app, |db| db.get_wallpaper(&entry.path))? {
+                        change.updated.push(info);
+                    }
+                }
+                report.missing.push(entry.path);
+            }
+        }
+    }
+
+    Ok((report, change))
+}
\ No newline at end of file
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use crate::commands::sort_wallpapers;
+use crate::library::db::with_library_db;
+use crate::state::AppState;
+use crate::types::{ScanBatch, ScanProgress, ScanStatus, WallpaperInfo, WallpaperSort};
+use crate::utils::scanner::{scan_directory_streaming, ScanCounts, ScanFilter};
+
+pub const SCAN_PROGRESS_EVENT: &str = "library-scan-progress";
+pub const SCAN_BATCH_EVENT: &str = "library-scan-batch";
+
+// Finished scans stay around for paging; older ones are dropped as new scans start
+const MAX_FINISHED_SCANS: usize = 4;
+
+static NEXT_SCAN_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Default)]
+pub struct LibraryScan {
+    pub sequence: u64,
+    pub cancelled: AtomicBool,
+    pub finished: AtomicBool,
+    pub results: Mutex<Vec<WallpaperInfo>>,
+}
+
+// Start a scan in the background and return its ID; results arrive through events and paging
+pub fn start_library_scan(
+    app: AppHandle<Wry>,
+    folder: String,
+    filter: ScanFilter,
+    sort_by: WallpaperSort,
+) -> String {
+    let sequence = NEXT_SCAN_ID.fetch_add(1, Ordering::Relaxed);
+    let scan_id = format!("scan-{}", sequence);
+    let scan = Arc::new(LibraryScan {
+        sequence,
+        ..Default::default()
+    });
+
+    {
+        let app_state = app.state::<AppState>();
+        let mut scans = app_state.library_scans.lock().unwrap();
+        prune_finished_scans(&mut scans);
+        scans.insert(scan_id.clone(), scan.clone());
+    }
+
+    let id = scan_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let mut last_counts = ScanCounts::default();
+        let completed = scan_directory_streaming(Path::new(&folder), &filter, &scan.cancelled, |wallpapers, counts| {
+            last_counts = counts;
+            scan.results.lock().unwrap().extend(wallpapers.iter().cloned());
+            let _ = app.emit(SCAN_BATCH_EVENT, ScanBatch {
+                scan_id: id.clone(),
+                wallpapers,
+            });
+            let _ = app.emit(SCAN_PROGRESS_EVENT, scan_progress(&id, counts, ScanStatus::Running));
+        });
+
+        // Pages are only sorted once everything is in; while running they follow discovery order
+        let status = if completed {
+            sort_wallpapers(&mut scan.results.lock().unwrap(), sort_by);
+            let duration_ms = started.elapsed().as_millis() as u64;
+            let _ = with_library_db(&app, |db| db.record_scan(&folder, last_counts.matched, duration_ms));
+            ScanStatus::Completed
+        } else {
+            ScanStatus::Cancelled
+        };
+        scan.finished.store(true, Ordering::Release);
+        let _ = app.emit(SCAN_PROGRESS_EVENT, scan_progress(&id, last_counts, status));
+    });
+
+    scan_id
+}
+
+fn scan_progress(scan_id: &str, counts: ScanCounts, status: ScanStatus) -> ScanProgress {
+    ScanProgress {
+        scan_id: scan_id.to_string(),
+        discovered: counts.discovered,
+        processed: counts.processed,
+        matched: counts.matched,
+        status,
+    }
+}
+
+fn prune_finished_scans(scans: &mut HashMap<String, Arc<LibraryScan>>) {
+    let mut finished: Vec<(String, u64)> = scans.iter()
+        .filter(|(_, scan)| scan.finished.load(Ordering::Relaxed))
+        .map(|(id, scan)| (id.clone(), scan.sequence))
+        .collect();
+    if finished.len() < MAX_FINISHED_SCANS {
+        return;
+    }
+
+    finished.sort_by_key(|(_, sequence)| *sequence);
+    for (id, _) in finished.iter().take(finished.len() + 1 - MAX_FINISHED_SCANS) {
+        scans.remove(id);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,153 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use crate::commands::load_app_state;
+use crate::library::db::with_library_db;
+use crate::library::indexing::schedule_library_indexing;
+use crate::state::AppState;
+use crate::types::{LibraryChange, WallpaperInfo};
+use crate::utils::file_utils::has_supported_extension;
+use crate::utils::scanner::{build_wallpaper_info, scan_directory, ScanFilter};
+
+// Long enough for a bulk copy to settle into one batch instead of one event per file
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub const LIBRARY_CHANGED_EVENT: &str = "library-changed";
+
+pub type LibraryWatcher = Debouncer<RecommendedWatcher>;
+
+// Rebuild the watcher for the current set of watched folders; call whenever that set changes
+pub async fn restart_library_watcher(app: AppHandle<Wry>) -> Result<(), String> {
+    let watched_paths: Vec<String> = load_app_state(app.clone()).await?
+        .library_folders
+        .into_iter()
+        .filter(|folder| folder.watched)
+        .map(|folder| folder.path)
+        .collect();
+
+    let app_state = app.state::<AppState>();
+    let mut library_watcher = app_state.library_watcher.lock().unwrap();
+
+    // Dropping the old debouncer stops its thread and removes its watches
+    *library_watcher = None;
+    if watched_paths.is_empty() {
+        return Ok(());
+    }
+
+    let app_handle = app.clone();
+    let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| {
+        match result {
+            Ok(events) => {
+                let paths: BTreeSet<PathBuf> = events.into_iter().map(|event| event.path).collect();
+                tauri::async_runtime::block_on(apply_watch_events(app_handle.clone(), paths));
+            }
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                eprintln!("Library watcher error: {}", _e);
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create library watcher: {}", e))?;
+
+    for path in &watched_paths {
+        // A folder on an unplugged drive should not stop the others from being watched
+        if let Err(_e) = debouncer.watcher().watch(Path::new(path), RecursiveMode::Recursive) {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to watch {}: {}", path, _e);
+        }
+    }
+
+    *library_watcher = Some(debouncer);
+    Ok(())
+}
+
+// Add or refresh wallpapers in the library, recording which of the two happened.
+// Entries are read back so the change carries their tags, rating and flags.
+pub fn merge_wallpapers(app: &AppHandle<Wry>, wallpapers: Vec<WallpaperInfo>, change: &mut LibraryChange) -> Result<(), String> {
+    with_library_db(app, |db| {
+        let inserted = db.upsert_wallpapers(&wallpapers)?;
+        for info in wallpapers {
+            let stored = db.get_wallpaper(&info.path)?.unwrap_or(info);
+            if inserted.contains(&stored.path) {
+                change.added.push(stored);
+            } else {
+                change.updated.push(stored);
+            }
+        }
+        Ok(())
+    })?;
+
+    schedule_library_indexing(app);
+    Ok(())
+}
+
+async fn apply_watch_events(app: AppHandle<Wry>, paths: BTreeSet<PathBuf>) {
+    let Ok(current_state) = load_app_state(app.clone()).await else {
+        return;
+    };
+
+    let filters: Vec<(PathBuf, ScanFilter)> = current_state.library_folders.iter()
+        .filter(|folder| folder.watched)
+        .filter_map(|folder| {
+            ScanFilter::new(&folder.scan_options).ok().map(|filter| (PathBuf::from(&folder.path), filter))
+        })
+        .collect();
+
+    let mut change = LibraryChange::default();
+
+    for path in paths {
+        let Some((root, filter)) = filters.iter().find(|(root, _)| path.starts_with(root)) else {
+            continue;
+        };
+
+        if !path.exists() {
+            // A removed directory only reports itself, so drop everything that lived under it
+            match with_library_db(&app, |db| db.remove_wallpapers_under(&path)) {
+                Ok(removed) => change.removed.extend(removed),
+                Err(_e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("Failed to remove {}: {}", path.display(), _e);
+                }
+            }
+            continue;
+        }
+
+        if path == *root || !filter.accepts_path(root, &path) {
+            continue;
+        }
+
+        let wallpapers = if path.is_dir() {
+            // Folders moved in arrive as a single event, so pick up their contents with a scan
+            scan_directory(&path, filter)
+                .into_iter()
+                .filter(|info| filter.accepts_path(root, Path::new(&info.path)))
+                .collect()
+        } else {
+            let Ok(metadata) = std::fs::metadata(&path) else {
+                continue;
+            };
+            if !filter.accepts_file(root, &path, &metadata) {
+                continue;
+            }
+            let info = build_wallpaper_info(&path);
+            if !(info.is_valid || has_supported_extension(&path)) || !filter.accepts_info(&info) {
+                continue;
+            }
+            vec![info]
+        };
+
+        if let Err(_e) = merge_wallpapers(&app, wallpapers, &mut change) {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to save library changes: {}", _e);
+        }
+    }
+
+    if change.is_empty() {
+        return;
+    }
+
+    let _ = app.emit(LIBRARY_CHANGED_EVENT, change);
+}
\ No newline at end of file
@@ -6,6 +6,9 @@ mod state;
 mod utils;
 mod platform;
 mod tray;
+mod library;
+mod slideshow;
+mod cli;
 
 use state::AppState;
 use commands::*;
@@ -19,6 +22,11 @@ fn main() {
         .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
             // This callback is called when a second instance is attempted
             println!("Second instance detected with args: {:?}, cwd: {:?}", args, cwd);
+
+            // Slideshow controls run in the background without bringing up the window
+            if cli::handle_cli_args(app, &args) {
+                return;
+            }
             
             // Focus the existing main window
             if let Some(window) = app.get_webview_window("main") {
@@ -35,29 +43,40 @@ fn main() {
         .manage(app_state)
         .setup(|app| {
             create_tray_menu(app)?;
+
+            if let Err(_e) = library::db::open_library_db(app.handle()) {
+                #[cfg(debug_assertions)]
+                eprintln!("Failed to open library database: {}", _e);
+            }
             
             // Load persistent state and restore previous session
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
+                // List collections in the tray now that the library is available
+                tray::refresh_tray_menu(&app_handle).await;
+
+                // Auto-change runs in the backend so it keeps going without the main window
+                slideshow::start_slideshow(app_handle.clone()).await;
+                slideshow::schedule::start_schedule(app_handle.clone());
+                slideshow::dynamic::start_dynamic(app_handle.clone());
+
                 if let Ok(state) = commands::load_app_state(app_handle.clone()).await {
-                    // Restore wallpaper if exists
-                    if let (Some(wallpaper_path), Some(file_type)) = (&state.last_wallpaper_path, &state.last_wallpaper_file_type) {
-                        let is_video_or_gif = ["mp4", "webm", "avi", "mov", "mkv", "gif"].contains(&file_type.to_lowercase().as_str());
+                    // Restore wallpaper, falling back to another library entry if the file is gone
+                    let restore_path = state.last_wallpaper_path.clone()
+                        .filter(|path| std::path::Path::new(path).is_file())
+                        .or_else(|| library::reconcile::fallback_wallpaper(&app_handle));
 
-                        if is_video_or_gif {
-                            let converted_path = format!("http://asset.localhost/{}", urlencoding::encode(wallpaper_path));
-                            
-                            if let Some(app_state) = app_handle.try_state::<AppState>() {
-                                let _ = commands::create_video_wallpaper(
-                                    app_handle.clone(),
-                                    wallpaper_path.clone(),
-                                    converted_path,
-                                    app_state,
-                                ).await;
-                            }
-                        } else {
-                            let _ = commands::set_static_wallpaper(wallpaper_path.clone()).await;
-                        }
+                    // Screens running their own slideshows put their wallpapers back themselves
+                    if let Some(wallpaper_path) = restore_path.filter(|_| !state.monitor_slideshows.independent) {
+                        let _ = commands::apply_wallpaper(app_handle.clone(), wallpaper_path).await;
+                    }
+                    
+                    // Resume syncing watched library folders
+                    if state.library_folders.iter().any(|folder| folder.watched)
+                        && let Err(_e) = library::watcher::restart_library_watcher(app_handle.clone()).await
+                    {
+                        #[cfg(debug_assertions)]
+                        eprintln!("Failed to start library watcher: {}", _e);
                     }
                     
                     // Restore date widget if enabled
@@ -73,10 +92,27 @@ fn main() {
                         }
                     }
                 }
+
+                // Started after the restore, so screens running their own slideshows cover it
+                slideshow::monitors::start_monitor_slideshows(app_handle.clone());
+                
+                // Check the library for moved or deleted files once the desktop is back up
+                let reconcile_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(_e) = library::reconcile::reconcile_library(reconcile_handle.clone(), None).await {
+                        #[cfg(debug_assertions)]
+                        eprintln!("Failed to check library files: {}", _e);
+                    }
+
+                    // Hash new entries and extract their colors up front so duplicate grouping
+                    // and color searches stay quick
+                    library::indexing::schedule_library_indexing(&reconcile_handle);
+                });
                 
-                // Show main window only if not started minimized
+                // Show main window only if not started minimized or to run a slideshow control
                 let args: Vec<String> = std::env::args().collect();
-                if !args.contains(&"--minimized".to_string()) {
+                let cli_action = cli::handle_cli_args(&app_handle, &args);
+                if !cli_action && !args.contains(&"--minimized".to_string()) {
                     if let Some(window) = app_handle.get_webview_window("main") {
                         let _ = window.show();
                         let _ = window.set_focus();
@@ -90,8 +126,73 @@ fn main() {
             commands::set_static_wallpaper,
             create_video_wallpaper,
             stop_video_wallpaper,
-            get_wallpaper_files,
+            start_library_scan,
+            cancel_library_scan,
+            get_library_scan_page,
             get_files_info,
+            get_thumbnail,
+            get_library_folders,
+            get_library_wallpapers,
+            query_wallpapers,
+            add_library_wallpapers,
+            remove_library_wallpapers,
+            get_broken_wallpapers,
+            repair_broken_wallpapers,
+            prune_broken_wallpapers,
+            update_wallpaper_attributes,
+            get_wallpaper_tags,
+            get_slideshow_candidates,
+            start_slideshow,
+            stop_slideshow,
+            next_slideshow_wallpaper,
+            previous_slideshow_wallpaper,
+            get_slideshow_status,
+            get_wallpaper_history,
+            reapply_history_wallpaper,
+            get_slideshow_probabilities,
+            get_monitors,
+            get_monitor_slideshows,
+            set_monitors_independent,
+            update_monitor_slideshow,
+            next_monitor_wallpaper,
+            get_schedule,
+            set_schedule,
+            preview_schedule,
+            get_solar_sets,
+            create_solar_set,
+            update_solar_set,
+            delete_solar_set,
+            get_active_solar_set,
+            set_active_solar_set,
+            get_solar_status,
+            search_cities,
+            get_dynamic_sets,
+            create_dynamic_set,
+            update_dynamic_set,
+            delete_dynamic_set,
+            get_active_dynamic_set,
+            set_active_dynamic_set,
+            find_gnome_backgrounds,
+            import_gnome_backgrounds,
+            export_collection_gnome,
+            export_dynamic_set_gnome,
+            get_collections,
+            create_collection,
+            rename_collection,
+            delete_collection,
+            get_collection_wallpapers,
+            add_to_collection,
+            remove_from_collection,
+            reorder_collection,
+            set_collection_settings,
+            get_active_collection,
+            set_active_collection,
+            export_collection,
+            import_collection,
+            find_duplicate_wallpapers,
+            merge_duplicate_wallpapers,
+            watch_library_folder,
+            unwatch_library_folder,
             show_main_window,
             hide_main_window,
             create_date_widget,
@@ -105,8 +206,7 @@ fn main() {
             get_autostart_status,
             update_wallpaper_state,
             update_date_widget_state,
-            update_wallpaper_settings_state,
-            save_wallpaper_list
+            update_wallpaper_settings_state
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
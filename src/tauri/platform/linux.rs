@@ -2,27 +2,60 @@
 // For example, different desktop environment integrations
 
 #[cfg(target_os = "linux")]
-pub fn set_linux_wallpaper(file_path: &str) -> Result<(), String> {
+use super::StaticBackend;
+
+// Tried in order; the first one installed on the system wins
+#[cfg(target_os = "linux")]
+pub const STATIC_BACKENDS: &[StaticBackend] = &[
+    StaticBackend {
+        name: "gsettings",
+        formats: &["jpg", "png", "bmp", "tiff"],
+        is_available: || command_exists("gsettings"),
+        apply: |file_path| run_wallpaper_command("gsettings", &["set", "org.gnome.desktop.background", "picture-uri", &format!("file://{}", file_path)]),
+    },
+    StaticBackend {
+        name: "feh",
+        formats: &["jpg", "png", "bmp", "tiff", "tga", "webp"],
+        is_available: || command_exists("feh"),
+        apply: |file_path| run_wallpaper_command("feh", &["--bg-fill", file_path]),
+    },
+    StaticBackend {
+        name: "nitrogen",
+        formats: &["jpg", "png", "bmp", "tiff"],
+        is_available: || command_exists("nitrogen"),
+        apply: |file_path| run_wallpaper_command("nitrogen", &["--set-scaled", file_path]),
+    },
+    StaticBackend {
+        name: "xfconf-query",
+        formats: &["jpg", "png", "bmp", "tiff"],
+        is_available: || command_exists("xfconf-query"),
+        apply: |file_path| run_wallpaper_command("xfconf-query", &["-c", "xfce4-desktop", "-p", "/backdrop/screen0/monitor0/workspace0/last-image", "-s", file_path]),
+    },
+];
+
+#[cfg(target_os = "linux")]
+pub fn command_exists(cmd: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(cmd).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+fn run_wallpaper_command(cmd: &str, args: &[&str]) -> Result<(), String> {
     use std::process::Command;
-    
-    let desktop_commands = [
-        ("gsettings", vec!["set", "org.gnome.desktop.background", "picture-uri", &format!("file://{}", file_path)]),
-        ("feh", vec!["--bg-fill", file_path]),
-        ("nitrogen", vec!["--set-scaled", file_path]),
-        ("xfconf-query", vec!["-c", "xfce4-desktop", "-p", "/backdrop/screen0/monitor0/workspace0/last-image", "-s", file_path]),
-    ];
 
-    let mut success = false;
-    for (cmd, args) in desktop_commands.iter() {
-        if Command::new(cmd).args(args).output().is_ok() {
-            success = true;
-            break;
-        }
-    }
-    
-    if !success {
-        return Err("Failed to set wallpaper on Linux".to_string());
-    }
+    let output = Command::new(cmd)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", cmd, e))?;
 
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed ({}): {}",
+            cmd,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
     Ok(())
 }
\ No newline at end of file
@@ -1,6 +1,17 @@
 // macOS-specific functionality can be added here
 // For example, native macOS wallpaper APIs or Cocoa integrations
 
+#[cfg(target_os = "macos")]
+use super::StaticBackend;
+
+#[cfg(target_os = "macos")]
+pub const STATIC_BACKENDS: &[StaticBackend] = &[StaticBackend {
+    name: "finder",
+    formats: &["jpg", "png", "tiff", "bmp", "gif"],
+    is_available: || true,
+    apply: set_macos_wallpaper,
+}];
+
 #[cfg(target_os = "macos")]
 pub fn set_macos_wallpaper(file_path: &str) -> Result<(), String> {
     use std::process::Command;
@@ -5,4 +5,26 @@ pub mod windows;
 pub mod macos;
 
 #[cfg(target_os = "linux")]
-pub mod linux;
\ No newline at end of file
+pub mod linux;
+
+// A system wallpaper setter together with the image formats it can display
+pub struct StaticBackend {
+    pub name: &'static str,
+    pub formats: &'static [&'static str],
+    pub is_available: fn() -> bool,
+    pub apply: fn(&str) -> Result<(), String>,
+}
+
+pub fn static_backends() -> &'static [StaticBackend] {
+    #[cfg(target_os = "windows")]
+    return windows::STATIC_BACKENDS;
+
+    #[cfg(target_os = "macos")]
+    return macos::STATIC_BACKENDS;
+
+    #[cfg(target_os = "linux")]
+    return linux::STATIC_BACKENDS;
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    return &[];
+}
\ No newline at end of file
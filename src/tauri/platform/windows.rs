@@ -1,3 +1,38 @@
+#[cfg(target_os = "windows")]
+use super::StaticBackend;
+
+#[cfg(target_os = "windows")]
+pub const STATIC_BACKENDS: &[StaticBackend] = &[StaticBackend {
+    name: "SystemParametersInfo",
+    formats: &["jpg", "png", "bmp"],
+    is_available: || true,
+    apply: set_windows_wallpaper,
+}];
+
+#[cfg(target_os = "windows")]
+pub fn set_windows_wallpaper(file_path: &str) -> Result<(), String> {
+    use std::ffi::CString;
+    use winapi::um::winuser::{SystemParametersInfoA, SPI_SETDESKWALLPAPER, SPIF_UPDATEINIFILE, SPIF_SENDCHANGE};
+
+    let path_cstring = CString::new(file_path)
+        .map_err(|_| "Invalid file path".to_string())?;
+
+    unsafe {
+        let result = SystemParametersInfoA(
+            SPI_SETDESKWALLPAPER,
+            0,
+            path_cstring.as_ptr() as *mut _,
+            SPIF_UPDATEINIFILE | SPIF_SENDCHANGE,
+        );
+
+        if result == 0 {
+            return Err("Failed to set wallpaper".to_string());
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(target_os = "windows")]
 pub fn set_wallpaper_behind_desktop_sync(window: &tauri::WebviewWindow) -> Result<(), String> {
     use winapi::um::winuser::{FindWindowA, FindWindowExA, PostMessageW, SetParent};
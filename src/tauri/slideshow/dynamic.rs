@@ -0,0 +1,345 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::Local;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Wry};
+use tokio::sync::Notify;
+use crate::commands::{
+    asset_url, close_wallpaper_window, known_content_hash, load_app_state, normalized_cache_dir, open_wallpaper_window,
+    primary_monitor_size, show_static_image, update_app_state, ALL_MONITORS,
+};
+use crate::library::db::with_library_db;
+use crate::platform::static_backends;
+use crate::slideshow::schedule::scheduled_target;
+use crate::slideshow::solar::forget_solar;
+use crate::slideshow::wake_slideshow;
+use crate::state::AppState;
+use crate::types::{DynamicSet, MediaKind};
+use crate::utils::image_cache::{blend_key, blend_steps, NormalizeRequest};
+use crate::utils::image_metadata::read_image_metadata;
+use crate::utils::media::sniff_media;
+
+// Static backends redraw the desktop for every fade step, so fades get a few steps at most,
+// spaced at least this many seconds apart
+const MIN_FADE_STEP_SECS: u32 = 2;
+const MAX_FADE_STEPS: u32 = 8;
+
+// The blended steps of a fade are prepared this long before it starts
+const PREPARE_SECS: f64 = 30.0;
+
+// Shortest wait between two checks, so rounding at a step boundary cannot spin the loop
+const MIN_DELAY_SECS: f64 = 0.25;
+
+#[derive(Default)]
+pub struct Dynamic {
+    // Wakes the player when the active set, its frames or the schedule change
+    wake: Notify,
+    // What the active set last put on screen, while it has the screen
+    shown: Mutex<Option<String>>,
+    // Cache key of the last fade prepared, which every step of it looks up again
+    blend: Mutex<Option<BlendKey>>,
+}
+
+struct BlendKey {
+    from: String,
+    to: String,
+    target_size: Option<(u32, u32)>,
+    key: String,
+}
+
+// Where a cycle is at a moment: the frame on screen, the one after it, how far the fade into
+// the next one has got if it has begun, and the seconds left until the next keyframe
+struct CyclePosition {
+    current: usize,
+    next: usize,
+    fade: Option<f64>,
+    until_next: f64,
+}
+
+// What the wallpaper window needs to play a set on its own
+#[derive(Serialize)]
+struct LiveSet {
+    period: u32,
+    frames: Vec<LiveFrame>,
+}
+
+#[derive(Serialize)]
+struct LiveFrame {
+    src: String,
+    #[serde(rename = "type")]
+    mime_type: String,
+    at: u32,
+    transition: u32,
+}
+
+// Play the active dynamic set for the lifetime of the app
+pub fn start_dynamic(app: AppHandle<Wry>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let delay = match run_dynamic(&app).await {
+                Ok(delay) => delay,
+                Err(_e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("Failed to show dynamic wallpaper: {}", _e);
+
+                    // Try again later instead of right away
+                    Some(Duration::from_secs(60))
+                }
+            };
+
+            let dynamic = &app.state::<AppState>().inner().dynamic;
+            match delay {
+                Some(delay) => {
+                    let _ = tokio::time::timeout(delay, dynamic.wake.notified()).await;
+                }
+                None => dynamic.wake.notified().await,
+            }
+        }
+    });
+}
+
+pub fn wake_dynamic(app: &AppHandle<Wry>) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state.dynamic.wake.notify_one();
+    }
+}
+
+// Stop holding the set's picture, letting the slideshow carry on
+pub fn forget_dynamic(app: &AppHandle<Wry>) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    if state.dynamic.shown.lock().unwrap().take().is_some() {
+        wake_slideshow(app);
+    }
+}
+
+pub fn dynamic_held(app: &AppHandle<Wry>) -> bool {
+    app.try_state::<AppState>()
+        .is_some_and(|state| state.dynamic.shown.lock().unwrap().is_some())
+}
+
+// None hands the screen back to the slideshow. Activating a set turns off the solar set.
+pub async fn activate_dynamic_set(app: &AppHandle<Wry>, id: Option<i64>) -> Result<Option<DynamicSet>, String> {
+    let set = match id {
+        Some(id) => {
+            let set = with_library_db(app, |db| db.get_dynamic_set(id))?
+                .ok_or_else(|| format!("Dynamic set {} does not exist", id))?;
+            if set.frames.is_empty() {
+                return Err(format!("{} has no pictures yet", set.name));
+            }
+            Some(set)
+        }
+        None => None,
+    };
+
+    update_app_state(app, |state| {
+        state.active_dynamic_set = id;
+        if id.is_some() {
+            state.active_solar_set = None;
+        }
+    })
+    .await?;
+    if id.is_some() {
+        forget_solar(app);
+    }
+
+    forget_dynamic(app);
+    wake_dynamic(app);
+    Ok(set)
+}
+
+// Put the active set's picture for this moment on screen. Returns how long until it changes,
+// or None when there is nothing to time: no set is active, or the set plays in a window.
+async fn run_dynamic(app: &AppHandle<Wry>) -> Result<Option<Duration>, String> {
+    let Some(set) = active_dynamic_set(app).await? else {
+        forget_dynamic(app);
+        return Ok(None);
+    };
+
+    // Videos and animations cannot be blended, so sets with any play in the wallpaper window,
+    // which fades between frames by itself
+    if set.frames.iter().any(|frame| sniff_media(Path::new(&frame.path)).media_kind != MediaKind::Static) {
+        show_live(app, &set).await?;
+        return Ok(None);
+    }
+
+    let position = cycle_position(&set, local_now());
+    let current = &set.frames[position.current];
+    let next = &set.frames[position.next];
+    let steps = fade_steps(next.transition);
+
+    let Some(progress) = position.fade.filter(|_| steps > 0) else {
+        show(app, &current.path).await?;
+
+        // Wake up to prepare the blends ahead of the fade, then again when it starts
+        let until_fade = if steps > 0 {
+            position.until_next - next.transition as f64
+        } else {
+            position.until_next
+        };
+        if steps > 0 && until_fade <= PREPARE_SECS {
+            prepare_blends(app, &current.path, &next.path, steps).await?;
+            return Ok(Some(delay(until_fade)));
+        }
+        return Ok(Some(delay(if steps > 0 { until_fade - PREPARE_SECS } else { until_fade })));
+    };
+
+    // Step k of the fade shows blend k, with step 0 still on the outgoing frame
+    let step = ((progress * (steps + 1) as f64).floor() as u32).min(steps);
+    if step == 0 {
+        show(app, &current.path).await?;
+    } else {
+        let blends = prepare_blends(app, &current.path, &next.path, steps).await?;
+        show(app, &blends[step as usize - 1].to_string_lossy()).await?;
+    }
+
+    let step_secs = next.transition as f64 / (steps + 1) as f64;
+    let elapsed = progress * next.transition as f64;
+    Ok(Some(delay((step + 1) as f64 * step_secs - elapsed)))
+}
+
+// The active set, unless a scheduled time range has the screen
+async fn active_dynamic_set(app: &AppHandle<Wry>) -> Result<Option<DynamicSet>, String> {
+    if scheduled_target(app).is_some() {
+        return Ok(None);
+    }
+    let set = match load_app_state(app.clone()).await?.active_dynamic_set {
+        Some(id) => with_library_db(app, |db| db.get_dynamic_set(id))?,
+        None => None,
+    };
+    Ok(set.filter(|set| !set.frames.is_empty()))
+}
+
+fn cycle_position(set: &DynamicSet, local_secs: f64) -> CyclePosition {
+    let period = set.period as f64;
+    let offset = local_secs.rem_euclid(period);
+    let count = set.frames.len();
+
+    // Before the first keyframe the last frame of the previous cycle is still up
+    let current = set.frames.iter().rposition(|frame| frame.at as f64 <= offset).unwrap_or(count - 1);
+    let next = (current + 1) % count;
+    let until_next = (set.frames[next].at as f64 - offset).rem_euclid(period);
+    let until_next = if until_next > 0.0 { until_next } else { period };
+
+    let transition = if count > 1 { set.frames[next].transition as f64 } else { 0.0 };
+    let fade = (transition > 0.0 && until_next <= transition).then(|| 1.0 - until_next / transition);
+    CyclePosition { current, next, fade, until_next }
+}
+
+fn fade_steps(transition: u32) -> u32 {
+    (transition / MIN_FADE_STEP_SECS).saturating_sub(1).min(MAX_FADE_STEPS)
+}
+
+// Show a frame or a blended fade step. Neither is recorded as the wallpaper: it would fill the
+// history and replace the wallpaper restored on the next start with whatever the set showed last.
+async fn show(app: &AppHandle<Wry>, path: &str) -> Result<(), String> {
+    let dynamic = &app.state::<AppState>().inner().dynamic;
+    let previous = dynamic.shown.lock().unwrap().replace(path.to_string());
+    if previous.as_deref() == Some(path) {
+        return Ok(());
+    }
+
+    let file_type = sniff_media(Path::new(path)).file_type;
+    let result = show_static_image(app, PathBuf::from(path), file_type)
+        .await
+        // A live set played before would cover it
+        .and_then(|_| close_wallpaper_window(app, &app.state::<AppState>(), ALL_MONITORS));
+    if result.is_err() {
+        *dynamic.shown.lock().unwrap() = previous;
+    }
+    // The slideshow holds from the first picture on
+    wake_slideshow(app);
+    result
+}
+
+// Open the wallpaper window on the set, unless it is already playing this version of it
+async fn show_live(app: &AppHandle<Wry>, set: &DynamicSet) -> Result<(), String> {
+    let live = LiveSet {
+        period: set.period,
+        frames: set.frames.iter()
+            .map(|frame| LiveFrame {
+                src: asset_url(&frame.path),
+                mime_type: sniff_media(Path::new(&frame.path)).mime_type,
+                at: frame.at,
+                transition: frame.transition,
+            })
+            .collect(),
+    };
+    let json = serde_json::to_string(&live).map_err(|e| format!("Failed to serialize dynamic set: {}", e))?;
+    let key = format!("live:{}:{}", set.id, json);
+
+    let dynamic = &app.state::<AppState>().inner().dynamic;
+    if dynamic.shown.lock().unwrap().as_deref() == Some(key.as_str()) {
+        return Ok(());
+    }
+
+    let url = format!("wallpaper.html?dynamic={}", urlencoding::encode(&json));
+    let state = app.state::<AppState>();
+    open_wallpaper_window(app, &state, url, None).await?;
+    *dynamic.shown.lock().unwrap() = Some(key);
+    wake_slideshow(app);
+    Ok(())
+}
+
+async fn prepare_blends(app: &AppHandle<Wry>, from: &str, to: &str, steps: u32) -> Result<Vec<PathBuf>, String> {
+    let cache_dir = normalized_cache_dir(app)?;
+    let target_size = primary_monitor_size(app);
+    let dynamic = &app.state::<AppState>().inner().dynamic;
+    let known_key = dynamic.blend.lock().unwrap()
+        .as_ref()
+        .filter(|blend| blend.from == from && blend.to == to && blend.target_size == target_size)
+        .map(|blend| blend.key.clone());
+    let (from_hash, to_hash) = match known_key {
+        Some(_) => (None, None),
+        None => (known_content_hash(app, Path::new(from)), known_content_hash(app, Path::new(to))),
+    };
+
+    let (from_path, to_path) = (PathBuf::from(from), PathBuf::from(to));
+    let (key, blends) = tokio::task::spawn_blocking(move || {
+        let formats = static_backends()
+            .iter()
+            .find(|backend| (backend.is_available)())
+            .map(|backend| backend.formats)
+            .unwrap_or(&["jpg"]);
+        let from_type = sniff_media(&from_path).file_type;
+        let to_type = sniff_media(&to_path).file_type;
+        let request = |source, file_type, content_hash| NormalizeRequest {
+            source,
+            file_type,
+            formats,
+            target_size,
+            orientation: read_image_metadata(source, file_type).orientation,
+            content_hash,
+        };
+        let from_request = request(&from_path, &from_type, from_hash.as_deref());
+        let to_request = request(&to_path, &to_type, to_hash.as_deref());
+        let key = match known_key {
+            Some(key) => key,
+            None => blend_key(&from_request, &to_request)?,
+        };
+        let blends = blend_steps(&cache_dir, &key, &from_request, &to_request, steps as usize)?;
+        Ok::<_, String>((key, blends))
+    })
+    .await
+    .map_err(|e| format!("Failed to blend wallpapers: {}", e))??;
+
+    *dynamic.blend.lock().unwrap() = Some(BlendKey {
+        from: from.to_string(),
+        to: to.to_string(),
+        target_size,
+        key,
+    });
+    Ok(blends)
+}
+
+// Seconds since the unix epoch on the local clock, with the fraction
+fn local_now() -> f64 {
+    let now = Local::now();
+    now.timestamp_millis() as f64 / 1000.0 + now.offset().local_minus_utc() as f64
+}
+
+fn delay(secs: f64) -> Duration {
+    Duration::from_secs_f64(secs.max(MIN_DELAY_SECS))
+}
\ No newline at end of file
@@ -0,0 +1,508 @@
+pub mod dynamic;
+pub mod monitors;
+mod order;
+pub mod schedule;
+pub mod solar;
+mod weights;
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tokio::sync::Notify;
+use crate::commands::{
+    apply_wallpaper, discard_preloaded_window, load_app_state, preload_wallpaper, record_wallpaper_shown,
+    update_app_state,
+};
+use crate::library::collections::{slideshow_scope, ACTIVE_COLLECTION_CHANGED_EVENT};
+use crate::library::db::with_library_db;
+use crate::library::duplicates::{cached_duplicate_groups, collapse_duplicates, DEFAULT_MAX_DISTANCE};
+use crate::state::AppState;
+use crate::types::{
+    Collection, HistoryEntry, PickWeight, RelinkedWallpaper, ScheduleTarget, SlideshowCursor, SlideshowStatus,
+    SlideshowWeighting, WallpaperHistory, WallpaperInfo, WallpaperSettings,
+};
+use dynamic::dynamic_held;
+use monitors::{next_screen_wallpapers, Monitors};
+use order::next_in_rotation;
+use schedule::scheduled_target;
+use solar::solar_held;
+use weights::{pick_weighted, pick_weights};
+
+pub const SLIDESHOW_CHANGED_EVENT: &str = "slideshow-changed";
+pub const WALLPAPER_SETTINGS_CHANGED_EVENT: &str = "wallpaper-settings-changed";
+
+// How many applied wallpapers the history keeps
+const HISTORY_LIMIT: usize = 100;
+
+// The next wallpaper is picked and warmed up this long before it is due
+const PRELOAD_LEAD: Duration = Duration::from_secs(15);
+
+// Key of the shared slideshow's rotation order in the library database
+const SHARED_ROTATION: &str = "shared";
+
+// The scheduler lives in AppState so rotation keeps going with the main window closed
+#[derive(Default)]
+pub struct Slideshow {
+    // Wakes the scheduler when the settings, the collection or the wallpaper change
+    wake: Notify,
+    inner: Mutex<SlideshowInner>,
+    // Screens running their own slideshows, by monitor name
+    monitors: Monitors,
+}
+
+#[derive(Default, Clone)]
+struct SlideshowInner {
+    // The saved history, kept here and written back whenever a wallpaper is applied
+    history: WallpaperHistory,
+    // When the current wallpaper went up; the next change is due one interval later
+    changed_at: Option<Instant>,
+    next_change_at: Option<i64>,
+    upcoming: Option<Upcoming>,
+}
+
+// The rotation's next wallpaper, picked ahead of time so it can be warmed up. It is used as long
+// as the slideshow settings and collection stay the same and it is still a candidate.
+#[derive(Clone)]
+struct Upcoming {
+    path: String,
+    collection_id: Option<i64>,
+    settings: WallpaperSettings,
+    // Where the rotation will be once it is shown; saved only then
+    cursor: Option<SlideshowCursor>,
+}
+
+impl Upcoming {
+    fn still_fits(&self, collection: Option<&Collection>, settings: &WallpaperSettings, current: Option<&str>) -> bool {
+        self.collection_id == collection.map(|collection| collection.id)
+            && &self.settings == settings
+            && current != Some(self.path.as_str())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Navigation {
+    Back,
+    Forward,
+    Jump,
+}
+
+impl SlideshowInner {
+    fn current(&self) -> Option<&str> {
+        self.history.entries.get(self.history.position).map(|entry| entry.path.as_str())
+    }
+
+    // Move to the history entry at `index`, or add the wallpaper at the end. Nothing is dropped
+    // when showing something new after stepping back, so a skipped wallpaper can still be found.
+    fn record(&mut self, path: &str, index: Option<usize>) {
+        if self.current() == Some(path) {
+            return;
+        }
+        let history = &mut self.history;
+        match index.filter(|index| history.entries.get(*index).is_some_and(|entry| entry.path == path)) {
+            Some(index) => history.position = index,
+            None => {
+                history.entries.push(HistoryEntry {
+                    path: path.to_string(),
+                    applied_at: unix_now(),
+                });
+                let excess = history.entries.len().saturating_sub(HISTORY_LIMIT);
+                history.entries.drain(..excess);
+                history.position = history.entries.len() - 1;
+            }
+        }
+    }
+}
+
+// Run the slideshow for the lifetime of the app, reading the settings in effect on every wake.
+// The history is loaded before this returns so the restored wallpaper is added to it rather
+// than starting a new one.
+pub async fn start_slideshow(app: AppHandle<Wry>) {
+    if let Ok(state) = load_app_state(app.clone()).await {
+        app.state::<AppState>().slideshow.inner.lock().unwrap().history = state.wallpaper_history;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let delay = next_delay(&app).await;
+            let _ = app.emit(SLIDESHOW_CHANGED_EVENT, slideshow_status(&app).await);
+
+            let state = app.state::<AppState>();
+            let slideshow = &state.slideshow;
+            let Some(delay) = delay else {
+                // Nothing is coming up, so a preloaded window would only hold on to memory
+                discard_preloaded_window(&app, &state);
+                slideshow.wake.notified().await;
+                continue;
+            };
+
+            // Get the next wallpaper ready shortly before it is due, so the change is immediate
+            let due = Instant::now() + delay;
+            if delay > PRELOAD_LEAD && tokio::time::timeout(delay - PRELOAD_LEAD, slideshow.wake.notified()).await.is_ok() {
+                continue;
+            }
+            if let Err(_e) = preload_next(&app).await {
+                #[cfg(debug_assertions)]
+                eprintln!("Failed to preload next wallpaper: {}", _e);
+            }
+            let remaining = due.saturating_duration_since(Instant::now());
+            if tokio::time::timeout(remaining, slideshow.wake.notified()).await.is_ok() {
+                continue;
+            }
+
+            if let Err(_e) = step_slideshow(&app, Navigation::Forward).await {
+                #[cfg(debug_assertions)]
+                eprintln!("Failed to change wallpaper: {}", _e);
+
+                // Try again after another interval instead of retrying right away
+                slideshow.inner.lock().unwrap().changed_at = Some(Instant::now());
+            }
+        }
+    });
+}
+
+// Re-read the settings, e.g. after they were saved or the active collection changed
+pub fn wake_slideshow(app: &AppHandle<Wry>) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state.slideshow.wake.notify_one();
+        state.slideshow.monitors.wake.notify_one();
+    }
+}
+
+// Called whenever a wallpaper is applied, so a manual pick restarts the countdown
+pub fn note_wallpaper_applied(app: &AppHandle<Wry>, path: &str) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    {
+        let mut inner = state.slideshow.inner.lock().unwrap();
+        inner.record(path, None);
+        inner.changed_at = Some(Instant::now());
+    }
+    state.slideshow.wake.notify_one();
+}
+
+// The history as it stands, for saving with the app state
+pub fn wallpaper_history(app: &AppHandle<Wry>) -> WallpaperHistory {
+    app.try_state::<AppState>()
+        .map(|state| state.slideshow.inner.lock().unwrap().history.clone())
+        .unwrap_or_default()
+}
+
+// Point history entries at files that moved
+pub fn relink_history(app: &AppHandle<Wry>, relinked: &[RelinkedWallpaper]) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let mut inner = state.slideshow.inner.lock().unwrap();
+    for entry in &mut inner.history.entries {
+        if let Some(relinked) = relinked.iter().find(|relinked| relinked.from == entry.path) {
+            entry.path = relinked.to.clone();
+        }
+    }
+}
+
+pub async fn next_wallpaper(app: &AppHandle<Wry>) -> Result<SlideshowStatus, String> {
+    step_slideshow(app, Navigation::Forward).await?;
+    Ok(slideshow_status(app).await)
+}
+
+// Take the next wallpaper from the rotation, ignoring any forward history
+pub async fn rotate_wallpaper(app: &AppHandle<Wry>) -> Result<(), String> {
+    step_slideshow(app, Navigation::Jump).await
+}
+
+pub async fn previous_wallpaper(app: &AppHandle<Wry>) -> Result<SlideshowStatus, String> {
+    step_slideshow(app, Navigation::Back).await?;
+    Ok(slideshow_status(app).await)
+}
+
+// Show a wallpaper from the history again. Previous and next then step from that entry, and the
+// rotation carries on from where it was once next runs past the end of the history. The entry
+// is named by when it was applied and its file rather than its place, which shifts as the
+// history grows and drops old entries.
+pub async fn reapply_from_history(
+    app: &AppHandle<Wry>,
+    applied_at: i64,
+    path: &str,
+) -> Result<SlideshowStatus, String> {
+    let index = app.state::<AppState>().slideshow.inner.lock().unwrap().history.entries
+        .iter()
+        .rposition(|entry| entry.applied_at == applied_at && entry.path == path);
+    let index = index.ok_or_else(|| format!("{} is no longer in the history", path))?;
+    if !Path::new(path).is_file() {
+        return Err(format!("{} no longer exists", path));
+    }
+    show_from_slideshow(app, path.to_string(), Some(index)).await?;
+    Ok(slideshow_status(app).await)
+}
+
+// Turn auto-change on or off in whichever settings are in effect: the active collection's own
+// settings if it has them, the global ones otherwise
+pub async fn set_auto_change(app: &AppHandle<Wry>, enabled: bool) -> Result<SlideshowStatus, String> {
+    let (collection, settings) = slideshow_scope(app).await?;
+    let mut settings = settings.unwrap_or_default();
+    settings.auto_change = enabled;
+
+    match collection.filter(|collection| collection.settings.is_some()) {
+        Some(mut collection) => {
+            with_library_db(app, |db| db.set_collection_settings(collection.id, Some(&settings)))?;
+            collection.settings = Some(settings);
+            let _ = app.emit(ACTIVE_COLLECTION_CHANGED_EVENT, Some(collection));
+        }
+        None => {
+            update_app_state(app, |state| state.wallpaper_settings = Some(settings.clone())).await?;
+            let _ = app.emit(WALLPAPER_SETTINGS_CHANGED_EVENT, settings);
+        }
+    }
+
+    wake_slideshow(app);
+    Ok(slideshow_status(app).await)
+}
+
+pub async fn slideshow_status(app: &AppHandle<Wry>) -> SlideshowStatus {
+    let (collection, settings) = slideshow_scope(app).await.unwrap_or_default();
+    let settings = settings.unwrap_or_default();
+    let independent = load_app_state(app.clone()).await.is_ok_and(|state| state.monitor_slideshows.independent);
+    let inner = app.state::<AppState>().slideshow.inner.lock().unwrap().clone();
+
+    SlideshowStatus {
+        enabled: settings.auto_change,
+        interval: settings.interval,
+        collection_id: collection.map(|collection| collection.id),
+        current: inner.current().map(str::to_string),
+        next_change_at: inner.next_change_at,
+        can_go_back: inner.history.position > 0 && !independent,
+    }
+}
+
+// What the slideshow picks from: usable, unblocked wallpapers in the active collection (or the
+// whole library) matching the slideshow filter, with near-duplicates folded into one when enabled
+pub async fn slideshow_candidates(app: &AppHandle<Wry>) -> Result<Vec<WallpaperInfo>, String> {
+    let (collection, settings) = slideshow_scope(app).await?;
+    collection_candidates(app, collection.map(|collection| collection.id), settings).await
+}
+
+// Same as slideshow_candidates, for a given collection and settings
+async fn collection_candidates(
+    app: &AppHandle<Wry>,
+    collection_id: Option<i64>,
+    settings: Option<WallpaperSettings>,
+) -> Result<Vec<WallpaperInfo>, String> {
+    let collapse = settings.as_ref().is_some_and(|settings| settings.collapse_duplicates);
+    let filter = settings.map(|settings| settings.filter).unwrap_or_default();
+
+    let app = app.clone();
+    tokio::task::spawn_blocking(move || {
+        let wallpapers: Vec<WallpaperInfo> = with_library_db(&app, |db| db.filter_wallpapers(&filter, collection_id))?
+            .into_iter()
+            .filter(|info| info.is_valid && !info.is_missing)
+            .collect();
+
+        if !collapse {
+            return Ok(wallpapers);
+        }
+        let groups = cached_duplicate_groups(&app, DEFAULT_MAX_DISTANCE)?;
+        Ok(collapse_duplicates(wallpapers, &groups))
+    })
+    .await
+    .map_err(|e| format!("Failed to load slideshow: {}", e))?
+}
+
+// The schedule, a solar set or a dynamic set has the screen
+fn slideshow_held(app: &AppHandle<Wry>) -> bool {
+    matches!(scheduled_target(app), Some(ScheduleTarget::Wallpaper { .. }))
+        || solar_held(app)
+        || dynamic_held(app)
+}
+
+// Time left until the next change, or None while auto-change is off, the screen is held or every
+// screen runs its own slideshow
+async fn next_delay(app: &AppHandle<Wry>) -> Option<Duration> {
+    let held = slideshow_held(app)
+        || load_app_state(app.clone()).await.is_ok_and(|state| state.monitor_slideshows.independent);
+    let settings = slideshow_scope(app).await.ok()
+        .and_then(|(_, settings)| settings)
+        .filter(|settings| settings.auto_change && settings.interval > 0 && !held);
+
+    let state = app.state::<AppState>();
+    let mut inner = state.slideshow.inner.lock().unwrap();
+    let Some(settings) = settings else {
+        // Turning auto-change back on starts a fresh interval
+        inner.changed_at = None;
+        inner.next_change_at = None;
+        return None;
+    };
+
+    let changed_at = *inner.changed_at.get_or_insert_with(Instant::now);
+    let delay = (changed_at + Duration::from_secs(settings.interval as u64)).saturating_duration_since(Instant::now());
+    inner.next_change_at = Some(unix_now() + delay.as_secs() as i64);
+    Some(delay)
+}
+
+// Back and forward walk the history, passing over entries whose files have gone away; forward
+// takes the next wallpaper from the rotation once it reaches the end, and so does a jump. Walking
+// the history leaves the rotation cursor alone.
+async fn step_slideshow(app: &AppHandle<Wry>, navigation: Navigation) -> Result<(), String> {
+    // Independent screens each move on in their own rotation, and have no shared history to go
+    // back through
+    if load_app_state(app.clone()).await?.monitor_slideshows.independent {
+        return match navigation {
+            Navigation::Back => Err("Each screen runs its own slideshow".to_string()),
+            Navigation::Forward | Navigation::Jump => next_screen_wallpapers(app).await,
+        };
+    }
+
+    let state = app.state::<AppState>();
+    let steps: Vec<(usize, String)> = {
+        let inner = state.slideshow.inner.lock().unwrap();
+        let entries = inner.history.entries.iter().map(|entry| entry.path.clone()).enumerate();
+        match navigation {
+            Navigation::Back => entries.take(inner.history.position).rev().collect(),
+            Navigation::Forward => entries.skip(inner.history.position + 1).collect(),
+            Navigation::Jump => Vec::new(),
+        }
+    };
+
+    match (steps.into_iter().find(|(_, path)| Path::new(path).is_file()), navigation) {
+        (Some((index, path)), _) => show_from_slideshow(app, path, Some(index)).await,
+        (None, Navigation::Back) => Err("No previous wallpaper".to_string()),
+        (None, _) => show_from_slideshow(app, pick_next(app).await?, None).await,
+    }
+}
+
+async fn show_from_slideshow(app: &AppHandle<Wry>, path: String, index: Option<usize>) -> Result<(), String> {
+    let slideshow = &app.state::<AppState>().inner().slideshow;
+
+    // Record the move before applying so the apply hook sees it as the current wallpaper
+    let snapshot = {
+        let mut inner = slideshow.inner.lock().unwrap();
+        let snapshot = inner.clone();
+        inner.record(&path, index);
+        snapshot
+    };
+    if let Err(e) = apply_wallpaper(app.clone(), path.clone()).await {
+        *slideshow.inner.lock().unwrap() = snapshot;
+        return Err(e);
+    }
+    record_wallpaper_shown(app, &path);
+    Ok(())
+}
+
+// The next wallpaper in the rotation, in order or shuffled as the settings say, or the one picked
+// ahead for preloading. The cursor is saved so the rotation carries on from the same place after
+// a restart.
+async fn pick_next(app: &AppHandle<Wry>) -> Result<String, String> {
+    let (collection, settings) = slideshow_scope(app).await?;
+    let settings = settings.unwrap_or_default();
+    let state = app.state::<AppState>();
+    let (current, upcoming) = {
+        let mut inner = state.slideshow.inner.lock().unwrap();
+        (inner.current().map(str::to_string), inner.upcoming.take())
+    };
+    let candidates = slideshow_candidates(app).await?;
+
+    let upcoming = upcoming.filter(|upcoming| {
+        upcoming.still_fits(collection.as_ref(), &settings, current.as_deref())
+            && candidates.iter().any(|info| info.path == upcoming.path)
+    });
+    let next = match upcoming {
+        Some(upcoming) => upcoming,
+        None => choose_next(app, collection, settings, candidates, current.as_deref()).await?,
+    };
+
+    if let Some(cursor) = next.cursor {
+        with_library_db(app, |db| db.set_rotation_order(SHARED_ROTATION, &cursor.order))?;
+        update_app_state(app, |state| state.slideshow_cursor = cursor).await?;
+    }
+    Ok(next.path)
+}
+
+// Pick from the candidates without moving the rotation yet
+async fn choose_next(
+    app: &AppHandle<Wry>,
+    collection: Option<Collection>,
+    settings: WallpaperSettings,
+    candidates: Vec<WallpaperInfo>,
+    current: Option<&str>,
+) -> Result<Upcoming, String> {
+    let collection_id = collection.map(|collection| collection.id);
+
+    // Weighted shuffle draws every pick on its own and leaves the cursor where it was
+    if settings.random_order && settings.weighting.enabled {
+        let weights = weigh_candidates(app, &candidates, &settings.weighting, current)?;
+        let path = pick_weighted(&weights)
+            .map(|pick| pick.path.clone())
+            .ok_or_else(|| "No wallpapers to show".to_string())?;
+        return Ok(Upcoming { path, collection_id, settings, cursor: None });
+    }
+
+    let candidates: Vec<String> = candidates.into_iter().map(|info| info.path).collect();
+    let mut cursor = load_app_state(app.clone()).await?.slideshow_cursor;
+    cursor.order = with_library_db(app, |db| db.rotation_order(SHARED_ROTATION))?;
+    let path = next_in_rotation(&mut cursor, &candidates, collection_id, settings.random_order, current)
+        .ok_or_else(|| "No wallpapers to show".to_string())?;
+    Ok(Upcoming { path, collection_id, settings, cursor: Some(cursor) })
+}
+
+// Warm up what the next change will show: the next entry in the history when stepping forward
+// will replay it, the rotation's next wallpaper otherwise
+async fn preload_next(app: &AppHandle<Wry>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let (forward, current, upcoming) = {
+        let inner = state.slideshow.inner.lock().unwrap();
+        let forward: Vec<String> = inner.history.entries.iter()
+            .skip(inner.history.position + 1)
+            .map(|entry| entry.path.clone())
+            .collect();
+        (forward, inner.current().map(str::to_string), inner.upcoming.clone())
+    };
+
+    let path = match forward.into_iter().find(|path| Path::new(path).is_file()) {
+        Some(path) => path,
+        None => {
+            let (collection, settings) = slideshow_scope(app).await?;
+            let settings = settings.unwrap_or_default();
+            match upcoming.filter(|upcoming| upcoming.still_fits(collection.as_ref(), &settings, current.as_deref())) {
+                Some(upcoming) => upcoming.path,
+                None => {
+                    let candidates = slideshow_candidates(app).await?;
+                    let upcoming = choose_next(app, collection, settings, candidates, current.as_deref()).await?;
+                    let path = upcoming.path.clone();
+                    state.slideshow.inner.lock().unwrap().upcoming = Some(upcoming);
+                    path
+                }
+            }
+        }
+    };
+    preload_wallpaper(app, &path).await
+}
+
+// The odds weighted shuffle gives each candidate for the next change, most likely first. They
+// follow the weighting in effect even while it is switched off, to help tune it.
+pub async fn slideshow_probabilities(app: &AppHandle<Wry>) -> Result<Vec<PickWeight>, String> {
+    let (_, settings) = slideshow_scope(app).await?;
+    let current = app.state::<AppState>().slideshow.inner.lock().unwrap().current().map(str::to_string);
+    let candidates = slideshow_candidates(app).await?;
+
+    let mut weights = weigh_candidates(app, &candidates, &settings.unwrap_or_default().weighting, current.as_deref())?;
+    weights.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+    Ok(weights)
+}
+
+fn weigh_candidates(
+    app: &AppHandle<Wry>,
+    candidates: &[WallpaperInfo],
+    weighting: &SlideshowWeighting,
+    current: Option<&str>,
+) -> Result<Vec<PickWeight>, String> {
+    let usage = with_library_db(app, |db| db.usage_stats())?;
+    Ok(pick_weights(candidates, &usage, weighting, current, unix_now()))
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
\ No newline at end of file
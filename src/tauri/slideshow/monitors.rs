@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Monitor, Wry};
+use tokio::sync::Notify;
+use crate::commands::{
+    asset_url, close_wallpaper_window, discard_preloaded_window, load_app_state, open_wallpaper_window,
+    record_wallpaper_shown, update_app_state, video_wallpaper_url, ALL_MONITORS,
+};
+use crate::library::collections::slideshow_scope;
+use crate::library::db::with_library_db;
+use crate::state::AppState;
+use crate::tray::refresh_tray_menu;
+use crate::types::{MonitorInfo, MonitorSlideshow, MonitorSlideshows, WallpaperSettings};
+use crate::utils::media::sniff_media;
+use super::order::next_in_rotation;
+use super::weights::pick_weighted;
+use super::{collection_candidates, slideshow_held, unix_now, wake_slideshow, weigh_candidates};
+
+pub const MONITORS_CHANGED_EVENT: &str = "monitors-changed";
+
+#[derive(Default)]
+pub struct Monitors {
+    // Wakes the per-screen scheduler along with the main one, and when screen settings change
+    pub(super) wake: Notify,
+    screens: Mutex<HashMap<String, Screen>>,
+}
+
+// A screen showing its own slideshow in a wallpaper window. The window is closed while a
+// wallpaper put on every screen by hand shows, until the screen's next change.
+struct Screen {
+    changed_at: Instant,
+    next_change_at: Option<i64>,
+}
+
+// Monitors are told apart by name; where the system gives none, by size and place on the desktop
+pub fn monitor_name(monitor: &Monitor) -> String {
+    monitor.name().cloned().unwrap_or_else(|| {
+        let (size, position) = (monitor.size(), monitor.position());
+        format!("{}x{}+{}+{}", size.width, size.height, position.x, position.y)
+    })
+}
+
+// Run the screens' own slideshows for the lifetime of the app. They only run while screens are
+// set to be independent and nothing else holds the screen.
+pub fn start_monitor_slideshows(app: AppHandle<Wry>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let delay = match run_monitor_slideshows(&app).await {
+                Ok(delay) => delay,
+                Err(_e) => {
+                    #[cfg(debug_assertions)]
+                    eprintln!("Failed to run monitor slideshows: {}", _e);
+
+                    // Try again later instead of right away
+                    Some(Duration::from_secs(60))
+                }
+            };
+            if let Ok(monitors) = monitor_infos(&app).await {
+                let _ = app.emit(MONITORS_CHANGED_EVENT, monitors);
+            }
+
+            let monitors = &app.state::<AppState>().inner().slideshow.monitors;
+            match delay {
+                Some(delay) => {
+                    let _ = tokio::time::timeout(delay, monitors.wake.notified()).await;
+                }
+                None => monitors.wake.notified().await,
+            }
+        }
+    });
+}
+
+// Put every connected screen's wallpaper up and change those that are due. Returns how long
+// until the next change on any screen, or None when no screen has one coming.
+async fn run_monitor_slideshows(app: &AppHandle<Wry>) -> Result<Option<Duration>, String> {
+    let config = load_app_state(app.clone()).await?.monitor_slideshows;
+    let state = app.state::<AppState>();
+    if !config.independent || slideshow_held(app) {
+        close_screens(app, &state, |_| true);
+        return Ok(None);
+    }
+
+    let monitors = app.available_monitors().map_err(|e| format!("Failed to list monitors: {}", e))?;
+    let names: Vec<String> = monitors.iter().map(monitor_name).collect();
+    close_screens(app, &state, |name| !names.contains(&name.to_string()));
+
+    let mut next_delay: Option<Duration> = None;
+    for (monitor, name) in monitors.iter().zip(names) {
+        let mut slideshow = screen_slideshow(app, &config, &name).await?;
+        let settings = &slideshow.settings;
+        let timed = settings.auto_change && settings.interval > 0;
+        let interval = Duration::from_secs(settings.interval as u64);
+
+        let changed_at = state.slideshow.monitors.screens.lock().unwrap().get(&name).map(|screen| screen.changed_at);
+        let due = match changed_at {
+            // A screen without a window yet gets its last wallpaper back, or a first one
+            None => true,
+            Some(changed_at) => timed && changed_at + interval <= Instant::now(),
+        };
+        if due {
+            let restore = changed_at.is_none()
+                && slideshow.current.as_deref().is_some_and(|path| Path::new(path).is_file());
+            let result = if restore {
+                show_on_screen(app, monitor, slideshow.current.clone().unwrap_or_default()).await
+            } else {
+                step_screen(app, monitor, &mut slideshow).await
+            };
+            if let Err(_e) = result {
+                #[cfg(debug_assertions)]
+                eprintln!("Failed to change wallpaper on {}: {}", name, _e);
+            }
+
+            // Failures also wait an interval before trying again
+            state.slideshow.monitors.screens.lock().unwrap().insert(name.clone(), Screen {
+                changed_at: Instant::now(),
+                next_change_at: None,
+            });
+        }
+
+        let mut screens = state.slideshow.monitors.screens.lock().unwrap();
+        let Some(screen) = screens.get_mut(&name) else {
+            continue;
+        };
+        screen.next_change_at = None;
+        if timed {
+            let delay = (screen.changed_at + interval).saturating_duration_since(Instant::now());
+            screen.next_change_at = Some(unix_now() + delay.as_secs() as i64);
+            next_delay = Some(next_delay.map_or(delay, |next_delay| next_delay.min(delay)));
+        }
+    }
+    Ok(next_delay)
+}
+
+// The screen's saved slideshow. A screen running its own for the first time starts from the
+// collection and settings the shared slideshow has in effect.
+async fn screen_slideshow(app: &AppHandle<Wry>, config: &MonitorSlideshows, name: &str) -> Result<MonitorSlideshow, String> {
+    if let Some(slideshow) = config.monitors.iter().find(|slideshow| slideshow.monitor == name) {
+        return Ok(slideshow.clone());
+    }
+    let (collection, settings) = slideshow_scope(app).await?;
+    let slideshow = MonitorSlideshow {
+        monitor: name.to_string(),
+        collection_id: collection.map(|collection| collection.id),
+        settings: settings.unwrap_or_default(),
+        cursor: Default::default(),
+        current: None,
+    };
+
+    update_app_state(app, |state| state.monitor_slideshows.monitors.push(slideshow.clone())).await?;
+    Ok(slideshow)
+}
+
+// Show the next wallpaper in the screen's rotation and save where the rotation is
+async fn step_screen(app: &AppHandle<Wry>, monitor: &Monitor, slideshow: &mut MonitorSlideshow) -> Result<(), String> {
+    let path = pick_for_screen(app, slideshow).await?;
+    show_on_screen(app, monitor, path.clone()).await?;
+    record_wallpaper_shown(app, &path);
+    slideshow.current = Some(path);
+
+    // Only the rotation is written back, in case the screen's settings changed meanwhile
+    update_app_state(app, |state| {
+        match state.monitor_slideshows.monitors.iter_mut().find(|saved| saved.monitor == slideshow.monitor) {
+            Some(saved) => {
+                saved.cursor = slideshow.cursor.clone();
+                saved.current = slideshow.current.clone();
+            }
+            None => state.monitor_slideshows.monitors.push(slideshow.clone()),
+        }
+    })
+    .await
+}
+
+// Same picking as the shared slideshow, over the screen's own collection and settings
+async fn pick_for_screen(app: &AppHandle<Wry>, slideshow: &mut MonitorSlideshow) -> Result<String, String> {
+    let settings = &slideshow.settings;
+    let candidates = collection_candidates(app, slideshow.collection_id, Some(settings.clone())).await?;
+    let current = slideshow.current.as_deref();
+
+    if settings.random_order && settings.weighting.enabled {
+        let weights = weigh_candidates(app, &candidates, &settings.weighting, current)?;
+        return pick_weighted(&weights)
+            .map(|pick| pick.path.clone())
+            .ok_or_else(|| "No wallpapers to show".to_string());
+    }
+
+    let candidates: Vec<String> = candidates.into_iter().map(|info| info.path).collect();
+    let (collection_id, shuffled) = (slideshow.collection_id, settings.random_order);
+    let scope = screen_rotation(&slideshow.monitor);
+    slideshow.cursor.order = with_library_db(app, |db| db.rotation_order(&scope))?;
+    let path = next_in_rotation(&mut slideshow.cursor, &candidates, collection_id, shuffled, current)
+        .ok_or_else(|| "No wallpapers to show".to_string())?;
+    with_library_db(app, |db| db.set_rotation_order(&scope, &slideshow.cursor.order))?;
+    Ok(path)
+}
+
+// Key of a screen's rotation order in the library database
+fn screen_rotation(monitor: &str) -> String {
+    format!("monitor:{}", monitor)
+}
+
+// Images and videos alike play in a wallpaper window on the screen, since the system wallpaper
+// is the same picture on every screen
+async fn show_on_screen(app: &AppHandle<Wry>, monitor: &Monitor, path: String) -> Result<(), String> {
+    let detection = sniff_media(Path::new(&path));
+    if !detection.is_valid {
+        return Err(format!("Cannot use {}", path));
+    }
+    let url = video_wallpaper_url(&asset_url(&path), &detection.mime_type);
+    let state = app.state::<AppState>();
+    open_wallpaper_window(app, &state, url, Some(monitor)).await
+}
+
+// Close the windows of the screens `close` picks and stop timing them
+fn close_screens(app: &AppHandle<Wry>, state: &AppState, close: impl Fn(&str) -> bool) {
+    let closed: Vec<String> = {
+        let mut screens = state.slideshow.monitors.screens.lock().unwrap();
+        let closed = screens.keys().filter(|name| close(name)).cloned().collect::<Vec<_>>();
+        for name in &closed {
+            screens.remove(name);
+        }
+        closed
+    };
+    for name in closed {
+        let _ = close_wallpaper_window(app, state, &name);
+    }
+}
+
+// Connected screens, with what their own slideshows show
+pub async fn monitor_infos(app: &AppHandle<Wry>) -> Result<Vec<MonitorInfo>, String> {
+    let primary = app.primary_monitor().ok().flatten().map(|monitor| monitor_name(&monitor));
+    let monitors = app.available_monitors().map_err(|e| format!("Failed to list monitors: {}", e))?;
+    let config = load_app_state(app.clone()).await?.monitor_slideshows;
+    let state = app.state::<AppState>();
+    let screens = state.slideshow.monitors.screens.lock().unwrap();
+
+    Ok(monitors.iter()
+        .map(|monitor| {
+            let name = monitor_name(monitor);
+            let screen = screens.get(&name);
+            MonitorInfo {
+                x: monitor.position().x,
+                y: monitor.position().y,
+                width: monitor.size().width,
+                height: monitor.size().height,
+                primary: primary.as_deref() == Some(name.as_str()),
+                current: screen
+                    .and_then(|_| config.monitors.iter().find(|slideshow| slideshow.monitor == name))
+                    .and_then(|slideshow| slideshow.current.clone()),
+                next_change_at: screen.and_then(|screen| screen.next_change_at),
+                name,
+            }
+        })
+        .collect())
+}
+
+// Switch between one rotation on every screen and a slideshow per screen. The window covering
+// every screen is closed going independent, and the shared slideshow picks up again coming back.
+pub async fn set_independent_screens(app: &AppHandle<Wry>, independent: bool) -> Result<MonitorSlideshows, String> {
+    let config = update_app_state(app, |state| {
+        state.monitor_slideshows.independent = independent;
+        state.monitor_slideshows.clone()
+    })
+    .await?;
+
+    if independent {
+        let state = app.state::<AppState>();
+        discard_preloaded_window(app, &state);
+        close_wallpaper_window(app, &state, ALL_MONITORS)?;
+    }
+    wake_slideshow(app);
+    // Previous only goes back through the shared history
+    refresh_tray_menu(app).await;
+    Ok(config)
+}
+
+// Change what a screen's slideshow picks from and how. The rotation starts over on a different
+// collection or order, as it does for the shared slideshow.
+pub async fn update_screen_slideshow(
+    app: &AppHandle<Wry>,
+    monitor: String,
+    collection_id: Option<i64>,
+    settings: WallpaperSettings,
+) -> Result<MonitorSlideshows, String> {
+    let config = update_app_state(app, |state| {
+        let monitors = &mut state.monitor_slideshows.monitors;
+        match monitors.iter_mut().find(|slideshow| slideshow.monitor == monitor) {
+            Some(slideshow) => {
+                slideshow.collection_id = collection_id;
+                slideshow.settings = settings;
+            }
+            None => monitors.push(MonitorSlideshow {
+                monitor,
+                collection_id,
+                settings,
+                cursor: Default::default(),
+                current: None,
+            }),
+        }
+        state.monitor_slideshows.clone()
+    })
+    .await?;
+
+    app.state::<AppState>().slideshow.monitors.wake.notify_one();
+    Ok(config)
+}
+
+// Change one screen's wallpaper now, restarting its countdown
+pub async fn next_screen_wallpaper(app: &AppHandle<Wry>, monitor: &str) -> Result<Vec<MonitorInfo>, String> {
+    let config = running_screen_slideshows(app).await?;
+    let target = app.available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?
+        .into_iter()
+        .find(|candidate| monitor_name(candidate) == monitor)
+        .ok_or_else(|| format!("Monitor {} is not connected", monitor))?;
+
+    advance_screen(app, &config, &target).await?;
+    app.state::<AppState>().slideshow.monitors.wake.notify_one();
+    monitor_infos(app).await
+}
+
+// Change every connected screen's wallpaper now, which is what the shared next does while the
+// screens are independent. A screen that fails does not hold up the others.
+pub async fn next_screen_wallpapers(app: &AppHandle<Wry>) -> Result<(), String> {
+    let config = running_screen_slideshows(app).await?;
+    let monitors = app.available_monitors().map_err(|e| format!("Failed to list monitors: {}", e))?;
+
+    let mut result = Ok(());
+    for monitor in &monitors {
+        if let Err(e) = advance_screen(app, &config, monitor).await {
+            result = Err(format!("{}: {}", monitor_name(monitor), e));
+        }
+    }
+    app.state::<AppState>().slideshow.monitors.wake.notify_one();
+    result
+}
+
+// Close the screens' own windows, which would cover a wallpaper put on every screen by hand.
+// Their countdowns carry on, so each screen's slideshow takes over again at its next change.
+pub fn uncover_screens(app: &AppHandle<Wry>, state: &AppState) -> Result<(), String> {
+    let names: Vec<String> = state.slideshow.monitors.screens.lock().unwrap().keys().cloned().collect();
+    for name in names {
+        close_wallpaper_window(app, state, &name)?;
+    }
+    Ok(())
+}
+
+// The screens' slideshows, while they are the ones changing the wallpaper
+async fn running_screen_slideshows(app: &AppHandle<Wry>) -> Result<MonitorSlideshows, String> {
+    let config = load_app_state(app.clone()).await?.monitor_slideshows;
+    if !config.independent {
+        return Err("Screens share one slideshow".to_string());
+    }
+    if slideshow_held(app) {
+        return Err("The schedule, a solar set or a dynamic set has the screen".to_string());
+    }
+    Ok(config)
+}
+
+// Step a screen's slideshow on now and restart its countdown
+async fn advance_screen(app: &AppHandle<Wry>, config: &MonitorSlideshows, monitor: &Monitor) -> Result<(), String> {
+    let name = monitor_name(monitor);
+    let mut slideshow = screen_slideshow(app, config, &name).await?;
+    step_screen(app, monitor, &mut slideshow).await?;
+
+    app.state::<AppState>().slideshow.monitors.screens.lock().unwrap().insert(name, Screen {
+        changed_at: Instant::now(),
+        next_change_at: None,
+    });
+    Ok(())
+}
\ No newline at end of file
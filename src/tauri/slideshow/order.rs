@@ -0,0 +1,103 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
+use crate::types::SlideshowCursor;
+
+// Advance the rotation and return the wallpaper to show. Sequential mode follows the candidate
+// order and wraps; shuffle mode plays a shuffled pass through every candidate before reshuffling.
+pub fn next_in_rotation(
+    cursor: &mut SlideshowCursor,
+    candidates: &[String],
+    collection_id: Option<i64>,
+    shuffled: bool,
+    current: Option<&str>,
+) -> Option<String> {
+    if candidates.is_empty() {
+        return None;
+    }
+
+    // Switching collections or modes starts over, from the wallpaper on screen in sequential mode
+    if cursor.collection_id != collection_id || cursor.shuffled != shuffled {
+        *cursor = SlideshowCursor {
+            collection_id,
+            shuffled,
+            last_path: current.map(str::to_string),
+            ..Default::default()
+        };
+    }
+
+    // Try twice so the wallpaper already on screen is not picked again
+    let mut next = None;
+    for _ in 0..2 {
+        next = Some(if shuffled {
+            next_shuffled(cursor, candidates, current)
+        } else {
+            next_sequential(cursor, candidates)
+        });
+        cursor.last_path = next.clone();
+        if candidates.len() == 1 || next.as_deref() != current {
+            break;
+        }
+    }
+    next
+}
+
+fn next_sequential(cursor: &mut SlideshowCursor, candidates: &[String]) -> String {
+    // If the last wallpaper left the set, whatever moved into its place comes next
+    let index = match cursor.last_path.as_ref().and_then(|last| candidates.iter().position(|path| path == last)) {
+        Some(index) => (index + 1) % candidates.len(),
+        None => cursor.position % candidates.len(),
+    };
+    cursor.position = index;
+    candidates[index].clone()
+}
+
+fn next_shuffled(cursor: &mut SlideshowCursor, candidates: &[String], current: Option<&str>) -> String {
+    let available: HashSet<&str> = candidates.iter().map(String::as_str).collect();
+
+    // Forget entries that are gone, keeping the position on the same unplayed entry
+    let played = cursor.position.min(cursor.order.len());
+    let removed_before = cursor.order[..played].iter().filter(|path| !available.contains(path.as_str())).count();
+    cursor.order.retain(|path| available.contains(path.as_str()));
+    cursor.position = played - removed_before;
+
+    // Wallpapers added mid-pass still get shown in this pass, somewhere among the unplayed ones
+    let known: HashSet<String> = cursor.order.iter().cloned().collect();
+    for path in candidates.iter().filter(|path| !known.contains(*path)) {
+        let index = cursor.position + random_index(cursor.order.len() - cursor.position + 1);
+        cursor.order.insert(index, path.clone());
+    }
+
+    if cursor.position >= cursor.order.len() {
+        cursor.order = candidates.to_vec();
+        shuffle(&mut cursor.order);
+        cursor.position = 0;
+
+        // Do not open the new pass with the wallpaper that closed the last one
+        if cursor.order.len() > 1 && current.is_some_and(|current| cursor.order[0] == current) {
+            let swap = 1 + random_index(cursor.order.len() - 1);
+            cursor.order.swap(0, swap);
+        }
+    }
+
+    let next = cursor.order[cursor.position].clone();
+    cursor.position += 1;
+    next
+}
+
+// Fisher-Yates
+pub fn shuffle<T>(items: &mut [T]) {
+    for index in (1..items.len()).rev() {
+        items.swap(index, random_index(index + 1));
+    }
+}
+
+// Random index below len, which must be at least 1
+pub fn random_index(len: usize) -> usize {
+    (random_u64() % len as u64) as usize
+}
+
+// Randomly seeded per call, which is plenty for picking wallpapers
+pub fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
\ No newline at end of file
@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::{Datelike, Local, NaiveDateTime, TimeZone, Timelike};
+use tauri::{AppHandle, Manager, Wry};
+use tokio::sync::Notify;
+use crate::commands::{apply_wallpaper, load_app_state};
+use crate::library::collections::activate_collection;
+use crate::library::db::with_library_db;
+use crate::slideshow::dynamic::wake_dynamic;
+use crate::slideshow::solar::{forget_solar, update_solar};
+use crate::slideshow::{rotate_wallpaper, unix_now, wake_slideshow};
+use crate::state::AppState;
+use crate::types::{ScheduleRule, SchedulePreview, ScheduleTarget, ScheduleTime};
+use crate::utils::cron::CronSchedule;
+
+// Forward clock jumps up to this long (e.g. waking from sleep) still fire the cron rules skipped over
+const MAX_CATCH_UP_MINUTES: i64 = 24 * 60;
+
+// How far ahead a preview looks for the next change
+const PREVIEW_HORIZON_MINUTES: i64 = 7 * 24 * 60;
+
+// How long fired cron minutes are remembered; longer than any DST shift, so the wall-clock
+// minutes of a repeated hour are recognized when they come round again
+const FIRED_MEMORY_MINUTES: i64 = 3 * 60;
+
+// Recent cron fires by rule, as unix seconds and the local time they showed
+type FiredLog = HashMap<String, Vec<(i64, NaiveDateTime)>>;
+
+#[derive(Default)]
+pub struct Schedule {
+    // Wakes the timer when the rules change
+    wake: Notify,
+    // Target of the time range in effect
+    active: Mutex<Option<ScheduleTarget>>,
+    // Picture of the active solar set on screen, while the set has the screen
+    pub(super) solar: Mutex<Option<String>>,
+}
+
+// A rule with its times and cron expression parsed
+struct CompiledRule {
+    rule: ScheduleRule,
+    when: CompiledTime,
+}
+
+enum CompiledTime {
+    // Minutes since midnight, and a bit per weekday with 0 meaning every day
+    Range { start: u32, end: u32, days: u8 },
+    Cron(CronSchedule),
+}
+
+impl CompiledRule {
+    fn in_range(&self, time: &NaiveDateTime) -> bool {
+        let CompiledTime::Range { start, end, days } = self.when else {
+            return false;
+        };
+        let minute = time.hour() * 60 + time.minute();
+        let weekday = time.weekday().num_days_from_sunday();
+        let on_day = |weekday: u32| days == 0 || days & (1 << weekday) != 0;
+
+        if start == end {
+            on_day(weekday)
+        } else if start < end {
+            start <= minute && minute < end && on_day(weekday)
+        } else {
+            // Past midnight the range still belongs to the day it started on
+            (minute >= start && on_day(weekday)) || (minute < end && on_day((weekday + 6) % 7))
+        }
+    }
+
+    fn fires_at(&self, time: &NaiveDateTime) -> bool {
+        matches!(&self.when, CompiledTime::Cron(cron) if cron.matches(time))
+    }
+}
+
+// Check every rule, so a bad one is reported when it is saved rather than silently skipped later
+fn compile_rules(rules: &[ScheduleRule]) -> Result<Vec<CompiledRule>, String> {
+    let mut compiled = Vec::new();
+
+    for rule in rules {
+        let when = match &rule.when {
+            ScheduleTime::Range { start, end, days } => {
+                if rule.target == ScheduleTarget::Next {
+                    return Err(format!("{}: a time range needs a wallpaper or a collection", rule.name));
+                }
+                if let Some(day) = days.iter().find(|day| **day > 6) {
+                    return Err(format!("{}: invalid day {}", rule.name, day));
+                }
+                CompiledTime::Range {
+                    start: parse_time_of_day(start).map_err(|e| format!("{}: {}", rule.name, e))?,
+                    end: parse_time_of_day(end).map_err(|e| format!("{}: {}", rule.name, e))?,
+                    days: days.iter().fold(0, |bits, day| bits | (1 << day)),
+                }
+            }
+            ScheduleTime::Cron { expression } => {
+                CompiledTime::Cron(CronSchedule::parse(expression).map_err(|e| format!("{}: {}", rule.name, e))?)
+            }
+        };
+
+        if rule.enabled {
+            compiled.push(CompiledRule { rule: rule.clone(), when });
+        }
+    }
+
+    Ok(compiled)
+}
+
+pub fn validate_schedule(rules: &[ScheduleRule]) -> Result<(), String> {
+    compile_rules(rules).map(|_| ())
+}
+
+// Run the schedule for the lifetime of the app
+pub fn start_schedule(app: AppHandle<Wry>) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_minute = None;
+        let mut fired_log = FiredLog::new();
+
+        loop {
+            let minute = unix_now().div_euclid(60) * 60;
+            if let Err(_e) = run_schedule(&app, last_minute, minute, &mut fired_log).await {
+                #[cfg(debug_assertions)]
+                eprintln!("Failed to run wallpaper schedule: {}", _e);
+            }
+            last_minute = Some(minute);
+
+            // Waking on every minute boundary of the wall clock keeps cron rules on time and
+            // notices clock changes within a minute, which a single long sleep would not
+            let delay = (minute + 60 - unix_now()).clamp(1, 60) as u64;
+            let schedule = &app.state::<AppState>().inner().schedule;
+            let _ = tokio::time::timeout(Duration::from_secs(delay), schedule.wake.notified()).await;
+        }
+    });
+}
+
+pub fn wake_schedule(app: &AppHandle<Wry>) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state.schedule.wake.notify_one();
+    }
+}
+
+// What the time range in effect asks for, if any
+pub fn scheduled_target(app: &AppHandle<Wry>) -> Option<ScheduleTarget> {
+    let state = app.try_state::<AppState>()?;
+    state.schedule.active.lock().unwrap().clone()
+}
+
+// Local times are derived from the instant on every check, so DST changes need no special
+// handling beyond the repeated hour, which `due_cron_rules` keeps from firing twice
+async fn run_schedule(
+    app: &AppHandle<Wry>,
+    last_minute: Option<i64>,
+    minute: i64,
+    fired_log: &mut FiredLog,
+) -> Result<(), String> {
+    let current_state = load_app_state(app.clone()).await?;
+    let rules = compile_rules(&current_state.schedule)?;
+    let Some(now) = local_time(minute) else {
+        return Ok(());
+    };
+
+    let target = range_rule(&rules, &now).map(|rule| rule.rule.target.clone());
+    let changed = {
+        let state = app.state::<AppState>();
+        let mut active = state.schedule.active.lock().unwrap();
+        let changed = *active != target;
+        *active = target.clone();
+        changed
+    };
+
+    // A time range in effect takes the screen from the solar set
+    let solar = if target.is_some() {
+        forget_solar(app);
+        false
+    } else {
+        update_solar(app).await.unwrap_or_else(|_e| {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to show solar wallpaper: {}", _e);
+            true
+        })
+    };
+    if changed {
+        // The dynamic set player steps aside for time ranges and comes back after them
+        wake_dynamic(app);
+        if target.is_some() || (!solar && current_state.active_dynamic_set.is_none()) {
+            apply_range_target(app, target).await?;
+        }
+    }
+
+    // Every minute since the last check counts, up to a limit, so a late wake-up still fires
+    let first = match last_minute {
+        Some(last) if last < minute && minute - last <= MAX_CATCH_UP_MINUTES * 60 => last + 60,
+        _ => minute,
+    };
+    for rule in due_cron_rules(&rules, first, minute, fired_log, local_time) {
+        if let Err(_e) = apply_cron_target(app, &rule.rule.target).await {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to run scheduled rule {}: {}", rule.rule.name, _e);
+        }
+    }
+
+    Ok(())
+}
+
+// Cron rules due in the minutes from `first` to `minute` (unix seconds), recorded in `fired_log`.
+// `local_time` gives the wall clock at an instant.
+fn due_cron_rules<'a>(
+    rules: &'a [CompiledRule],
+    first: i64,
+    minute: i64,
+    fired_log: &mut FiredLog,
+    local_time: impl Fn(i64) -> Option<NaiveDateTime>,
+) -> Vec<&'a CompiledRule> {
+    // Rules are told apart by name and expression, so reordering or editing others keeps
+    // their record
+    let keys: Vec<Option<String>> = rules.iter()
+        .map(|rule| match &rule.rule.when {
+            ScheduleTime::Cron { expression } => Some(format!("{}\n{}", rule.rule.name, expression)),
+            ScheduleTime::Range { .. } => None,
+        })
+        .collect();
+    fired_log.retain(|key, _| keys.iter().flatten().any(|rule_key| rule_key == key));
+
+    let mut due = Vec::new();
+    for (rule, key) in rules.iter().zip(keys) {
+        let Some(key) = key else {
+            continue;
+        };
+        let fired_before = fired_log.entry(key).or_default();
+        fired_before.retain(|(at, _)| minute - at < FIRED_MEMORY_MINUTES * 60);
+
+        // Neither the same instant nor the same wall-clock minute fires twice
+        let fired = (0..=(minute - first) / 60)
+            .map(|step| minute - step * 60)
+            .filter_map(|at| Some((at, local_time(at)?)))
+            .find(|(at, time)| {
+                rule.fires_at(time)
+                    && !fired_before.iter().any(|(fired_at, fired_time)| fired_at == at || fired_time == time)
+            });
+        if let Some(fired) = fired {
+            fired_before.push(fired);
+            due.push(rule);
+        }
+    }
+    due
+}
+
+async fn apply_range_target(app: &AppHandle<Wry>, target: Option<ScheduleTarget>) -> Result<(), String> {
+    match target {
+        Some(ScheduleTarget::Wallpaper { path }) => {
+            // The slideshow holds while a wallpaper is scheduled
+            apply_wallpaper(app.clone(), path).await?;
+            wake_slideshow(app);
+        }
+        // Entering or leaving a collection range switches to a wallpaper from the new set right away
+        _ => {
+            rotate_wallpaper(app).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn apply_cron_target(app: &AppHandle<Wry>, target: &ScheduleTarget) -> Result<(), String> {
+    match target {
+        ScheduleTarget::Wallpaper { path } => {
+            apply_wallpaper(app.clone(), path.clone()).await?;
+        }
+        ScheduleTarget::Collection { id } => {
+            activate_collection(app.clone(), Some(*id)).await?;
+            rotate_wallpaper(app).await?;
+        }
+        ScheduleTarget::Next => {
+            rotate_wallpaper(app).await?;
+        }
+    }
+    Ok(())
+}
+
+// What the schedule would show at a moment, given in unix seconds
+pub async fn preview_schedule(app: &AppHandle<Wry>, at: i64) -> Result<SchedulePreview, String> {
+    let state = load_app_state(app.clone()).await?;
+    let rules = compile_rules(&state.schedule)?;
+    let minute = at.div_euclid(60) * 60;
+    let time = local_time(minute).ok_or_else(|| format!("Invalid time: {}", at))?;
+
+    // A cron rule firing in that very minute decides over the time range around it
+    let range = range_rule(&rules, &time);
+    let rule = rules.iter().find(|rule| rule.fires_at(&time)).or(range);
+
+    let (collection, wallpaper) = with_library_db(app, |db| match rule.map(|rule| &rule.rule.target) {
+        Some(ScheduleTarget::Wallpaper { path }) => Ok((None, db.get_wallpaper(path)?)),
+        Some(ScheduleTarget::Collection { id }) => Ok((db.get_collection(*id)?, None)),
+        _ => {
+            let collection = match state.active_collection {
+                Some(id) => db.get_collection(id)?,
+                None => None,
+            };
+            Ok((collection, None))
+        }
+    })?;
+
+    let range_target = range.map(|rule| &rule.rule.target);
+    let next_change_at = (1..=PREVIEW_HORIZON_MINUTES)
+        .map(|step| minute + step * 60)
+        .find(|&candidate| {
+            local_time(candidate).is_some_and(|time| {
+                rules.iter().any(|rule| rule.fires_at(&time))
+                    || range_rule(&rules, &time).map(|rule| &rule.rule.target) != range_target
+            })
+        });
+
+    Ok(SchedulePreview {
+        rule: rule.map(|rule| rule.rule.clone()),
+        collection,
+        wallpaper,
+        next_change_at,
+    })
+}
+
+fn range_rule<'a>(rules: &'a [CompiledRule], time: &NaiveDateTime) -> Option<&'a CompiledRule> {
+    rules.iter().find(|rule| rule.in_range(time))
+}
+
+// Wall clock time at an instant; times skipped by a DST change simply never come up
+fn local_time(unix_secs: i64) -> Option<NaiveDateTime> {
+    Local.timestamp_opt(unix_secs, 0).single().map(|time| time.naive_local())
+}
+
+// "HH:MM" to minutes since midnight
+fn parse_time_of_day(text: &str) -> Result<u32, String> {
+    let (hours, minutes) = text.trim().split_once(':').ok_or_else(|| format!("Invalid time: {}", text))?;
+    let hours: u32 = hours.parse().map_err(|_| format!("Invalid time: {}", text))?;
+    let minutes: u32 = minutes.parse().map_err(|_| format!("Invalid time: {}", text))?;
+    if hours > 23 || minutes > 59 {
+        return Err(format!("Invalid time: {}", text));
+    }
+    Ok(hours * 60 + minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, NaiveDate};
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 1, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn rule(name: &str, when: ScheduleTime, target: ScheduleTarget) -> ScheduleRule {
+        ScheduleRule { name: name.to_string(), enabled: true, when, target }
+    }
+
+    fn range(start: &str, end: &str, days: Vec<u8>) -> CompiledRule {
+        let when = ScheduleTime::Range { start: start.to_string(), end: end.to_string(), days };
+        let target = ScheduleTarget::Collection { id: 1 };
+        compile_rules(&[rule("night", when, target)]).unwrap().pop().unwrap()
+    }
+
+    fn cron(name: &str, expression: &str) -> CompiledRule {
+        let when = ScheduleTime::Cron { expression: expression.to_string() };
+        compile_rules(&[rule(name, when, ScheduleTarget::Next)]).unwrap().pop().unwrap()
+    }
+
+    #[test]
+    fn range_within_a_day() {
+        let rule = range("09:00", "17:00", vec![]);
+        assert!(rule.in_range(&at(5, 9, 0)));
+        assert!(rule.in_range(&at(5, 16, 59)));
+        assert!(!rule.in_range(&at(5, 17, 0)));
+        assert!(!rule.in_range(&at(5, 8, 59)));
+    }
+
+    #[test]
+    fn range_across_midnight() {
+        let rule = range("22:00", "06:00", vec![]);
+        assert!(rule.in_range(&at(5, 22, 0)));
+        assert!(rule.in_range(&at(5, 23, 59)));
+        assert!(rule.in_range(&at(6, 0, 0)));
+        assert!(rule.in_range(&at(6, 5, 59)));
+        assert!(!rule.in_range(&at(6, 6, 0)));
+        assert!(!rule.in_range(&at(5, 21, 59)));
+    }
+
+    #[test]
+    fn range_across_midnight_keeps_its_start_day() {
+        // Fridays only; 2026-01-09 is a Friday
+        let rule = range("22:00", "06:00", vec![5]);
+        assert!(rule.in_range(&at(9, 23, 0)));
+        assert!(rule.in_range(&at(10, 5, 0)));
+        assert!(!rule.in_range(&at(9, 5, 0)));
+        assert!(!rule.in_range(&at(10, 23, 0)));
+    }
+
+    #[test]
+    fn cron_fires_once_per_minute() {
+        let rules = [cron("every", "* * * * *")];
+        let mut fired_log = FiredLog::new();
+        let local_time = |secs: i64| DateTime::from_timestamp(secs, 0).map(|time| time.naive_utc());
+
+        assert_eq!(due_cron_rules(&rules, 600, 600, &mut fired_log, local_time).len(), 1);
+        assert!(due_cron_rules(&rules, 600, 600, &mut fired_log, local_time).is_empty());
+        assert_eq!(due_cron_rules(&rules, 660, 660, &mut fired_log, local_time).len(), 1);
+    }
+
+    #[test]
+    fn cron_catches_up_once() {
+        let rules = [cron("hourly", "0 * * * *")];
+        let mut fired_log = FiredLog::new();
+        let local_time = |secs: i64| DateTime::from_timestamp(secs, 0).map(|time| time.naive_utc());
+
+        // Waking at 02:30 after sleeping through 01:00 and 02:00 fires once, not twice
+        assert_eq!(due_cron_rules(&rules, 3600, 9000, &mut fired_log, local_time).len(), 1);
+        assert!(due_cron_rules(&rules, 9060, 9060, &mut fired_log, local_time).is_empty());
+    }
+
+    #[test]
+    fn cron_skips_repeated_hour() {
+        let rules = [cron("half past one", "30 1 * * *")];
+        let mut fired_log = FiredLog::new();
+        // Clocks fall back an hour at 7200: the hour after it shows the same wall-clock
+        // times as the hour before it
+        let local_time = |secs: i64| {
+            let shown = if secs < 7200 { secs } else { secs - 3600 };
+            DateTime::from_timestamp(shown, 0).map(|time| time.naive_utc())
+        };
+
+        assert_eq!(due_cron_rules(&rules, 5400, 5400, &mut fired_log, local_time).len(), 1);
+        assert!(due_cron_rules(&rules, 9000, 9000, &mut fired_log, local_time).is_empty());
+        // The next day fires again
+        assert_eq!(due_cron_rules(&rules, 95400, 95400, &mut fired_log, local_time).len(), 1);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,115 @@
+use tauri::{AppHandle, Manager, Wry};
+use crate::commands::{apply_wallpaper, load_app_state, update_app_state};
+use crate::library::db::with_library_db;
+use crate::slideshow::dynamic::wake_dynamic;
+use crate::slideshow::schedule::wake_schedule;
+use crate::slideshow::{unix_now, wake_slideshow};
+use crate::state::AppState;
+use crate::types::{SolarPhase, SolarSet, SolarStatus, SolarVariants};
+use crate::utils::solar::{next_phase_change, solar_phase, solar_position};
+
+// How far ahead the status looks for the next phase change
+const STATUS_HORIZON_MINUTES: i64 = 2 * 24 * 60;
+
+// The picture for a phase, borrowing from the closest phase that has one
+pub fn variant_for_phase(variants: &SolarVariants, phase: SolarPhase) -> Option<&String> {
+    let order = match phase {
+        SolarPhase::Day => [&variants.day, &variants.golden_hour, &variants.dusk, &variants.night],
+        SolarPhase::GoldenHour => [&variants.golden_hour, &variants.day, &variants.dusk, &variants.night],
+        SolarPhase::Dusk => [&variants.dusk, &variants.golden_hour, &variants.night, &variants.day],
+        SolarPhase::Night => [&variants.night, &variants.dusk, &variants.golden_hour, &variants.day],
+    };
+    order.into_iter().find_map(Option::as_ref)
+}
+
+// Show the active solar set's picture for where the sun is now. Returns whether a set is in
+// control, in which case the slideshow holds. Called from the schedule's minute tick.
+pub async fn update_solar(app: &AppHandle<Wry>) -> Result<bool, String> {
+    let set = match load_app_state(app.clone()).await?.active_solar_set {
+        Some(id) => with_library_db(app, |db| db.get_solar_set(id))?,
+        None => None,
+    };
+    let path = set.and_then(|set| {
+        let phase = solar_phase(solar_position(unix_now(), set.latitude, set.longitude).elevation);
+        variant_for_phase(&set.variants, phase).cloned()
+    });
+    let Some(path) = path else {
+        forget_solar(app);
+        return Ok(false);
+    };
+
+    // Only switch when the phase brings a different picture, so a manual pick stays until then
+    let schedule = &app.state::<AppState>().inner().schedule;
+    let applied = schedule.solar.lock().unwrap().replace(path.clone());
+    if applied.as_ref() != Some(&path) {
+        if let Err(e) = apply_wallpaper(app.clone(), path).await {
+            *schedule.solar.lock().unwrap() = None;
+            return Err(e);
+        }
+        wake_slideshow(app);
+    }
+    Ok(true)
+}
+
+// Stop holding the solar picture, letting the slideshow carry on
+pub fn forget_solar(app: &AppHandle<Wry>) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    if state.schedule.solar.lock().unwrap().take().is_some() {
+        wake_slideshow(app);
+    }
+}
+
+pub fn solar_held(app: &AppHandle<Wry>) -> bool {
+    app.try_state::<AppState>()
+        .is_some_and(|state| state.schedule.solar.lock().unwrap().is_some())
+}
+
+// None hands the screen back to the slideshow. Activating a set turns off the dynamic set.
+pub async fn activate_solar_set(app: &AppHandle<Wry>, id: Option<i64>) -> Result<Option<SolarSet>, String> {
+    let set = match id {
+        Some(id) => {
+            let set = with_library_db(app, |db| db.get_solar_set(id))?
+                .ok_or_else(|| format!("Solar set {} does not exist", id))?;
+            if variant_for_phase(&set.variants, SolarPhase::Day).is_none() {
+                return Err(format!("{} has no pictures yet", set.name));
+            }
+            Some(set)
+        }
+        None => None,
+    };
+
+    update_app_state(app, |state| {
+        state.active_solar_set = id;
+        if id.is_some() {
+            state.active_dynamic_set = None;
+        }
+    })
+    .await?;
+    if id.is_some() {
+        wake_dynamic(app);
+    }
+
+    // The schedule picks the new set up right away
+    forget_solar(app);
+    wake_schedule(app);
+    Ok(set)
+}
+
+pub async fn solar_status(app: &AppHandle<Wry>, id: i64) -> Result<SolarStatus, String> {
+    let set = with_library_db(app, |db| db.get_solar_set(id))?
+        .ok_or_else(|| format!("Solar set {} does not exist", id))?;
+    let now = unix_now();
+    let position = solar_position(now, set.latitude, set.longitude);
+    let phase = solar_phase(position.elevation);
+    let next = next_phase_change(now, set.latitude, set.longitude, STATUS_HORIZON_MINUTES);
+
+    Ok(SolarStatus {
+        position,
+        phase,
+        wallpaper: variant_for_phase(&set.variants, phase).cloned(),
+        next_change_at: next.map(|(at, _)| at),
+        next_phase: next.map(|(_, phase)| phase),
+    })
+}
\ No newline at end of file
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use crate::types::{PickWeight, SlideshowWeighting, UsageStats, WallpaperInfo};
+use super::order::random_u64;
+
+// A wallpaper keeps at least this share of its weight however recently it was shown, so a
+// collection that has all been shown lately still has something to pick from
+const MIN_RECENCY_FACTOR: f64 = 0.02;
+
+// Every candidate's chance of being picked next. The wallpaper on screen gets none unless it is
+// the only one.
+pub fn pick_weights(
+    candidates: &[WallpaperInfo],
+    usage: &HashMap<String, UsageStats>,
+    weighting: &SlideshowWeighting,
+    current: Option<&str>,
+    now: i64,
+) -> Vec<PickWeight> {
+    let usage_of = |info: &WallpaperInfo| usage.get(&info.path).copied().unwrap_or_default();
+    let average_shown = if candidates.is_empty() {
+        0.0
+    } else {
+        candidates.iter().map(|info| usage_of(info).times_shown as f64).sum::<f64>() / candidates.len() as f64
+    };
+    let recency_secs = weighting.recency_hours.max(0.0) * 3600.0;
+    let skip_current = |info: &WallpaperInfo| candidates.len() > 1 && current == Some(info.path.as_str());
+
+    let mut weights: Vec<PickWeight> = candidates.iter()
+        .map(|info| {
+            let usage = usage_of(info);
+            let stars = info.rating.map(|rating| rating.clamp(1, 5) as i32).unwrap_or(3);
+            let rating_factor = weighting.rating_factor.max(1.0).powi(stars - 3);
+            let favorite_factor = if info.favorite { weighting.favorite_factor.max(0.0) } else { 1.0 };
+            let recency_factor = match usage.last_shown_at {
+                Some(last_shown_at) if recency_secs > 0.0 => {
+                    ((now - last_shown_at) as f64 / recency_secs).clamp(MIN_RECENCY_FACTOR, 1.0)
+                }
+                _ => 1.0,
+            };
+            // Only showing up more often than the average counts against a wallpaper
+            let excess = if average_shown > 0.0 {
+                (usage.times_shown as f64 - average_shown).max(0.0) / average_shown
+            } else {
+                0.0
+            };
+            let frequency_factor = 1.0 / (1.0 + weighting.frequency_penalty.max(0.0) * excess);
+            let weight = if skip_current(info) {
+                0.0
+            } else {
+                rating_factor * favorite_factor * recency_factor * frequency_factor
+            };
+
+            PickWeight {
+                path: info.path.clone(),
+                name: info.name.clone(),
+                usage,
+                rating_factor,
+                favorite_factor,
+                recency_factor,
+                frequency_factor,
+                weight,
+                probability: 0.0,
+            }
+        })
+        .collect();
+
+    // With every weight at zero, e.g. a favorite factor of zero and only favorites left, fall
+    // back to an even chance
+    let total: f64 = weights.iter().map(|weight| weight.weight).sum();
+    let eligible = candidates.iter().filter(|info| !skip_current(info)).count();
+    for (weight, info) in weights.iter_mut().zip(candidates) {
+        weight.probability = if total > 0.0 {
+            weight.weight / total
+        } else if skip_current(info) {
+            0.0
+        } else {
+            1.0 / eligible as f64
+        };
+    }
+    weights
+}
+
+pub fn pick_weighted(weights: &[PickWeight]) -> Option<&PickWeight> {
+    let mut remaining = random_u64() as f64 / u64::MAX as f64;
+    let mut last = None;
+    for weight in weights.iter().filter(|weight| weight.probability > 0.0) {
+        if remaining < weight.probability {
+            return Some(weight);
+        }
+        remaining -= weight.probability;
+        last = Some(weight);
+    }
+    // Rounding can leave a sliver past the last one
+    last
+}
\ No newline at end of file
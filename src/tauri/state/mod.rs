@@ -1,8 +1,29 @@
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use crate::commands::wallpaper::PreloadedWindow;
+use crate::library::db::LibraryDb;
+use crate::library::duplicates::DuplicateCache;
+use crate::library::indexing::LibraryIndexer;
+use crate::library::scan::LibraryScan;
+use crate::library::watcher::LibraryWatcher;
+use crate::slideshow::Slideshow;
+use crate::slideshow::dynamic::Dynamic;
+use crate::slideshow::schedule::Schedule;
 
 #[derive(Default)]
 pub struct AppState {
+    // Wallpaper window labels by monitor name, or ALL_MONITORS for the one covering every screen
     pub video_windows: Mutex<HashMap<String, String>>,
+    pub preloaded_window: Mutex<Option<PreloadedWindow>>,
     pub date_widgets: Mutex<HashMap<String, String>>,
+    pub library_watcher: Mutex<Option<LibraryWatcher>>,
+    pub library_scans: Mutex<HashMap<String, Arc<LibraryScan>>>,
+    pub library_db: Mutex<Option<LibraryDb>>,
+    pub duplicate_groups: Mutex<Option<DuplicateCache>>,
+    pub library_indexer: LibraryIndexer,
+    // Held while the persisted state is read, changed and written back
+    pub app_state_lock: tokio::sync::Mutex<()>,
+    pub slideshow: Slideshow,
+    pub schedule: Schedule,
+    pub dynamic: Dynamic,
 }
\ No newline at end of file
@@ -1,23 +1,32 @@
+use chrono::{Local, TimeZone};
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     Manager, AppHandle, Wry,
 };
 use crate::state::AppState;
-use crate::commands::stop_video_wallpaper;
+use crate::commands::{load_app_state, stop_video_wallpaper};
 use crate::commands::date_widget::center_to_position;
-use crate::types::DateWidgetSettings;
+use crate::library::collections::activate_collection;
+use crate::library::db::with_library_db;
+use crate::slideshow::{next_wallpaper, previous_wallpaper, reapply_from_history, wallpaper_history};
+use crate::types::{Collection, DateWidgetSettings, WallpaperHistory};
+
+const TRAY_ID: &str = "main";
+
+// Collection entries are "collection:<id>", plus "collection:all" for the whole library
+const COLLECTION_MENU_PREFIX: &str = "collection:";
+
+// History entries are "history:<applied_at>:<path>", newest first. Their place in the history
+// shifts as wallpapers are added, so it would pick the wrong one from a menu built before.
+const HISTORY_MENU_PREFIX: &str = "history:";
+const HISTORY_MENU_ITEMS: usize = 10;
 
 pub fn create_tray_menu(app: &tauri::App) -> tauri::Result<()> {
-    // Create tray menu
-    let show = MenuItem::with_id(app, "show", "Show Settings", true, None::<&str>)?;
-    let hide = MenuItem::with_id(app, "hide", "Hide Settings", true, None::<&str>)?;
-    let stop_video = MenuItem::with_id(app, "stop_video", "Stop Video Wallpaper", true, None::<&str>)?;
-    let date_widget = MenuItem::with_id(app, "date_widget", "Toggle Date Widget", true, None::<&str>)?;
-    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&show, &hide, &stop_video, &date_widget, &quit])?;
+    // Collections are filled in by refresh_tray_menu once the library is open
+    let menu = build_tray_menu(app, &[], None, &WallpaperHistory::default(), false)?;
     // Create tray icon with event handling
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
         .icon(app.default_window_icon().unwrap().clone())
         .tooltip("Wallora")
@@ -32,6 +41,111 @@ pub fn create_tray_menu(app: &tauri::App) -> tauri::Result<()> {
     Ok(())
 }
 
+// Rebuild the menu so the collections and history submenus match the library, the active
+// collection and the wallpapers shown lately
+pub async fn refresh_tray_menu(app: &AppHandle<Wry>) {
+    let state = load_app_state(app.clone()).await.ok();
+    let active = state.as_ref().and_then(|state| state.active_collection);
+    let independent = state.is_some_and(|state| state.monitor_slideshows.independent);
+    let collections = with_library_db(app, |db| db.list_collections()).unwrap_or_default();
+
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_tray_menu(app, &collections, active, &wallpaper_history(app), independent) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(_e) => {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to rebuild tray menu: {}", _e);
+        }
+    }
+}
+
+fn build_tray_menu<M: Manager<Wry>>(
+    manager: &M,
+    collections: &[Collection],
+    active: Option<i64>,
+    history: &WallpaperHistory,
+    independent: bool,
+) -> tauri::Result<Menu<Wry>> {
+    let show = MenuItem::with_id(manager, "show", "Show Settings", true, None::<&str>)?;
+    let hide = MenuItem::with_id(manager, "hide", "Hide Settings", true, None::<&str>)?;
+    let stop_video = MenuItem::with_id(manager, "stop_video", "Stop Video Wallpaper", true, None::<&str>)?;
+    let date_widget = MenuItem::with_id(manager, "date_widget", "Toggle Date Widget", true, None::<&str>)?;
+    let quit = MenuItem::with_id(manager, "quit", "Quit", true, None::<&str>)?;
+
+    // A deleted active collection falls back to the whole library
+    let active = active.filter(|id| collections.iter().any(|collection| collection.id == *id));
+    let mut collection_items = vec![CheckMenuItem::with_id(
+        manager,
+        format!("{}all", COLLECTION_MENU_PREFIX),
+        "All Wallpapers",
+        true,
+        active.is_none(),
+        None::<&str>,
+    )?];
+    for collection in collections {
+        collection_items.push(CheckMenuItem::with_id(
+            manager,
+            format!("{}{}", COLLECTION_MENU_PREFIX, collection.id),
+            &collection.name,
+            true,
+            active == Some(collection.id),
+            None::<&str>,
+        )?);
+    }
+    let collection_refs: Vec<&dyn IsMenuItem<Wry>> = collection_items.iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let collections_menu = Submenu::with_items(manager, "Collections", true, &collection_refs)?;
+
+    // Independent screens have no shared history to go back through; next moves each of them on
+    let can_go_back = history.position > 0 && !independent;
+    let previous = MenuItem::with_id(manager, "previous_wallpaper", "Previous Wallpaper", can_go_back, None::<&str>)?;
+    let next = MenuItem::with_id(manager, "next_wallpaper", "Next Wallpaper", true, None::<&str>)?;
+    let mut history_items = Vec::new();
+    for (index, entry) in history.entries.iter().enumerate().rev().take(HISTORY_MENU_ITEMS) {
+        let name = std::path::Path::new(&entry.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| entry.path.clone());
+        let label = match Local.timestamp_opt(entry.applied_at, 0).single() {
+            Some(applied_at) => format!("{} ({})", name, applied_at.format("%b %-d, %H:%M")),
+            None => name,
+        };
+        history_items.push(CheckMenuItem::with_id(
+            manager,
+            format!("{}{}:{}", HISTORY_MENU_PREFIX, entry.applied_at, entry.path),
+            label,
+            true,
+            index == history.position,
+            None::<&str>,
+        )?);
+    }
+    let history_refs: Vec<&dyn IsMenuItem<Wry>> = history_items.iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let history_menu = Submenu::with_items(manager, "Recent Wallpapers", !history_items.is_empty(), &history_refs)?;
+    let navigation_separator = PredefinedMenuItem::separator(manager)?;
+    let controls_separator = PredefinedMenuItem::separator(manager)?;
+
+    Menu::with_items(manager, &[
+        &show,
+        &hide,
+        &navigation_separator,
+        &previous,
+        &next,
+        &history_menu,
+        &collections_menu,
+        &controls_separator,
+        &stop_video,
+        &date_widget,
+        &quit,
+    ])
+}
+
 fn handle_tray_menu_event(app: &AppHandle<Wry>, event_id: &str) {
     match event_id {
         "show" => {
@@ -80,10 +194,47 @@ fn handle_tray_menu_event(app: &AppHandle<Wry>, event_id: &str) {
                 }
             });
         }
+        "previous_wallpaper" => {
+            let app_clone = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = previous_wallpaper(&app_clone).await;
+            });
+        }
+        "next_wallpaper" => {
+            let app_clone = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = next_wallpaper(&app_clone).await;
+            });
+        }
         "quit" => {
             app.exit(0);
         }
-        _ => {}
+        other => {
+            let history_entry = other.strip_prefix(HISTORY_MENU_PREFIX)
+                .and_then(|entry| entry.split_once(':'))
+                .and_then(|(applied_at, path)| Some((applied_at.parse::<i64>().ok()?, path.to_string())));
+            if let Some((applied_at, path)) = history_entry {
+                let app_clone = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if reapply_from_history(&app_clone, applied_at, &path).await.is_err() {
+                        // The entry or its file is gone; put the check marks back
+                        refresh_tray_menu(&app_clone).await;
+                    }
+                });
+                return;
+            }
+            if let Some(collection) = other.strip_prefix(COLLECTION_MENU_PREFIX) {
+                // "all" does not parse, which switches back to the whole library
+                let collection_id = collection.parse::<i64>().ok();
+                let app_clone = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if activate_collection(app_clone.clone(), collection_id).await.is_err() {
+                        // Put the check marks back in line with the saved state
+                        refresh_tray_menu(&app_clone).await;
+                    }
+                });
+            }
+        }
     }
 }
 
@@ -1,11 +1,92 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct WallpaperInfo {
     pub path: String,
     pub name: String,
     pub file_type: String,
     pub size: u64,
+    // Seconds since the Unix epoch
+    #[serde(default)]
+    pub modified_at: Option<i64>,
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub media_kind: MediaKind,
+    #[serde(default = "default_is_valid")]
+    pub is_valid: bool,
+    #[serde(default)]
+    pub invalid_reason: Option<String>,
+    // The file was in the library but can no longer be found on disk
+    #[serde(default)]
+    pub is_missing: bool,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub orientation: Option<u16>,
+    #[serde(default)]
+    pub captured_at: Option<String>,
+    #[serde(default)]
+    pub camera_model: Option<String>,
+    #[serde(default)]
+    pub gps_location: Option<GpsLocation>,
+    #[serde(default)]
+    pub video: Option<VideoMetadata>,
+    // Set by the user and stored in the library database, not read from the file
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub favorite: bool,
+    // 1 to 5 stars
+    #[serde(default)]
+    pub rating: Option<u8>,
+    // Never shown by the slideshow
+    #[serde(default)]
+    pub blocked: bool,
+    // Dominant colors as #rrggbb, most common first; filled in by background indexing
+    #[serde(default)]
+    pub palette: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VideoMetadata {
+    pub duration_secs: Option<f64>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub frame_rate: Option<f64>,
+    pub codec: Option<String>,
+    pub has_audio: bool,
+    pub frame_count: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct GpsLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WallpaperSort {
+    #[default]
+    Name,
+    CaptureDate,
+}
+
+// Entries saved before content sniffing existed were only ever added when supported
+fn default_is_valid() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    #[default]
+    Static,
+    Animated,
+    Video,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,14 +111,585 @@ pub struct AppPersistentState {
     pub last_wallpaper_file_type: Option<String>,
     pub date_widget_settings: Option<DateWidgetSettings>,
     pub wallpaper_settings: Option<WallpaperSettings>,
+    // Only read once, to migrate into the library database
+    #[serde(default)]
     pub wallpaper_list: Vec<WallpaperInfo>,
     pub autostart_enabled: bool,
+    #[serde(default)]
+    pub library_folders: Vec<LibraryFolder>,
+    // The slideshow picks only from this collection while it is set
+    #[serde(default)]
+    pub active_collection: Option<i64>,
+    #[serde(default)]
+    pub slideshow_cursor: SlideshowCursor,
+    // Rules are checked in order; the first time range in effect wins
+    #[serde(default)]
+    pub schedule: Vec<ScheduleRule>,
+    // Shown instead of the slideshow, following the sun, unless a time range says otherwise
+    #[serde(default)]
+    pub active_solar_set: Option<i64>,
+    // Like a solar set, but timed by keyframes; only one of the two is active at a time
+    #[serde(default)]
+    pub active_dynamic_set: Option<i64>,
+    #[serde(default)]
+    pub wallpaper_history: WallpaperHistory,
+    #[serde(default)]
+    pub monitor_slideshows: MonitorSlideshows,
+}
+
+// Where the slideshow rotation is, so it resumes from the same place after a restart
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SlideshowCursor {
+    pub collection_id: Option<i64>,
+    pub shuffled: bool,
+    // The last wallpaper the rotation picked and its index in the order
+    pub last_path: Option<String>,
+    pub position: usize,
+    // Shuffle mode only: the current pass, with entries before `position` already shown. Kept in
+    // the library database's rotation table rather than the settings file.
+    #[serde(skip)]
+    pub order: Vec<String>,
+}
+
+// Applied wallpapers, oldest first. The entry at `position` is on screen; stepping back and
+// forth moves it, and anything newly applied is added at the end.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WallpaperHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub position: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    pub path: String,
+    // Unix seconds when it was first shown
+    pub applied_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LibraryFolder {
+    pub path: String,
+    pub scan_options: ScanOptions,
+    #[serde(default)]
+    pub watched: bool,
+}
+
+// Payload of the `library-changed` event
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LibraryChange {
+    pub added: Vec<WallpaperInfo>,
+    pub updated: Vec<WallpaperInfo>,
+    pub removed: Vec<String>,
+}
+
+impl LibraryChange {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.removed.is_empty()
+    }
+}
+
+// A bulk edit of user-set attributes; fields that are left out stay as they are
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WallpaperAttributeUpdate {
+    pub favorite: Option<bool>,
+    // 1 to 5 stars, or 0 to clear the rating
+    pub rating: Option<u8>,
+    pub blocked: Option<bool>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagCount {
+    pub name: String,
+    pub count: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub item_count: usize,
+    // Used instead of the global slideshow settings while the collection is active
+    pub settings: Option<WallpaperSettings>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionImport {
+    pub collection: Collection,
+    // Entries from the file that are missing on disk or not a usable wallpaper
+    pub skipped: Vec<String>,
+}
+
+// What a GNOME background file turned into
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GnomeImport {
+    pub dynamic_sets: Vec<DynamicSet>,
+    // Plain pictures from a background list, gathered in one collection
+    pub collection: Option<Collection>,
+    // Files that were missing or could not be read
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelinkedWallpaper {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReconcileReport {
+    pub checked: usize,
+    pub relinked: Vec<RelinkedWallpaper>,
+    // Entries that are still missing after trying to relink them
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKind {
+    // Byte-for-byte identical files
+    Exact,
+    // The same picture at a different size, format or quality
+    Similar,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateItem {
+    pub wallpaper: WallpaperInfo,
+    // 1.0 for an exact copy of the best item, lower the more it differs
+    pub similarity: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateGroup {
+    pub kind: DuplicateKind,
+    // Path of the highest resolution item, the one a merge keeps
+    pub best: String,
+    // Lowest similarity of any item to the best one
+    pub similarity: f64,
+    pub items: Vec<DuplicateItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanStatus {
+    Running,
+    Completed,
+    Cancelled,
+}
+
+// Payload of the `library-scan-progress` event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanProgress {
+    pub scan_id: String,
+    pub discovered: usize,
+    pub processed: usize,
+    pub matched: usize,
+    pub status: ScanStatus,
+}
+
+// Payload of the `library-scan-batch` event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanBatch {
+    pub scan_id: String,
+    pub wallpapers: Vec<WallpaperInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageOrientation {
+    Landscape,
+    Portrait,
+    Square,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LibrarySort {
+    // Library order, which is the order wallpapers were added in unless rearranged
+    #[default]
+    Added,
+    Name,
+    Modified,
+    CaptureDate,
+    Size,
+    Rating,
+    Usage,
+}
+
+// Filters are combined; fields left unset do not filter anything
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WallpaperQuery {
+    // Case-insensitive part of the file name
+    pub name: Option<String>,
+    pub kinds: Vec<MediaKind>,
+    // Wallpapers with any of these tags
+    pub tags: Vec<String>,
+    pub favorites_only: bool,
+    pub min_rating: Option<u8>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub min_width: Option<u32>,
+    pub max_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub max_height: Option<u32>,
+    // Width divided by height; sizes are taken as displayed, after EXIF rotation
+    pub min_aspect_ratio: Option<f64>,
+    pub max_aspect_ratio: Option<f64>,
+    pub orientation: Option<ImageOrientation>,
+    // Inclusive YYYY-MM-DD bounds on the capture date, or the modified date when there is none
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    // A #rrggbb color that one of the dominant colors must be close to
+    pub color: Option<String>,
+    // Largest Lab difference still counted as close; defaults to a moderate tolerance
+    pub color_distance: Option<f64>,
+    // Hue range in degrees that one of the dominant colors must fall in; wraps past 360 when min > max
+    pub min_hue: Option<f64>,
+    pub max_hue: Option<f64>,
+    // At least the resolution of the primary monitor, which wallpapers are sized for, at about its aspect ratio
+    pub fits_monitor: bool,
+    pub sort_by: LibrarySort,
+    pub descending: bool,
+    pub offset: usize,
+    // None returns everything after `offset`
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WallpaperQueryPage {
+    pub items: Vec<WallpaperInfo>,
+    pub offset: usize,
+    // Matches across all pages
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WallpaperPage {
+    pub items: Vec<WallpaperInfo>,
+    pub offset: usize,
+    pub total: usize,
+    pub finished: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    // Ignore symlinked files and directories entirely
+    Skip,
+    // Pick up symlinked files but never descend into symlinked directories
+    #[default]
+    FilesOnly,
+    // Follow symlinked directories too; loops are detected and skipped
+    Follow,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ScanOptions {
+    // None scans without a depth limit
+    pub max_depth: Option<usize>,
+    pub symlink_policy: SymlinkPolicy,
+    pub include_patterns: Vec<String>,
+    pub exclude_patterns: Vec<String>,
+    pub include_hidden: bool,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub min_width: Option<u32>,
+    pub max_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub max_height: Option<u32>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(2),
+            symlink_policy: SymlinkPolicy::default(),
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            include_hidden: false,
+            min_size: None,
+            max_size: None,
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+        }
+    }
+}
+
+// Field names match the frontend's settings object
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct WallpaperSettings {
     pub auto_change: bool,
     pub interval: u32,
     pub random_order: bool,
     pub pause_on_fullscreen: bool,
+    // Show only the best copy of each group of near-duplicates in the slideshow
+    #[serde(default)]
+    pub collapse_duplicates: bool,
+    #[serde(default)]
+    pub filter: SlideshowFilter,
+    #[serde(default)]
+    pub weighting: SlideshowWeighting,
+}
+
+// Same defaults the settings page starts with
+impl Default for WallpaperSettings {
+    fn default() -> Self {
+        Self {
+            auto_change: false,
+            interval: 30,
+            random_order: false,
+            pause_on_fullscreen: true,
+            collapse_duplicates: false,
+            filter: SlideshowFilter::default(),
+            weighting: SlideshowWeighting::default(),
+        }
+    }
+}
+
+// Narrows down what the slideshow picks from; blocked wallpapers are always skipped
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SlideshowFilter {
+    // Wallpapers with any of these tags; empty means tags are not considered
+    pub tags: Vec<String>,
+    pub favorites_only: bool,
+    pub min_rating: Option<u8>,
+    // Same meaning as the color fields of WallpaperQuery
+    pub color: Option<String>,
+    pub color_distance: Option<f64>,
+    pub min_hue: Option<f64>,
+    pub max_hue: Option<f64>,
+}
+
+// How shuffle mode weighs its picks while enabled; otherwise every candidate is as likely
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SlideshowWeighting {
+    pub enabled: bool,
+    // Each star above three multiplies the weight by this, each star below divides by it;
+    // unrated wallpapers count as three stars
+    pub rating_factor: f64,
+    pub favorite_factor: f64,
+    // A wallpaper that was just shown starts back near zero and regains its full weight
+    // evenly over this many hours
+    pub recency_hours: f64,
+    // Shown twice as often as the average, a wallpaper's weight is divided by one plus this
+    pub frequency_penalty: f64,
+}
+
+impl Default for SlideshowWeighting {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rating_factor: 1.5,
+            favorite_factor: 2.0,
+            recency_hours: 24.0,
+            frequency_penalty: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+pub struct UsageStats {
+    pub times_shown: u32,
+    pub last_shown_at: Option<i64>,
+}
+
+// A candidate's chance of being picked next by weighted shuffle, and what went into it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PickWeight {
+    pub path: String,
+    pub name: String,
+    pub usage: UsageStats,
+    pub rating_factor: f64,
+    pub favorite_factor: f64,
+    pub recency_factor: f64,
+    pub frequency_factor: f64,
+    pub weight: f64,
+    pub probability: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SlideshowStatus {
+    // Auto-change is on in the settings in effect
+    pub enabled: bool,
+    pub interval: u32,
+    pub collection_id: Option<i64>,
+    pub current: Option<String>,
+    // Unix seconds; None while the slideshow is not scheduled
+    pub next_change_at: Option<i64>,
+    pub can_go_back: bool,
+}
+
+// How the slideshow covers several screens. Off, every screen shares the one rotation the
+// slideshow settings describe; on, each screen runs its own.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MonitorSlideshows {
+    pub independent: bool,
+    // A screen gets an entry, copied from the shared slideshow, the first time it runs its own
+    pub monitors: Vec<MonitorSlideshow>,
+}
+
+// One screen's own slideshow, found by the name the system gives the monitor
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MonitorSlideshow {
+    pub monitor: String,
+    // None picks from the whole library
+    pub collection_id: Option<i64>,
+    pub settings: WallpaperSettings,
+    #[serde(default)]
+    pub cursor: SlideshowCursor,
+    // Put back on the screen after a restart
+    #[serde(default)]
+    pub current: Option<String>,
+}
+
+// A connected screen, in physical pixels, and what its own slideshow shows
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MonitorInfo {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub primary: bool,
+    pub current: Option<String>,
+    // Unix seconds; None unless the screen runs its own slideshow with auto-change on
+    pub next_change_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScheduleRule {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub when: ScheduleTime,
+    pub target: ScheduleTarget,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleTime {
+    // Local "HH:MM" times; an end before the start runs past midnight. Days are 0 (Sunday)
+    // to 6 and count from the day the range starts; empty means every day.
+    Range {
+        start: String,
+        end: String,
+        #[serde(default)]
+        days: Vec<u8>,
+    },
+    // Five-field cron expression in local time; the target is applied each time it fires
+    Cron { expression: String },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleTarget {
+    Wallpaper { path: String },
+    // Rotate through a collection instead of the active one
+    Collection { id: i64 },
+    // Move the slideshow on; only meaningful for cron rules
+    Next,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SchedulePreview {
+    // The rule deciding what shows; None when the regular slideshow runs
+    pub rule: Option<ScheduleRule>,
+    pub collection: Option<Collection>,
+    pub wallpaper: Option<WallpaperInfo>,
+    // Unix seconds of the next time the schedule changes anything, within a week
+    pub next_change_at: Option<i64>,
+}
+
+// Where the sun is, in degrees: elevation above the horizon and azimuth clockwise from north
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct SolarPosition {
+    pub elevation: f64,
+    pub azimuth: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SolarPhase {
+    Day,
+    GoldenHour,
+    // Twilight both before sunrise and after sunset
+    Dusk,
+    Night,
+}
+
+// A picture per phase of the day; phases without one borrow the nearest phase's picture
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SolarVariants {
+    pub day: Option<String>,
+    pub golden_hour: Option<String>,
+    pub dusk: Option<String>,
+    pub night: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SolarSet {
+    pub id: i64,
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    // City or place name the coordinates came from, for display only
+    #[serde(default)]
+    pub location: Option<String>,
+    #[serde(default)]
+    pub variants: SolarVariants,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SolarStatus {
+    pub position: SolarPosition,
+    pub phase: SolarPhase,
+    // The picture shown for this phase
+    pub wallpaper: Option<String>,
+    // Unix seconds of the next phase change and the phase it leads to, within two days
+    pub next_change_at: Option<i64>,
+    pub next_phase: Option<SolarPhase>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct City {
+    pub name: String,
+    pub country: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+// One picture in a dynamic set, shown from its keyframe until the next one's
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DynamicFrame {
+    pub path: String,
+    // Seconds into the cycle where the picture is fully shown
+    pub at: u32,
+    // Seconds of crossfade from the previous picture, ending at `at`
+    #[serde(default)]
+    pub transition: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DynamicSet {
+    pub id: i64,
+    pub name: String,
+    // Cycle length in seconds, counted from local midnight on 1970-01-01, so a day-long cycle
+    // keys the frames to the time of day
+    pub period: u32,
+    // In keyframe order
+    pub frames: Vec<DynamicFrame>,
 }
\ No newline at end of file
@@ -0,0 +1,133 @@
+use crate::types::City;
+
+// How many matches a search returns
+const MAX_RESULTS: usize = 20;
+
+// Larger cities across every time zone, so solar sets can be placed without a network lookup.
+// Name, country, latitude, longitude.
+const CITIES: &[(&str, &str, f64, f64)] = &[
+    ("Abu Dhabi", "United Arab Emirates", 24.4539, 54.3773),
+    ("Accra", "Ghana", 5.6037, -0.1870),
+    ("Addis Ababa", "Ethiopia", 9.0320, 38.7469),
+    ("Adelaide", "Australia", -34.9285, 138.6007),
+    ("Amsterdam", "Netherlands", 52.3676, 4.9041),
+    ("Anchorage", "United States", 61.2181, -149.9003),
+    ("Athens", "Greece", 37.9838, 23.7275),
+    ("Atlanta", "United States", 33.7490, -84.3880),
+    ("Auckland", "New Zealand", -36.8485, 174.7633),
+    ("Bangkok", "Thailand", 13.7563, 100.5018),
+    ("Barcelona", "Spain", 41.3874, 2.1686),
+    ("Beijing", "China", 39.9042, 116.4074),
+    ("Berlin", "Germany", 52.5200, 13.4050),
+    ("Bogotá", "Colombia", 4.7110, -74.0721),
+    ("Boston", "United States", 42.3601, -71.0589),
+    ("Brisbane", "Australia", -27.4698, 153.0251),
+    ("Brussels", "Belgium", 50.8503, 4.3517),
+    ("Bucharest", "Romania", 44.4268, 26.1025),
+    ("Budapest", "Hungary", 47.4979, 19.0402),
+    ("Buenos Aires", "Argentina", -34.6037, -58.3816),
+    ("Cairo", "Egypt", 30.0444, 31.2357),
+    ("Calgary", "Canada", 51.0447, -114.0719),
+    ("Cape Town", "South Africa", -33.9249, 18.4241),
+    ("Caracas", "Venezuela", 10.4806, -66.9036),
+    ("Casablanca", "Morocco", 33.5731, -7.5898),
+    ("Chicago", "United States", 41.8781, -87.6298),
+    ("Copenhagen", "Denmark", 55.6761, 12.5683),
+    ("Dakar", "Senegal", 14.7167, -17.4677),
+    ("Dallas", "United States", 32.7767, -96.7970),
+    ("Delhi", "India", 28.7041, 77.1025),
+    ("Denver", "United States", 39.7392, -104.9903),
+    ("Dhaka", "Bangladesh", 23.8103, 90.4125),
+    ("Dubai", "United Arab Emirates", 25.2048, 55.2708),
+    ("Dublin", "Ireland", 53.3498, -6.2603),
+    ("Edinburgh", "United Kingdom", 55.9533, -3.1883),
+    ("Frankfurt", "Germany", 50.1109, 8.6821),
+    ("Geneva", "Switzerland", 46.2044, 6.1432),
+    ("Guangzhou", "China", 23.1291, 113.2644),
+    ("Hamburg", "Germany", 53.5511, 9.9937),
+    ("Hanoi", "Vietnam", 21.0278, 105.8342),
+    ("Havana", "Cuba", 23.1136, -82.3666),
+    ("Helsinki", "Finland", 60.1699, 24.9384),
+    ("Ho Chi Minh City", "Vietnam", 10.8231, 106.6297),
+    ("Hong Kong", "China", 22.3193, 114.1694),
+    ("Honolulu", "United States", 21.3069, -157.8583),
+    ("Houston", "United States", 29.7604, -95.3698),
+    ("Istanbul", "Turkey", 41.0082, 28.9784),
+    ("Jakarta", "Indonesia", -6.2088, 106.8456),
+    ("Jerusalem", "Israel", 31.7683, 35.2137),
+    ("Johannesburg", "South Africa", -26.2041, 28.0473),
+    ("Karachi", "Pakistan", 24.8607, 67.0011),
+    ("Kathmandu", "Nepal", 27.7172, 85.3240),
+    ("Kolkata", "India", 22.5726, 88.3639),
+    ("Kuala Lumpur", "Malaysia", 3.1390, 101.6869),
+    ("Kyiv", "Ukraine", 50.4501, 30.5234),
+    ("Lagos", "Nigeria", 6.5244, 3.3792),
+    ("Lima", "Peru", -12.0464, -77.0428),
+    ("Lisbon", "Portugal", 38.7223, -9.1393),
+    ("London", "United Kingdom", 51.5074, -0.1278),
+    ("Los Angeles", "United States", 34.0522, -118.2437),
+    ("Madrid", "Spain", 40.4168, -3.7038),
+    ("Manila", "Philippines", 14.5995, 120.9842),
+    ("Melbourne", "Australia", -37.8136, 144.9631),
+    ("Mexico City", "Mexico", 19.4326, -99.1332),
+    ("Miami", "United States", 25.7617, -80.1918),
+    ("Milan", "Italy", 45.4642, 9.1900),
+    ("Montevideo", "Uruguay", -34.9011, -56.1645),
+    ("Montreal", "Canada", 45.5017, -73.5673),
+    ("Moscow", "Russia", 55.7558, 37.6173),
+    ("Mumbai", "India", 19.0760, 72.8777),
+    ("Munich", "Germany", 48.1351, 11.5820),
+    ("Nairobi", "Kenya", -1.2921, 36.8219),
+    ("New York", "United States", 40.7128, -74.0060),
+    ("Osaka", "Japan", 34.6937, 135.5023),
+    ("Oslo", "Norway", 59.9139, 10.7522),
+    ("Ottawa", "Canada", 45.4215, -75.6972),
+    ("Paris", "France", 48.8566, 2.3522),
+    ("Perth", "Australia", -31.9505, 115.8605),
+    ("Philadelphia", "United States", 39.9526, -75.1652),
+    ("Phoenix", "United States", 33.4484, -112.0740),
+    ("Prague", "Czechia", 50.0755, 14.4378),
+    ("Reykjavík", "Iceland", 64.1466, -21.9426),
+    ("Rio de Janeiro", "Brazil", -22.9068, -43.1729),
+    ("Riyadh", "Saudi Arabia", 24.7136, 46.6753),
+    ("Rome", "Italy", 41.9028, 12.4964),
+    ("San Francisco", "United States", 37.7749, -122.4194),
+    ("Santiago", "Chile", -33.4489, -70.6693),
+    ("São Paulo", "Brazil", -23.5505, -46.6333),
+    ("Seattle", "United States", 47.6062, -122.3321),
+    ("Seoul", "South Korea", 37.5665, 126.9780),
+    ("Shanghai", "China", 31.2304, 121.4737),
+    ("Singapore", "Singapore", 1.3521, 103.8198),
+    ("Stockholm", "Sweden", 59.3293, 18.0686),
+    ("Sydney", "Australia", -33.8688, 151.2093),
+    ("Taipei", "Taiwan", 25.0330, 121.5654),
+    ("Tehran", "Iran", 35.6892, 51.3890),
+    ("Tokyo", "Japan", 35.6762, 139.6503),
+    ("Toronto", "Canada", 43.6532, -79.3832),
+    ("Vancouver", "Canada", 49.2827, -123.1207),
+    ("Vienna", "Austria", 48.2082, 16.3738),
+    ("Warsaw", "Poland", 52.2297, 21.0122),
+    ("Wellington", "New Zealand", -41.2865, 174.7762),
+    ("Zurich", "Switzerland", 47.3769, 8.5417),
+];
+
+// Cities whose name or country contains the query, names starting with it first
+pub fn search_cities(query: &str) -> Vec<City> {
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<_> = CITIES.iter()
+        .filter(|(name, country, _, _)| {
+            name.to_lowercase().contains(&query) || country.to_lowercase().contains(&query)
+        })
+        .collect();
+    matches.sort_by_key(|(name, _, _, _)| !name.to_lowercase().starts_with(&query));
+
+    matches.into_iter()
+        .take(MAX_RESULTS)
+        .map(|&(name, country, latitude, longitude)| City {
+            name: name.to_string(),
+            country: country.to_string(),
+            latitude,
+            longitude,
+        })
+        .collect()
+}
\ No newline at end of file
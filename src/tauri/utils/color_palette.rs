@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::path::Path;
+use image::ImageReader;
+
+// How many colors are kept per image
+const PALETTE_SIZE: usize = 5;
+
+// Histogram buckets closer than this in Lab are folded into one palette color
+const MERGE_DISTANCE: f64 = 12.0;
+
+// Below this saturation or brightness a color is a gray and has no meaningful hue
+const MIN_HUE_SATURATION: f64 = 0.2;
+const MIN_HUE_VALUE: f64 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PaletteColor {
+    pub rgb: [u8; 3],
+    // Share of the image's pixels, 0.0 to 1.0
+    pub weight: f64,
+}
+
+// The most common colors of an image, most dominant first. Meant to be run on a small thumbnail.
+pub fn dominant_colors(path: &Path) -> Result<Vec<PaletteColor>, String> {
+    let image = ImageReader::open(path)
+        .map_err(|e| format!("Failed to open image: {}", e))?
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .decode()
+        .map_err(|e| format!("Failed to decode image: {}", e))?
+        .to_rgb8();
+
+    // 4 bits per channel keeps the histogram small while still separating visibly different colors
+    let mut buckets: HashMap<u16, (u64, [u64; 3])> = HashMap::new();
+    for pixel in image.pixels() {
+        let [r, g, b] = pixel.0;
+        let key = ((r as u16 >> 4) << 8) | ((g as u16 >> 4) << 4) | (b as u16 >> 4);
+        let (count, sums) = buckets.entry(key).or_insert((0, [0; 3]));
+        *count += 1;
+        for (sum, channel) in sums.iter_mut().zip([r, g, b]) {
+            *sum += channel as u64;
+        }
+    }
+
+    let total: u64 = buckets.values().map(|(count, _)| count).sum();
+    if total == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut averages: Vec<(u64, [u8; 3])> = buckets.into_values()
+        .map(|(count, sums)| (count, sums.map(|sum| (sum / count) as u8)))
+        .collect();
+    averages.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    // Each palette entry keeps the color of its most common bucket and collects the counts of similar ones
+    let mut palette: Vec<(u64, [u8; 3], [f64; 3])> = Vec::new();
+    for (count, rgb) in averages {
+        let lab = rgb_to_lab(rgb);
+        match palette.iter_mut().find(|(_, _, entry_lab)| delta_e(*entry_lab, lab) < MERGE_DISTANCE) {
+            Some((entry_count, _, _)) => *entry_count += count,
+            None => palette.push((count, rgb, lab)),
+        }
+    }
+    palette.sort_by_key(|entry| std::cmp::Reverse(entry.0));
+
+    Ok(palette.into_iter()
+        .take(PALETTE_SIZE)
+        .map(|(count, rgb, _)| PaletteColor {
+            rgb,
+            weight: count as f64 / total as f64,
+        })
+        .collect())
+}
+
+// CIE L*a*b* under a D65 white point, where equal distances look roughly equally different
+pub fn rgb_to_lab(rgb: [u8; 3]) -> [f64; 3] {
+    let [r, g, b] = rgb.map(|channel| {
+        let value = channel as f64 / 255.0;
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    });
+
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+
+    let f = |t: f64| {
+        if t > 216.0 / 24389.0 {
+            t.cbrt()
+        } else {
+            (24389.0 / 27.0 * t + 16.0) / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+// CIE76 color difference; around 2 is barely noticeable, above 20 clearly a different color
+pub fn delta_e(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum::<f64>().sqrt()
+}
+
+// HSV hue in degrees, or None for grays, black and white
+pub fn hue_degrees(rgb: [u8; 3]) -> Option<f64> {
+    let [r, g, b] = rgb.map(|channel| channel as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+
+    if max < MIN_HUE_VALUE || chroma / max < MIN_HUE_SATURATION {
+        return None;
+    }
+
+    let sector = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    Some(sector * 60.0)
+}
+
+// Accepts "#008080" or "008080"
+pub fn parse_hex_color(text: &str) -> Result<[u8; 3], String> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Not a color: {}", text));
+    }
+
+    let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).unwrap_or(0);
+    Ok([channel(0), channel(2), channel(4)])
+}
\ No newline at end of file
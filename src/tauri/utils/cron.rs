@@ -0,0 +1,207 @@
+use chrono::{Datelike, NaiveDateTime, Timelike};
+
+const MONTH_NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+// A standard five-field cron expression: minute, hour, day of month, month, day of week.
+// Fields take `*`, numbers, ranges, steps and lists (`*/15`, `9-17`, `1,15`, `mon-fri`),
+// and the @hourly, @daily, @weekly, @monthly and @yearly shorthands are accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    // Like cron, when both day fields are restricted a time matches if either one does
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("Expected 5 fields in cron expression, found {}", fields.len()));
+        };
+
+        // 7 is also Sunday
+        let weekdays = parse_field(weekday, 0, 7, &WEEKDAY_NAMES, 0)?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], 0)?,
+            hours: parse_field(hour, 0, 23, &[], 0)? as u32,
+            days: parse_field(day, 1, 31, &[], 1)? as u32,
+            months: parse_field(month, 1, 12, &MONTH_NAMES, 1)? as u16,
+            weekdays: ((weekdays | (weekdays >> 7)) & 0x7f) as u8,
+            // `*/2` restricts the values but still counts as unrestricted here, as in cron
+            any_day: day.starts_with('*'),
+            any_weekday: weekday.starts_with('*'),
+        })
+    }
+
+    // Whether the schedule fires in the minute containing this local time
+    pub fn matches(&self, time: &NaiveDateTime) -> bool {
+        let day_matches = self.days & (1 << time.day()) != 0;
+        let weekday_matches = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+        let day_ok = match (self.any_day, self.any_weekday) {
+            (false, false) => day_matches || weekday_matches,
+            _ => day_matches && weekday_matches,
+        };
+
+        self.minutes & (1 << time.minute()) != 0
+            && self.hours & (1 << time.hour()) != 0
+            && self.months & (1 << time.month()) != 0
+            && day_ok
+    }
+}
+
+// Parse one field into a bit set of allowed values
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], name_offset: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("Invalid step in cron field: {}", part))?;
+                if step == 0 {
+                    return Err(format!("Invalid step in cron field: {}", part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, names, name_offset)?, parse_value(end, names, name_offset)?)
+        } else {
+            let value = parse_value(range, names, name_offset)?;
+            // "5/10" means every 10 starting at 5
+            (value, if step > 1 { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!("Cron field out of range: {}", part));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Ok(bits)
+}
+
+fn parse_value(value: &str, names: &[&str], name_offset: u32) -> Result<u32, String> {
+    if let Ok(number) = value.parse() {
+        return Ok(number);
+    }
+    names.iter()
+        .position(|name| name.eq_ignore_ascii_case(value))
+        .map(|index| index as u32 + name_offset)
+        .ok_or_else(|| format!("Invalid value in cron expression: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_fields() {
+        let cron = CronSchedule::parse("*/15 9-17 1,15 * *").unwrap();
+        assert_eq!(cron.minutes, 1 | 1 << 15 | 1 << 30 | 1 << 45);
+        assert_eq!(cron.hours, (9..=17).fold(0, |bits, hour| bits | 1 << hour));
+        assert_eq!(cron.days, 1 << 1 | 1 << 15);
+        assert!(cron.matches(&at(2026, 3, 15, 9, 45)));
+        assert!(!cron.matches(&at(2026, 3, 15, 18, 0)));
+        assert!(!cron.matches(&at(2026, 3, 16, 9, 0)));
+    }
+
+    #[test]
+    fn parses_names_and_steps() {
+        let cron = CronSchedule::parse("5/20 0 * jan-mar MON-fri").unwrap();
+        assert_eq!(cron.minutes, 1 << 5 | 1 << 25 | 1 << 45);
+        assert_eq!(cron.months, 1 << 1 | 1 << 2 | 1 << 3);
+        assert_eq!(cron.weekdays, 0b0111110);
+        // 2026-01-05 is a Monday, 2026-01-04 a Sunday
+        assert!(cron.matches(&at(2026, 1, 5, 0, 25)));
+        assert!(!cron.matches(&at(2026, 1, 4, 0, 25)));
+        assert!(!cron.matches(&at(2026, 4, 6, 0, 25)));
+    }
+
+    #[test]
+    fn seven_is_sunday() {
+        assert_eq!(CronSchedule::parse("0 0 * * 7").unwrap().weekdays, 1);
+        assert_eq!(CronSchedule::parse("0 0 * * 5-7").unwrap().weekdays, 0b1100001);
+    }
+
+    #[test]
+    fn expands_shorthands() {
+        assert_eq!(CronSchedule::parse("@hourly"), CronSchedule::parse("0 * * * *"));
+        assert_eq!(CronSchedule::parse(" @daily "), CronSchedule::parse("0 0 * * *"));
+        assert_eq!(CronSchedule::parse("@midnight"), CronSchedule::parse("0 0 * * *"));
+        assert_eq!(CronSchedule::parse("@weekly"), CronSchedule::parse("0 0 * * 0"));
+        assert_eq!(CronSchedule::parse("@monthly"), CronSchedule::parse("0 0 1 * *"));
+        assert_eq!(CronSchedule::parse("@annually"), CronSchedule::parse("0 0 1 1 *"));
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "*/x * * * *",
+            "5-1 * * * *",
+            "* * * foo *",
+            "@often",
+        ] {
+            assert!(CronSchedule::parse(expression).is_err(), "{:?} should not parse", expression);
+        }
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 1st of the month or any Monday
+        let cron = CronSchedule::parse("0 0 1 * mon").unwrap();
+        assert!(cron.matches(&at(2026, 1, 1, 0, 0)));
+        assert!(cron.matches(&at(2026, 1, 5, 0, 0)));
+        assert!(!cron.matches(&at(2026, 1, 6, 0, 0)));
+    }
+
+    #[test]
+    fn stepped_star_day_field_is_unrestricted() {
+        // Odd days that are also Mondays, not odd days or Mondays
+        let cron = CronSchedule::parse("0 0 */2 * mon").unwrap();
+        assert!(cron.matches(&at(2026, 1, 5, 0, 0)));
+        assert!(!cron.matches(&at(2026, 1, 12, 0, 0)));
+        assert!(!cron.matches(&at(2026, 1, 7, 0, 0)));
+
+        let cron = CronSchedule::parse("0 0 1 * */2").unwrap();
+        assert!(cron.any_weekday);
+        // 2026-05-01 is a Friday, 2026-02-01 a Sunday
+        assert!(!cron.matches(&at(2026, 5, 1, 0, 0)));
+        assert!(cron.matches(&at(2026, 2, 1, 0, 0)));
+        assert!(!cron.matches(&at(2026, 1, 3, 0, 0)));
+    }
+}
\ No newline at end of file
@@ -1,4 +1,8 @@
+use std::fs::{File, Metadata};
+use std::io::{BufReader, Read};
 use std::path::Path;
+use std::time::UNIX_EPOCH;
+use sha2::{Digest, Sha256};
 
 pub fn get_supported_extensions() -> [&'static str; 13] {
     [
@@ -7,28 +11,36 @@ pub fn get_supported_extensions() -> [&'static str; 13] {
     ]
 }
 
-// Utility function to check if a file is a GIF
-pub fn is_gif_type(file_type: &str) -> bool {
-    file_type.to_lowercase() == "gif"
+pub fn has_supported_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| get_supported_extensions().contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
 }
 
-pub fn get_mime_type(file_path: &str) -> String {
-    let path = Path::new(file_path);
-    let file_extension = path.extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("")
-        .to_lowercase();
-    
-    if file_extension == "gif" {
-        "image/gif".to_string()
-    } else {
-        match file_extension.as_str() {
-            "mp4" => "video/mp4",
-            "webm" => "video/webm",
-            "avi" => "video/avi",
-            "mov" => "video/quicktime",
-            "mkv" => "video/x-matroska",
-            _ => "video/mp4",
-        }.to_string()
+// SHA-256 of the whole file, used to recognize the same wallpaper after a move or rename
+pub fn content_hash(path: &Path) -> Result<String, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    loop {
+        let read = reader.read(&mut buffer).map_err(|e| format!("Failed to hash file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
     }
+
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+// Last modification time in seconds since the Unix epoch
+pub fn modified_secs(metadata: &Metadata) -> Option<i64> {
+    metadata.modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs() as i64)
 }
\ No newline at end of file
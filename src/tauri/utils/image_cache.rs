@@ -0,0 +1,274 @@
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, RgbImage};
+use sha2::{Digest, Sha256};
+use crate::utils::file_utils::content_hash;
+
+const MAX_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+const MAX_CACHE_ENTRIES: usize = 64;
+const JPEG_QUALITY: u8 = 92;
+
+// Bump when the conversion output changes so stale entries are not reused
+const CACHE_VERSION: &str = "v2";
+
+pub struct NormalizeRequest<'a> {
+    pub source: &'a Path,
+    pub file_type: &'a str,
+    pub formats: &'a [&'a str],
+    pub target_size: Option<(u32, u32)>,
+    pub orientation: Option<u16>,
+    // Hash the library already stored for the source, so it is not read again just for the key
+    pub content_hash: Option<&'a str>,
+}
+
+// Return a path the backend can display: the source itself when it is already usable,
+// otherwise a converted, oriented and downscaled copy from the cache
+pub fn normalize_for_backend(cache_dir: &Path, request: &NormalizeRequest) -> Result<PathBuf, String> {
+    let mut decoder = open_decoder(request.source, request.file_type)?;
+    let (width, height) = decoder.dimensions();
+    let orientation = resolve_orientation(request, &mut decoder);
+
+    // Rotated orientations swap the displayed width and height
+    let (display_width, display_height) = match orientation {
+        Orientation::Rotate90 | Orientation::Rotate270
+        | Orientation::Rotate90FlipH | Orientation::Rotate270FlipH => (height, width),
+        _ => (width, height),
+    };
+    let scaled_size = request.target_size
+        .and_then(|target| downscaled_size(display_width, display_height, target));
+
+    let format_supported = request.formats.contains(&request.file_type);
+    if format_supported && orientation == Orientation::NoTransforms && scaled_size.is_none() {
+        return Ok(request.source.to_path_buf());
+    }
+
+    let has_alpha = decoder.color_type().has_alpha();
+    let (extension, format) = output_format(request.formats, has_alpha)
+        .ok_or_else(|| "Wallpaper backend does not accept any convertible format".to_string())?;
+
+    let key = cache_key(request, orientation, scaled_size, extension)?;
+    fs::create_dir_all(cache_dir).map_err(|e| format!("Failed to create image cache: {}", e))?;
+    let cached_path = cache_dir.join(format!("{}.{}", key, extension));
+
+    if cached_path.exists() {
+        touch(&cached_path);
+        return Ok(cached_path);
+    }
+
+    let mut image = DynamicImage::from_decoder(decoder)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    image.apply_orientation(orientation);
+
+    if let Some((scaled_width, scaled_height)) = scaled_size {
+        image = image.resize_exact(scaled_width, scaled_height, FilterType::Lanczos3);
+    }
+
+    // Write next to the final path and rename so a crash never leaves a partial entry
+    let temp_path = cache_dir.join(format!("{}.tmp", key));
+    write_image(&image, &temp_path, format)?;
+    fs::rename(&temp_path, &cached_path).map_err(|e| format!("Failed to store cached image: {}", e))?;
+
+    evict_cache(cache_dir, &cached_path);
+    Ok(cached_path)
+}
+
+// Crossfade frames from one image to another, scaled as normalize_for_backend scales `from` so
+// the fade lines up with the frame before it; `to` is cropped to that shape only if its own
+// differs. Frame k of `steps` is k / (steps + 1) of the way to `to`. Kept in the cache like
+// normalized images, under `key` from blend_key.
+pub fn blend_steps(
+    cache_dir: &Path,
+    key: &str,
+    from: &NormalizeRequest,
+    to: &NormalizeRequest,
+    steps: usize,
+) -> Result<Vec<PathBuf>, String> {
+    let (extension, format) = output_format(from.formats, false)
+        .ok_or_else(|| "Wallpaper backend does not accept any convertible format".to_string())?;
+
+    let paths: Vec<PathBuf> = (1..=steps)
+        .map(|step| cache_dir.join(format!("{}-{}of{}.{}", key, step, steps, extension)))
+        .collect();
+    if paths.iter().all(|path| path.exists()) {
+        paths.iter().for_each(|path| touch(path));
+        return Ok(paths);
+    }
+
+    fs::create_dir_all(cache_dir).map_err(|e| format!("Failed to create image cache: {}", e))?;
+    let mut from_image = open_oriented(from)?;
+    let (width, height) = from.target_size
+        .and_then(|target| downscaled_size(from_image.width(), from_image.height(), target))
+        .unwrap_or((from_image.width(), from_image.height()));
+    if (width, height) != (from_image.width(), from_image.height()) {
+        from_image = from_image.resize_exact(width, height, FilterType::Lanczos3);
+    }
+    let from_pixels = from_image.to_rgb8().into_raw();
+    let to_pixels = open_oriented(to)?.resize_to_fill(width, height, FilterType::Lanczos3).to_rgb8().into_raw();
+
+    for (index, path) in paths.iter().enumerate() {
+        let amount = (index + 1) as f32 / (steps + 1) as f32;
+        let pixels = from_pixels.iter()
+            .zip(&to_pixels)
+            .map(|(&a, &b)| (a as f32 + (b as f32 - a as f32) * amount).round() as u8)
+            .collect();
+        let image = RgbImage::from_raw(width, height, pixels)
+            .ok_or_else(|| "Failed to blend images".to_string())?;
+
+        let temp_path = path.with_extension("tmp");
+        write_image(&DynamicImage::ImageRgb8(image), &temp_path, format)?;
+        fs::rename(&temp_path, path).map_err(|e| format!("Failed to store cached image: {}", e))?;
+    }
+
+    if let Some(last) = paths.last() {
+        evict_cache(cache_dir, last);
+    }
+    Ok(paths)
+}
+
+// Cache key of the fade between two images. Hashing the sources is slow for files the library
+// does not know, so callers keep it for the length of the fade.
+pub fn blend_key(from: &NormalizeRequest, to: &NormalizeRequest) -> Result<String, String> {
+    let (extension, _) = output_format(from.formats, false)
+        .ok_or_else(|| "Wallpaper backend does not accept any convertible format".to_string())?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(cache_key(from, Orientation::NoTransforms, from.target_size, extension)?);
+    hasher.update(cache_key(to, Orientation::NoTransforms, to.target_size, extension)?);
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+// Prefer the EXIF tag read at scan time; decoders only report it for some formats
+fn resolve_orientation(request: &NormalizeRequest, decoder: &mut impl ImageDecoder) -> Orientation {
+    request.orientation
+        .and_then(|value| Orientation::from_exif(value as u8))
+        .unwrap_or_else(|| decoder.orientation().unwrap_or(Orientation::NoTransforms))
+}
+
+fn open_oriented(request: &NormalizeRequest) -> Result<DynamicImage, String> {
+    let mut decoder = open_decoder(request.source, request.file_type)?;
+    let orientation = resolve_orientation(request, &mut decoder);
+    let mut image = DynamicImage::from_decoder(decoder)
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+fn open_decoder(source: &Path, file_type: &str) -> Result<impl ImageDecoder, String> {
+    let mut reader = ImageReader::open(source)
+        .map_err(|e| format!("Failed to open image: {}", e))?;
+
+    // TGA has no signature, so trust the sniffed type over image's own guess
+    if let Some(format) = ImageFormat::from_extension(file_type) {
+        reader.set_format(format);
+    }
+
+    reader.into_decoder().map_err(|e| format!("Failed to read image header: {}", e))
+}
+
+// Scale so the image still covers the whole target, never upscaling
+fn downscaled_size(width: u32, height: u32, target: (u32, u32)) -> Option<(u32, u32)> {
+    let (target_width, target_height) = target;
+    if width == 0 || height == 0 || target_width == 0 || target_height == 0 {
+        return None;
+    }
+
+    let scale = (target_width as f64 / width as f64).max(target_height as f64 / height as f64);
+    if scale >= 1.0 {
+        return None;
+    }
+
+    Some((
+        ((width as f64 * scale).round() as u32).max(1),
+        ((height as f64 * scale).round() as u32).max(1),
+    ))
+}
+
+fn output_format(formats: &[&str], has_alpha: bool) -> Option<(&'static str, ImageFormat)> {
+    let preferred: &[&'static str] = if has_alpha {
+        &["png", "tiff", "bmp", "jpg"]
+    } else {
+        &["jpg", "png", "bmp", "tiff"]
+    };
+
+    preferred.iter()
+        .find(|extension| formats.contains(extension))
+        .and_then(|extension| ImageFormat::from_extension(extension).map(|format| (*extension, format)))
+}
+
+fn cache_key(
+    request: &NormalizeRequest,
+    orientation: Orientation,
+    scaled_size: Option<(u32, u32)>,
+    extension: &str,
+) -> Result<String, String> {
+    let source_hash = match request.content_hash {
+        Some(hash) => hash.to_string(),
+        None => content_hash(request.source)?,
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(source_hash.as_bytes());
+    hasher.update(format!("{}:{:?}:{:?}:{}", CACHE_VERSION, orientation, scaled_size, extension).as_bytes());
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+fn write_image(image: &DynamicImage, path: &Path, format: ImageFormat) -> Result<(), String> {
+    let result = if format == ImageFormat::Jpeg {
+        let file = File::create(path).map_err(|e| format!("Failed to create cached image: {}", e))?;
+        let encoder = JpegEncoder::new_with_quality(BufWriter::new(file), JPEG_QUALITY);
+        DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)
+    } else {
+        image.save_with_format(path, format)
+    };
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(path);
+        format!("Failed to encode cached image: {}", e)
+    })
+}
+
+// Mark an entry as recently used; eviction goes by modification time
+fn touch(path: &Path) {
+    if let Ok(file) = File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+fn evict_cache(cache_dir: &Path, keep: &Path) {
+    let Ok(read_dir) = fs::read_dir(cache_dir) else {
+        return;
+    };
+
+    let mut entries: Vec<(PathBuf, u64, SystemTime)> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata.modified().ok()?;
+            metadata.is_file().then(|| (entry.path(), metadata.len(), modified))
+        })
+        .filter(|(path, _, _)| path.extension().and_then(|ext| ext.to_str()) != Some("tmp"))
+        .collect();
+
+    entries.sort_by_key(|(_, _, modified)| *modified);
+
+    let mut total_bytes: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    let mut total_entries = entries.len();
+
+    for (path, size, _) in entries {
+        if total_bytes <= MAX_CACHE_BYTES && total_entries <= MAX_CACHE_ENTRIES {
+            break;
+        }
+        if path == keep {
+            continue;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total_bytes -= size;
+            total_entries -= 1;
+        }
+    }
+}
\ No newline at end of file
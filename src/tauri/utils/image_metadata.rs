@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use exif::{DateTime, Exif, In, Reader, Tag, Value};
+use image::{ImageFormat, ImageReader};
+use crate::types::GpsLocation;
+
+#[derive(Debug, Clone, Default)]
+pub struct ImageMetadata {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub orientation: Option<u16>,
+    pub captured_at: Option<String>,
+    pub camera_model: Option<String>,
+    pub gps_location: Option<GpsLocation>,
+}
+
+// Read dimensions and EXIF tags from the file headers without decoding any pixels
+pub fn read_image_metadata(path: &Path, file_type: &str) -> ImageMetadata {
+    let mut metadata = ImageMetadata::default();
+
+    if let Some((width, height)) = read_dimensions(path, file_type) {
+        metadata.width = Some(width);
+        metadata.height = Some(height);
+    }
+
+    if let Some(exif) = read_exif(path) {
+        metadata.orientation = exif.get_field(Tag::Orientation, In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+            .filter(|orientation| (1..=8).contains(orientation))
+            .map(|orientation| orientation as u16);
+
+        metadata.captured_at = read_ascii(&exif, Tag::DateTimeOriginal)
+            .or_else(|| read_ascii(&exif, Tag::DateTime))
+            .and_then(|value| DateTime::from_ascii(value.as_bytes()).ok())
+            .map(|date| format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                date.year, date.month, date.day, date.hour, date.minute, date.second
+            ));
+
+        metadata.camera_model = read_ascii(&exif, Tag::Model);
+        metadata.gps_location = read_gps_location(&exif);
+    }
+
+    metadata
+}
+
+fn read_dimensions(path: &Path, file_type: &str) -> Option<(u32, u32)> {
+    let mut reader = ImageReader::open(path).ok()?;
+    if let Some(format) = ImageFormat::from_extension(file_type) {
+        reader.set_format(format);
+    }
+    reader.into_dimensions().ok()
+}
+
+fn read_exif(path: &Path) -> Option<Exif> {
+    let file = File::open(path).ok()?;
+    Reader::new().read_from_container(&mut BufReader::new(file)).ok()
+}
+
+fn read_ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => values.first()
+            .map(|value| String::from_utf8_lossy(value).trim_matches(char::from(0)).trim().to_string())
+            .filter(|value| !value.is_empty()),
+        _ => None,
+    }
+}
+
+fn read_gps_location(exif: &Exif) -> Option<GpsLocation> {
+    let latitude = read_gps_coordinate(exif, Tag::GPSLatitude, Tag::GPSLatitudeRef, "S")?;
+    let longitude = read_gps_coordinate(exif, Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")?;
+
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+
+    Some(GpsLocation { latitude, longitude })
+}
+
+// GPS coordinates are stored as degrees, minutes and seconds plus a hemisphere reference
+fn read_gps_coordinate(exif: &Exif, tag: Tag, reference_tag: Tag, negative_reference: &str) -> Option<f64> {
+    let Value::Rational(parts) = &exif.get_field(tag, In::PRIMARY)?.value else {
+        return None;
+    };
+    if parts.len() < 3 || parts.iter().any(|part| part.denom == 0) {
+        return None;
+    }
+
+    let degrees = parts[0].to_f64() + parts[1].to_f64() / 60.0 + parts[2].to_f64() / 3600.0;
+    let is_negative = read_ascii(exif, reference_tag)
+        .map(|reference| reference.eq_ignore_ascii_case(negative_reference))
+        .unwrap_or(false);
+
+    Some(if is_negative { -degrees } else { degrees })
+}
\ No newline at end of file
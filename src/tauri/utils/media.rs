@@ -0,0 +1,247 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use crate::types::MediaKind;
+
+// Enough to cover every container signature we check, including the Matroska DocType
+const SNIFF_LEN: usize = 64;
+const TGA_FOOTER: &[u8] = b"TRUEVISION-XFILE.\0";
+
+#[derive(Debug, Clone)]
+pub struct MediaDetection {
+    pub file_type: String,
+    pub mime_type: String,
+    pub media_kind: MediaKind,
+    pub is_valid: bool,
+    pub invalid_reason: Option<String>,
+}
+
+impl MediaDetection {
+    fn valid(file_type: &str, mime_type: &str, media_kind: MediaKind) -> Self {
+        Self {
+            file_type: file_type.to_string(),
+            mime_type: mime_type.to_string(),
+            media_kind,
+            is_valid: true,
+            invalid_reason: None,
+        }
+    }
+
+    fn corrupt(mut self, reason: &str) -> Self {
+        self.is_valid = false;
+        self.invalid_reason = Some(reason.to_string());
+        self
+    }
+
+    fn unsupported(file_type: String, reason: &str) -> Self {
+        Self {
+            file_type,
+            mime_type: "application/octet-stream".to_string(),
+            media_kind: MediaKind::Static,
+            is_valid: false,
+            invalid_reason: Some(reason.to_string()),
+        }
+    }
+}
+
+// Detect the real format of a file from its leading bytes instead of its extension
+pub fn sniff_media(path: &Path) -> MediaDetection {
+    let extension = path.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => return MediaDetection::unsupported(extension, &format!("Failed to open file: {}", e)),
+    };
+
+    let file_len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if file_len == 0 {
+        return MediaDetection::unsupported(extension, "File is empty");
+    }
+
+    let mut header = [0u8; SNIFF_LEN];
+    let read = match read_up_to(&mut file, &mut header) {
+        Ok(read) => read,
+        Err(e) => return MediaDetection::unsupported(extension, &format!("Failed to read file: {}", e)),
+    };
+    let header = &header[..read];
+
+    if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        let detection = MediaDetection::valid("jpg", "image/jpeg", MediaKind::Static);
+        if !has_jpeg_end_marker(&mut file, file_len) {
+            return detection.corrupt("JPEG data is truncated");
+        }
+        return detection;
+    }
+
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let detection = MediaDetection::valid("png", "image/png", MediaKind::Static);
+        if header.len() < 16 || &header[12..16] != b"IHDR" {
+            return detection.corrupt("PNG header is missing");
+        }
+        if !has_png_end_chunk(&mut file, file_len) {
+            return detection.corrupt("PNG data is truncated");
+        }
+        if is_animated_png(&mut file) {
+            return MediaDetection::valid("png", "image/apng", MediaKind::Animated);
+        }
+        return detection;
+    }
+
+    if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        let detection = MediaDetection::valid("gif", "image/gif", MediaKind::Animated);
+        if header.len() < 13 {
+            return detection.corrupt("GIF header is truncated");
+        }
+        return detection;
+    }
+
+    if header.starts_with(b"BM") {
+        let detection = MediaDetection::valid("bmp", "image/bmp", MediaKind::Static);
+        let dib_size = read_u32_le(header, 14);
+        return match dib_size {
+            Some(12 | 40 | 52 | 56 | 64 | 108 | 124) => detection,
+            _ => detection.corrupt("BMP header is invalid"),
+        };
+    }
+
+    if header.starts_with(b"II*\0") || header.starts_with(b"MM\0*") {
+        return MediaDetection::valid("tiff", "image/tiff", MediaKind::Static);
+    }
+
+    if header.len() >= 12 && &header[0..4] == b"RIFF" {
+        let declared_len = read_u32_le(header, 4).unwrap_or(0) as u64 + 8;
+        let detection = match &header[8..12] {
+            b"WEBP" => {
+                // VP8X carries the animation flag in its first payload byte
+                let animated = header.len() > 20 && &header[12..16] == b"VP8X" && header[20] & 0x02 != 0;
+                let media_kind = if animated { MediaKind::Animated } else { MediaKind::Static };
+                MediaDetection::valid("webp", "image/webp", media_kind)
+            }
+            b"AVI " => MediaDetection::valid("avi", "video/x-msvideo", MediaKind::Video),
+            _ => return MediaDetection::unsupported(extension, "Unsupported RIFF container"),
+        };
+        if declared_len > file_len {
+            return detection.corrupt("RIFF data is truncated");
+        }
+        return detection;
+    }
+
+    if header.len() >= 12 && &header[4..8] == b"ftyp" {
+        let box_size = read_u32_be(header, 0).unwrap_or(0);
+        if box_size < 8 || box_size as u64 > file_len {
+            return MediaDetection::valid("mp4", "video/mp4", MediaKind::Video)
+                .corrupt("MP4 header is invalid");
+        }
+        return match &header[8..12] {
+            b"qt  " => MediaDetection::valid("mov", "video/quicktime", MediaKind::Video),
+            b"heic" | b"heix" | b"mif1" | b"msf1" | b"avif" | b"avis" => {
+                MediaDetection::unsupported(extension, "HEIF/AVIF images are not supported")
+            }
+            _ => MediaDetection::valid("mp4", "video/mp4", MediaKind::Video),
+        };
+    }
+
+    if header.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        // The EBML DocType element tells WebM apart from generic Matroska
+        return match find_subslice(header, b"webm") {
+            Some(_) => MediaDetection::valid("webm", "video/webm", MediaKind::Video),
+            None => MediaDetection::valid("mkv", "video/x-matroska", MediaKind::Video),
+        };
+    }
+
+    // TGA has no leading signature, so only trust it when the file claims to be one
+    if is_tga(&mut file, header, file_len) || (extension == "tga" && has_tga_header(header)) {
+        return MediaDetection::valid("tga", "image/x-tga", MediaKind::Static);
+    }
+
+    MediaDetection::unsupported(extension, "Unsupported or unrecognized file format")
+}
+
+fn read_up_to(file: &mut File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut total = 0;
+    while total < buffer.len() {
+        match file.read(&mut buffer[total..])? {
+            0 => break,
+            n => total += n,
+        }
+    }
+    Ok(total)
+}
+
+fn read_tail(file: &mut File, file_len: u64, len: usize) -> Option<Vec<u8>> {
+    let len = len.min(file_len as usize);
+    file.seek(SeekFrom::Start(file_len - len as u64)).ok()?;
+    let mut tail = vec![0u8; len];
+    file.read_exact(&mut tail).ok()?;
+    Some(tail)
+}
+
+fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_u32_be(bytes: &[u8], offset: usize) -> Option<u32> {
+    bytes.get(offset..offset + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+// Some encoders pad after EOI, so look for the marker near the end rather than at it
+fn has_jpeg_end_marker(file: &mut File, file_len: u64) -> bool {
+    read_tail(file, file_len, 1024)
+        .map(|tail| find_subslice(&tail, &[0xFF, 0xD9]).is_some())
+        .unwrap_or(false)
+}
+
+fn has_png_end_chunk(file: &mut File, file_len: u64) -> bool {
+    read_tail(file, file_len, 12)
+        .map(|tail| find_subslice(&tail, b"IEND").is_some())
+        .unwrap_or(false)
+}
+
+// APNG files declare an acTL chunk before the first IDAT
+fn is_animated_png(file: &mut File) -> bool {
+    let mut offset = 8u64;
+    let mut chunk_header = [0u8; 8];
+
+    loop {
+        if file.seek(SeekFrom::Start(offset)).is_err() || file.read_exact(&mut chunk_header).is_err() {
+            return false;
+        }
+        let length = u32::from_be_bytes([chunk_header[0], chunk_header[1], chunk_header[2], chunk_header[3]]) as u64;
+        match &chunk_header[4..8] {
+            b"acTL" => return true,
+            b"IDAT" | b"IEND" => return false,
+            _ => offset += 12 + length,
+        }
+    }
+}
+
+fn is_tga(file: &mut File, header: &[u8], file_len: u64) -> bool {
+    has_tga_header(header)
+        && read_tail(file, file_len, TGA_FOOTER.len())
+            .map(|tail| tail == TGA_FOOTER)
+            .unwrap_or(false)
+}
+
+fn has_tga_header(header: &[u8]) -> bool {
+    if header.len() < 18 {
+        return false;
+    }
+    let color_map_type = header[1];
+    let image_type = header[2];
+    let width = u16::from_le_bytes([header[12], header[13]]);
+    let height = u16::from_le_bytes([header[14], header[15]]);
+    let pixel_depth = header[16];
+
+    color_map_type <= 1
+        && matches!(image_type, 1 | 2 | 3 | 9 | 10 | 11)
+        && matches!(pixel_depth, 8 | 15 | 16 | 24 | 32)
+        && width > 0
+        && height > 0
+}
@@ -1 +1,12 @@
-pub mod file_utils;
\ No newline at end of file
+pub mod file_utils;
+pub mod media;
+pub mod image_cache;
+pub mod image_metadata;
+pub mod video_probe;
+pub mod thumbnails;
+pub mod perceptual_hash;
+pub mod color_palette;
+pub mod cron;
+pub mod solar;
+pub mod cities;
+pub mod scanner;
\ No newline at end of file
@@ -0,0 +1,30 @@
+use std::path::Path;
+use image::imageops::FilterType;
+use image::ImageReader;
+
+// dHash: shrink to 9x8 grayscale and record whether each pixel is darker than its right neighbour.
+// Resizing and re-encoding barely move the result, so close hashes mean visually similar images.
+pub fn difference_hash(path: &Path) -> Result<u64, String> {
+    let image = ImageReader::open(path)
+        .map_err(|e| format!("Failed to open image: {}", e))?
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .decode()
+        .map_err(|e| format!("Failed to decode image: {}", e))?;
+
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y)[0] < small.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    Ok(hash)
+}
+
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
\ No newline at end of file
@@ -0,0 +1,363 @@
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use rayon::prelude::*;
+use walkdir::{DirEntry, WalkDir};
+use crate::types::{MediaKind, ScanOptions, SymlinkPolicy, WallpaperInfo};
+use crate::utils::file_utils::{has_supported_extension, modified_secs};
+use crate::utils::image_metadata::{read_image_metadata, ImageMetadata};
+use crate::utils::media::sniff_media;
+use crate::utils::video_probe::probe_video_metadata;
+
+// Build library metadata for a file, typed by its content rather than its extension
+pub fn build_wallpaper_info(path: &Path) -> WallpaperInfo {
+    let detection = sniff_media(path);
+    let file_metadata = std::fs::metadata(path).ok();
+    let size = file_metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+
+    let metadata = if detection.is_valid && detection.media_kind != MediaKind::Video {
+        read_image_metadata(path, &detection.file_type)
+    } else {
+        ImageMetadata::default()
+    };
+
+    let video = if detection.is_valid && (detection.media_kind == MediaKind::Video || detection.file_type == "gif") {
+        probe_video_metadata(path, &detection.file_type)
+    } else {
+        None
+    };
+
+    WallpaperInfo {
+        path: path.to_string_lossy().to_string(),
+        name: path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        file_type: detection.file_type,
+        size,
+        modified_at: file_metadata.as_ref().and_then(modified_secs),
+        mime_type: detection.mime_type,
+        media_kind: detection.media_kind,
+        is_valid: detection.is_valid,
+        invalid_reason: detection.invalid_reason,
+        is_missing: false,
+        width: metadata.width.or_else(|| video.as_ref().and_then(|v| v.width)),
+        height: metadata.height.or_else(|| video.as_ref().and_then(|v| v.height)),
+        orientation: metadata.orientation,
+        captured_at: metadata.captured_at,
+        camera_model: metadata.camera_model,
+        gps_location: metadata.gps_location,
+        video,
+        tags: Vec::new(),
+        favorite: false,
+        rating: None,
+        blocked: false,
+        palette: Vec::new(),
+    }
+}
+
+pub fn invalid_wallpaper_info(path: &Path, reason: &str) -> WallpaperInfo {
+    WallpaperInfo {
+        path: path.to_string_lossy().to_string(),
+        name: path.file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        file_type: path.extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase(),
+        is_valid: false,
+        invalid_reason: Some(reason.to_string()),
+        ..Default::default()
+    }
+}
+
+// Scan options compiled once per scan so every entry is not re-parsing globs
+pub struct ScanFilter {
+    options: ScanOptions,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl ScanFilter {
+    pub fn new(options: &ScanOptions) -> Result<Self, String> {
+        Ok(Self {
+            options: options.clone(),
+            include: build_glob_set(&options.include_patterns)?,
+            exclude: build_glob_set(&options.exclude_patterns)?,
+        })
+    }
+
+    pub fn options(&self) -> &ScanOptions {
+        &self.options
+    }
+
+    // Decide whether to descend into a directory or look at a file at all
+    pub fn accepts_entry(&self, root: &Path, entry: &DirEntry) -> bool {
+        if entry.depth() == 0 {
+            return true;
+        }
+        if !self.options.include_hidden && is_hidden(entry) {
+            return false;
+        }
+        if entry.path_is_symlink() && self.options.symlink_policy == SymlinkPolicy::Skip {
+            return false;
+        }
+        !self.is_excluded(root, entry.path())
+    }
+
+    // Same rules as a walk, for a single path reported by a file watcher
+    pub fn accepts_path(&self, root: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+        if self.options.max_depth.is_some_and(|max_depth| relative.components().count() > max_depth) {
+            return false;
+        }
+        if !self.options.include_hidden
+            && relative.components().any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+        {
+            return false;
+        }
+        if self.options.symlink_policy == SymlinkPolicy::Skip && path.is_symlink() {
+            return false;
+        }
+        !path.ancestors()
+            .take_while(|ancestor| ancestor.starts_with(root) && *ancestor != root)
+            .any(|ancestor| self.is_excluded(root, ancestor))
+    }
+
+    // Cheap checks on the path and size before any file contents are read
+    pub fn accepts_file(&self, root: &Path, path: &Path, metadata: &Metadata) -> bool {
+        if !metadata.is_file() {
+            return false;
+        }
+        if self.include.as_ref().is_some_and(|include| !glob_matches(include, root, path)) {
+            return false;
+        }
+        if self.options.min_size.is_some_and(|min| metadata.len() < min)
+            || self.options.max_size.is_some_and(|max| metadata.len() > max)
+        {
+            return false;
+        }
+        true
+    }
+
+    // Resolution limits only apply when the dimensions could be read
+    pub fn accepts_info(&self, info: &WallpaperInfo) -> bool {
+        let options = &self.options;
+        let out_of_range = |value: Option<u32>, min: Option<u32>, max: Option<u32>| {
+            value.is_some_and(|value| min.is_some_and(|min| value < min) || max.is_some_and(|max| value > max))
+        };
+
+        !out_of_range(info.width, options.min_width, options.max_width)
+            && !out_of_range(info.height, options.min_height, options.max_height)
+    }
+
+    fn is_excluded(&self, root: &Path, path: &Path) -> bool {
+        self.exclude.as_ref().is_some_and(|exclude| glob_matches(exclude, root, path))
+    }
+}
+
+// List the files a scan would look at, with their sizes, without reading any contents
+pub fn walk_files(directory: &Path, filter: &ScanFilter) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    walk_matching_files(directory, filter, &AtomicBool::new(false), |path, size| files.push((path, size)));
+    files
+}
+
+// Files are read in batches so progress and results can be reported while the walk continues
+const SCAN_BATCH_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanCounts {
+    pub discovered: usize,
+    pub processed: usize,
+    pub matched: usize,
+}
+
+pub fn scan_directory(directory: &Path, filter: &ScanFilter) -> Vec<WallpaperInfo> {
+    let mut wallpapers = Vec::new();
+    scan_directory_streaming(directory, filter, &AtomicBool::new(false), |batch, _| {
+        wallpapers.extend(batch);
+    });
+    wallpapers
+}
+
+// Walk the folder and hand each batch of matches to `on_batch` as it is ready. Reading
+// metadata is the slow part on network shares, so each batch is processed in parallel.
+// Returns false when the scan stopped early because `cancelled` was set.
+pub fn scan_directory_streaming<F>(
+    directory: &Path,
+    filter: &ScanFilter,
+    cancelled: &AtomicBool,
+    mut on_batch: F,
+) -> bool
+where
+    F: FnMut(Vec<WallpaperInfo>, ScanCounts),
+{
+    let mut counts = ScanCounts::default();
+    let mut pending = Vec::with_capacity(SCAN_BATCH_SIZE);
+
+    let completed = walk_matching_files(directory, filter, cancelled, |path, _| {
+        counts.discovered += 1;
+        pending.push(path);
+        if pending.len() == SCAN_BATCH_SIZE {
+            process_batch(std::mem::take(&mut pending), filter, &mut counts, &mut on_batch);
+        }
+    });
+
+    if !completed {
+        return false;
+    }
+    process_batch(pending, filter, &mut counts, &mut on_batch);
+    true
+}
+
+// Walk the folder and hand every accepted file to `on_file` on the calling thread. The top level
+// is listed first, then each subfolder gets its own walker on the rayon pool, so folders are read
+// and stat'ed in parallel. Returns false when the walk stopped early because `cancelled` was set.
+fn walk_matching_files<F>(directory: &Path, filter: &ScanFilter, cancelled: &AtomicBool, mut on_file: F) -> bool
+where
+    F: FnMut(PathBuf, u64),
+{
+    let options = filter.options();
+    let (sender, receiver) = mpsc::channel();
+    let mut subtrees = Vec::new();
+
+    // Symlink loops surface as walk errors when following links, so dropping errors also skips them
+    for entry in build_walker(directory, options, Some(options.max_depth.unwrap_or(1).min(1)))
+        .into_iter()
+        .filter_entry(|entry| filter.accepts_entry(directory, entry))
+        .filter_map(|e| e.ok())
+    {
+        if entry.depth() == 0 {
+            continue;
+        }
+        if entry.file_type().is_dir() {
+            if options.max_depth.is_none_or(|max_depth| max_depth > 1) {
+                subtrees.push(entry.into_path());
+            }
+        } else if let Some(file) = accepted_file(directory, filter, entry) {
+            let _ = sender.send(file);
+        }
+    }
+
+    // A subfolder walker cannot see the scan root as an ancestor, so a link back to it is cut here
+    let root = std::fs::canonicalize(directory).ok();
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            subtrees.par_iter().for_each_with(sender, |sender, subtree| {
+                // Subfolders are one level below the root, so they get one level less to descend
+                let walker = build_walker(subtree, options, options.max_depth.map(|max_depth| max_depth - 1))
+                    .into_iter()
+                    .filter_entry(|entry| {
+                        entry.depth() == 0
+                            || (filter.accepts_entry(directory, entry) && !links_to_root(entry, root.as_deref()))
+                    })
+                    .filter_map(|e| e.ok());
+                for entry in walker {
+                    if cancelled.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    if let Some(file) = accepted_file(directory, filter, entry)
+                        && sender.send(file).is_err()
+                    {
+                        return;
+                    }
+                }
+            });
+        });
+
+        for (path, size) in receiver {
+            if cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+            on_file(path, size);
+        }
+    });
+
+    !cancelled.load(Ordering::Relaxed)
+}
+
+fn links_to_root(entry: &DirEntry, root: Option<&Path>) -> bool {
+    entry.path_is_symlink()
+        && entry.file_type().is_dir()
+        && root.is_some_and(|root| std::fs::canonicalize(entry.path()).is_ok_and(|target| target == root))
+}
+
+fn accepted_file(root: &Path, filter: &ScanFilter, entry: DirEntry) -> Option<(PathBuf, u64)> {
+    let metadata = std::fs::metadata(entry.path()).ok()?;
+    filter.accepts_file(root, entry.path(), &metadata)
+        .then(|| (entry.into_path(), metadata.len()))
+}
+
+fn build_walker(directory: &Path, options: &ScanOptions, max_depth: Option<usize>) -> WalkDir {
+    let walker = WalkDir::new(directory)
+        .follow_links(options.symlink_policy == SymlinkPolicy::Follow);
+    match max_depth {
+        Some(max_depth) => walker.max_depth(max_depth),
+        None => walker,
+    }
+}
+
+fn process_batch<F>(paths: Vec<PathBuf>, filter: &ScanFilter, counts: &mut ScanCounts, on_batch: &mut F)
+where
+    F: FnMut(Vec<WallpaperInfo>, ScanCounts),
+{
+    if paths.is_empty() {
+        return;
+    }
+
+    let processed = paths.len();
+    let wallpapers: Vec<WallpaperInfo> = paths
+        .par_iter()
+        .map(|path| build_wallpaper_info(path))
+        // Keep recognized media even without a known extension, and surface broken
+        // files that look like wallpapers instead of dropping them
+        .filter(|info| (info.is_valid || has_supported_extension(Path::new(&info.path))) && filter.accepts_info(info))
+        .collect();
+
+    counts.processed += processed;
+    counts.matched += wallpapers.len();
+    on_batch(wallpapers, *counts);
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, String> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| format!("Invalid pattern {}: {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder.build().map(Some).map_err(|e| format!("Failed to build patterns: {}", e))
+}
+
+// Patterns may target the path relative to the scan root or just the file name
+fn glob_matches(set: &GlobSet, root: &Path, path: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    set.is_match(relative) || path.file_name().is_some_and(|name| set.is_match(name))
+}
+
+fn is_hidden(entry: &DirEntry) -> bool {
+    if entry.file_name().to_string_lossy().starts_with('.') {
+        return true;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if let Ok(metadata) = entry.metadata() {
+            return metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0;
+        }
+    }
+
+    false
+}
\ No newline at end of file
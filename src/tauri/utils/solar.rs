@@ -0,0 +1,93 @@
+use crate::types::{SolarPhase, SolarPosition};
+
+// Elevations in degrees where the phases meet. Golden hour spans the low sun on either side of
+// sunrise and sunset, and civil plus nautical twilight count as dusk.
+const GOLDEN_HOUR_ABOVE: f64 = 6.0;
+const DUSK_ABOVE: f64 = -4.0;
+const NIGHT_BELOW: f64 = -12.0;
+
+// Sun position for an instant and a place, after NOAA's solar calculator. Accurate to well under
+// a degree between 1900 and 2100, which is far more than picking a wallpaper needs.
+pub fn solar_position(unix_secs: i64, latitude: f64, longitude: f64) -> SolarPosition {
+    let julian_day = unix_secs as f64 / 86400.0 + 2440587.5;
+    let t = (julian_day - 2451545.0) / 36525.0;
+
+    // Geometric mean longitude and anomaly of the sun, and the eccentricity of earth's orbit
+    let mean_longitude = (280.46646 + t * (36000.76983 + t * 0.0003032)).rem_euclid(360.0);
+    let mean_anomaly = 357.52911 + t * (35999.05029 - 0.0001537 * t);
+    let eccentricity = 0.016708634 - t * (0.000042037 + 0.0000001267 * t);
+
+    let anomaly = mean_anomaly.to_radians();
+    let center = anomaly.sin() * (1.914602 - t * (0.004817 + 0.000014 * t))
+        + (2.0 * anomaly).sin() * (0.019993 - 0.000101 * t)
+        + (3.0 * anomaly).sin() * 0.000289;
+    let omega = (125.04 - 1934.136 * t).to_radians();
+    let apparent_longitude = (mean_longitude + center - 0.00569 - 0.00478 * omega.sin()).to_radians();
+
+    let mean_obliquity = 23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.00059 - t * 0.001813))) / 60.0) / 60.0;
+    let obliquity = (mean_obliquity + 0.00256 * omega.cos()).to_radians();
+    let declination = (obliquity.sin() * apparent_longitude.sin()).asin();
+
+    // Equation of time, in minutes
+    let y = (obliquity / 2.0).tan().powi(2);
+    let longitude_rad = mean_longitude.to_radians();
+    let equation_of_time = 4.0
+        * (y * (2.0 * longitude_rad).sin() - 2.0 * eccentricity * anomaly.sin()
+            + 4.0 * eccentricity * y * anomaly.sin() * (2.0 * longitude_rad).cos()
+            - 0.5 * y * y * (4.0 * longitude_rad).sin()
+            - 1.25 * eccentricity * eccentricity * (2.0 * anomaly).sin())
+        .to_degrees();
+
+    let utc_minutes = unix_secs.rem_euclid(86400) as f64 / 60.0;
+    let solar_minutes = (utc_minutes + equation_of_time + 4.0 * longitude).rem_euclid(1440.0);
+    let hour_angle = (solar_minutes / 4.0 - 180.0).to_radians();
+
+    let latitude = latitude.to_radians();
+    let cos_zenith = latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    let elevation = 90.0 - cos_zenith.clamp(-1.0, 1.0).acos().to_degrees();
+    let azimuth = hour_angle.sin()
+        .atan2(hour_angle.cos() * latitude.sin() - declination.tan() * latitude.cos())
+        .to_degrees();
+
+    SolarPosition {
+        elevation: elevation + refraction(elevation),
+        azimuth: (azimuth + 180.0).rem_euclid(360.0),
+    }
+}
+
+pub fn solar_phase(elevation: f64) -> SolarPhase {
+    if elevation >= GOLDEN_HOUR_ABOVE {
+        SolarPhase::Day
+    } else if elevation >= DUSK_ABOVE {
+        SolarPhase::GoldenHour
+    } else if elevation >= NIGHT_BELOW {
+        SolarPhase::Dusk
+    } else {
+        SolarPhase::Night
+    }
+}
+
+// The first minute within `horizon_minutes` after `unix_secs` where the phase differs, and that phase
+pub fn next_phase_change(
+    unix_secs: i64,
+    latitude: f64,
+    longitude: f64,
+    horizon_minutes: i64,
+) -> Option<(i64, SolarPhase)> {
+    let phase = solar_phase(solar_position(unix_secs, latitude, longitude).elevation);
+    let minute = unix_secs.div_euclid(60) * 60;
+
+    (1..=horizon_minutes)
+        .map(|step| minute + step * 60)
+        .map(|time| (time, solar_phase(solar_position(time, latitude, longitude).elevation)))
+        .find(|(_, next)| *next != phase)
+}
+
+// Atmospheric refraction lifts the sun by about half a degree at the horizon (Saemundsson)
+fn refraction(elevation: f64) -> f64 {
+    if elevation < -1.0 {
+        return 0.0;
+    }
+    let minutes = 1.02 / (elevation + 10.3 / (elevation + 5.11)).to_radians().tan();
+    minutes / 60.0
+}
\ No newline at end of file
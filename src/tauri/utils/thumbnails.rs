@@ -0,0 +1,246 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::UNIX_EPOCH;
+use image::codecs::gif::GifDecoder;
+use image::imageops::FilterType;
+use image::{AnimationDecoder, DynamicImage, ImageFormat, ImageReader};
+use md5::{Digest, Md5};
+use crate::types::MediaKind;
+use crate::utils::media::sniff_media;
+use crate::utils::video_probe::probe_video_metadata;
+
+// Size buckets from the freedesktop thumbnail spec, smallest first
+const THUMBNAIL_BUCKETS: [(&str, u32); 4] = [
+    ("normal", 128),
+    ("large", 256),
+    ("x-large", 512),
+    ("xx-large", 1024),
+];
+
+// Characters GLib leaves unescaped when turning a path into a file:// URI
+const URI_SAFE_CHARS: &[u8] = b"-._~!$&'()*+,;=:@/";
+
+// Where thumbnails are shared with other applications; the spec only exists on Linux
+pub fn default_thumbnail_root() -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|cache| cache.join("thumbnails"))
+}
+
+// Use the shared cache when there is one, falling back to our own when it is not writable
+pub fn get_or_create_cached_thumbnail(app_root: &Path, source: &Path, size: u32) -> Result<PathBuf, String> {
+    match default_thumbnail_root() {
+        Some(root) => get_or_create_thumbnail(&root, source, size)
+            .or_else(|_| get_or_create_thumbnail(app_root, source, size)),
+        None => get_or_create_thumbnail(app_root, source, size),
+    }
+}
+
+// Return a cached PNG thumbnail for the file, generating it when missing or stale
+pub fn get_or_create_thumbnail(root: &Path, source: &Path, size: u32) -> Result<PathBuf, String> {
+    let source = fs::canonicalize(source).map_err(|e| format!("Failed to resolve file: {}", e))?;
+    let mtime = fs::metadata(&source)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Failed to read file metadata: {}", e))?
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    let uri = file_uri(&source);
+    let file_name = format!("{:x}.png", Md5::digest(uri.as_bytes()));
+    let (bucket, bucket_size) = thumbnail_bucket(size);
+
+    // A larger existing thumbnail is just as good, and may already come from the file manager
+    for (name, _) in THUMBNAIL_BUCKETS.iter().filter(|(_, max)| *max >= bucket_size) {
+        let candidate = root.join(name).join(&file_name);
+        if is_thumbnail_fresh(&candidate, &uri, mtime) {
+            return Ok(candidate);
+        }
+    }
+
+    let detection = sniff_media(&source);
+    if !detection.is_valid {
+        return Err(detection.invalid_reason.unwrap_or_else(|| "Unsupported file".to_string()));
+    }
+
+    let frame = match detection.media_kind {
+        MediaKind::Video => extract_video_frame(&source, &detection.file_type)?,
+        MediaKind::Animated if detection.file_type == "gif" => extract_gif_frame(&source)?,
+        _ => decode_image(&source, &detection.file_type)?,
+    };
+
+    let (width, height) = (frame.width(), frame.height());
+    let thumbnail = if width > bucket_size || height > bucket_size {
+        frame.resize(bucket_size, bucket_size, FilterType::Triangle)
+    } else {
+        frame
+    };
+
+    let directory = root.join(bucket);
+    create_private_dir(&directory)?;
+    let thumbnail_path = directory.join(&file_name);
+
+    let size_bytes = fs::metadata(&source).map(|m| m.len()).unwrap_or(0);
+    let text_chunks = [
+        ("Thumb::URI", uri),
+        ("Thumb::MTime", mtime.to_string()),
+        ("Thumb::Size", size_bytes.to_string()),
+        ("Thumb::Mimetype", detection.mime_type),
+        ("Thumb::Image::Width", width.to_string()),
+        ("Thumb::Image::Height", height.to_string()),
+        ("Software", "Wallora".to_string()),
+    ];
+
+    // The spec requires an atomic rename so readers never see a half-written thumbnail
+    let temp_path = directory.join(format!("{}.wallora-{}.tmp", file_name, std::process::id()));
+    write_thumbnail(&thumbnail, &temp_path, &text_chunks)?;
+    fs::rename(&temp_path, &thumbnail_path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("Failed to store thumbnail: {}", e)
+    })?;
+
+    Ok(thumbnail_path)
+}
+
+fn thumbnail_bucket(size: u32) -> (&'static str, u32) {
+    THUMBNAIL_BUCKETS.iter()
+        .copied()
+        .find(|(_, max)| *max >= size)
+        .unwrap_or(THUMBNAIL_BUCKETS[THUMBNAIL_BUCKETS.len() - 1])
+}
+
+fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || URI_SAFE_CHARS.contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+fn is_thumbnail_fresh(path: &Path, uri: &str, mtime: u64) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let Ok(reader) = png::Decoder::new(BufReader::new(file)).read_info() else {
+        return false;
+    };
+
+    let text = &reader.info().uncompressed_latin1_text;
+    let value = |keyword: &str| text.iter()
+        .find(|chunk| chunk.keyword == keyword)
+        .map(|chunk| chunk.text.as_str());
+
+    value("Thumb::URI") == Some(uri) && value("Thumb::MTime") == Some(mtime.to_string().as_str())
+}
+
+fn decode_image(path: &Path, file_type: &str) -> Result<DynamicImage, String> {
+    let mut reader = ImageReader::open(path).map_err(|e| format!("Failed to open image: {}", e))?;
+    if let Some(format) = ImageFormat::from_extension(file_type) {
+        reader.set_format(format);
+    }
+    reader.decode().map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+// The middle frame says more about an animation than the first, which is often blank
+fn extract_gif_frame(path: &Path) -> Result<DynamicImage, String> {
+    let frame_count = probe_video_metadata(path, "gif")
+        .and_then(|metadata| metadata.frame_count)
+        .unwrap_or(1);
+
+    let file = File::open(path).map_err(|e| format!("Failed to open GIF: {}", e))?;
+    let decoder = GifDecoder::new(BufReader::new(file)).map_err(|e| format!("Failed to read GIF: {}", e))?;
+    let frame = decoder.into_frames()
+        .nth((frame_count / 2) as usize)
+        .ok_or_else(|| "GIF has no frames".to_string())?
+        .map_err(|e| format!("Failed to decode GIF frame: {}", e))?;
+
+    Ok(DynamicImage::ImageRgba8(frame.into_buffer()))
+}
+
+// Grab a frame a little way in, past fade-ins and title cards
+fn extract_video_frame(path: &Path, file_type: &str) -> Result<DynamicImage, String> {
+    let seek_secs = probe_video_metadata(path, file_type)
+        .and_then(|metadata| metadata.duration_secs)
+        .map(|duration| duration * 0.1)
+        .unwrap_or(1.0);
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-v", "error", "-ss", &format!("{:.3}", seek_secs), "-i"])
+        .arg(path)
+        .args(["-frames:v", "1", "-f", "image2pipe", "-vcodec", "png", "-"]);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000);
+    }
+
+    let output = command.output().map_err(|e| format!("Failed to execute ffmpeg: {}", e))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(format!("ffmpeg could not extract a frame: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    image::load_from_memory_with_format(&output.stdout, ImageFormat::Png)
+        .map_err(|e| format!("Failed to decode video frame: {}", e))
+}
+
+fn write_thumbnail(image: &DynamicImage, path: &Path, text_chunks: &[(&str, String)]) -> Result<(), String> {
+    let rgba = image.to_rgba8();
+    let file = File::create(path).map_err(|e| format!("Failed to create thumbnail: {}", e))?;
+    set_private_permissions(path);
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), rgba.width(), rgba.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (keyword, text) in text_chunks {
+        encoder.add_text_chunk(keyword.to_string(), text.clone())
+            .map_err(|e| format!("Failed to write thumbnail metadata: {}", e))?;
+    }
+
+    let result = encoder.write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba.as_raw()));
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(path);
+        format!("Failed to encode thumbnail: {}", e)
+    })
+}
+
+// The spec asks for thumbnails only the owner can read, since they reveal file contents
+fn create_private_dir(path: &Path) -> Result<(), String> {
+    fs::create_dir_all(path).map_err(|e| format!("Failed to create thumbnail directory: {}", e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(0o700));
+    }
+
+    Ok(())
+}
+
+fn set_private_permissions(_path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(_path, fs::Permissions::from_mode(0o600));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use serde_json::Value;
+use crate::types::VideoMetadata;
+
+// Browsers bump GIF frame delays below 20ms up to 100ms, so durations should match playback
+const GIF_MIN_DELAY_CS: u64 = 2;
+const GIF_DEFAULT_DELAY_CS: u64 = 10;
+
+type ProbeCache = Mutex<HashMap<String, (SystemTime, VideoMetadata)>>;
+
+fn probe_cache() -> &'static ProbeCache {
+    static CACHE: OnceLock<ProbeCache> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// Probe duration, resolution and codec details, reusing results until the file changes
+pub fn probe_video_metadata(path: &Path, file_type: &str) -> Option<VideoMetadata> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    let key = path.to_string_lossy().to_string();
+
+    let cached = probe_cache().lock().unwrap()
+        .get(&key)
+        .filter(|(cached_modified, _)| *cached_modified == modified)
+        .map(|(_, metadata)| metadata.clone());
+    if cached.is_some() {
+        return cached;
+    }
+
+    let metadata = if file_type == "gif" {
+        probe_gif(path)
+    } else {
+        probe_with_ffprobe(path)
+    }?;
+
+    probe_cache().lock().unwrap().insert(key, (modified, metadata.clone()));
+    Some(metadata)
+}
+
+fn probe_with_ffprobe(path: &Path) -> Option<VideoMetadata> {
+    let mut command = Command::new("ffprobe");
+    command
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(path);
+
+    // Keep ffprobe from flashing a console window on every probe
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(0x08000000);
+    }
+
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        #[cfg(debug_assertions)]
+        eprintln!("ffprobe failed for {}: {}", path.display(), String::from_utf8_lossy(&output.stderr));
+        return None;
+    }
+
+    let probe: Value = serde_json::from_slice(&output.stdout).ok()?;
+    let streams = probe["streams"].as_array()?;
+    let video_stream = streams.iter().find(|stream| stream["codec_type"] == "video")?;
+
+    let duration_secs = parse_number(&probe["format"]["duration"])
+        .or_else(|| parse_number(&video_stream["duration"]));
+    let frame_rate = parse_frame_rate(&video_stream["avg_frame_rate"])
+        .or_else(|| parse_frame_rate(&video_stream["r_frame_rate"]));
+
+    Some(VideoMetadata {
+        duration_secs,
+        width: video_stream["width"].as_u64().map(|width| width as u32),
+        height: video_stream["height"].as_u64().map(|height| height as u32),
+        frame_rate,
+        codec: video_stream["codec_name"].as_str().map(|codec| codec.to_string()),
+        has_audio: streams.iter().any(|stream| stream["codec_type"] == "audio"),
+        frame_count: None,
+    })
+}
+
+// ffprobe reports numbers as strings, e.g. "12.480000"
+fn parse_number(value: &Value) -> Option<f64> {
+    value.as_str()
+        .and_then(|text| text.parse::<f64>().ok())
+        .or_else(|| value.as_f64())
+        .filter(|number| number.is_finite() && *number > 0.0)
+}
+
+// Frame rates come as fractions such as "30000/1001"
+fn parse_frame_rate(value: &Value) -> Option<f64> {
+    let (numerator, denominator) = value.as_str()?.split_once('/')?;
+    let numerator: f64 = numerator.parse().ok()?;
+    let denominator: f64 = denominator.parse().ok()?;
+    if denominator == 0.0 || numerator == 0.0 {
+        return None;
+    }
+    Some(numerator / denominator)
+}
+
+// Walk the GIF block structure to count frames and add up their delays without decoding
+fn probe_gif(path: &Path) -> Option<VideoMetadata> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+
+    let mut header = [0u8; 13];
+    reader.read_exact(&mut header).ok()?;
+    let width = u16::from_le_bytes([header[6], header[7]]) as u32;
+    let height = u16::from_le_bytes([header[8], header[9]]) as u32;
+    skip_color_table(&mut reader, header[10])?;
+
+    let mut frame_count = 0u64;
+    let mut total_delay_cs = 0u64;
+    let mut pending_delay_cs = None;
+
+    loop {
+        let mut introducer = [0u8; 1];
+        if reader.read_exact(&mut introducer).is_err() {
+            break;
+        }
+
+        match introducer[0] {
+            // Extension block; the graphic control extension carries the frame delay
+            0x21 => {
+                let mut label = [0u8; 1];
+                reader.read_exact(&mut label).ok()?;
+                if label[0] == 0xF9 {
+                    let mut control = [0u8; 5];
+                    reader.read_exact(&mut control).ok()?;
+                    pending_delay_cs = Some(u16::from_le_bytes([control[2], control[3]]) as u64);
+                }
+                skip_sub_blocks(&mut reader)?;
+            }
+            // Image descriptor, followed by an optional local color table and the LZW data
+            0x2C => {
+                let mut descriptor = [0u8; 9];
+                reader.read_exact(&mut descriptor).ok()?;
+                skip_color_table(&mut reader, descriptor[8])?;
+                reader.seek_relative(1).ok()?;
+                skip_sub_blocks(&mut reader)?;
+
+                let delay = pending_delay_cs.take().unwrap_or(0);
+                total_delay_cs += if delay < GIF_MIN_DELAY_CS { GIF_DEFAULT_DELAY_CS } else { delay };
+                frame_count += 1;
+            }
+            // Trailer, or garbage after a truncated file
+            _ => break,
+        }
+    }
+
+    if frame_count == 0 {
+        return None;
+    }
+
+    let duration_secs = total_delay_cs as f64 / 100.0;
+    Some(VideoMetadata {
+        duration_secs: Some(duration_secs),
+        width: Some(width),
+        height: Some(height),
+        frame_rate: Some(frame_count as f64 / duration_secs),
+        codec: Some("gif".to_string()),
+        has_audio: false,
+        frame_count: Some(frame_count),
+    })
+}
+
+fn skip_color_table(reader: &mut BufReader<File>, packed: u8) -> Option<()> {
+    if packed & 0x80 != 0 {
+        let table_size = 3 * (1i64 << ((packed & 0x07) + 1));
+        reader.seek_relative(table_size).ok()?;
+    }
+    Some(())
+}
+
+fn skip_sub_blocks(reader: &mut BufReader<File>) -> Option<()> {
+    loop {
+        let mut size = [0u8; 1];
+        reader.read_exact(&mut size).ok()?;
+        if size[0] == 0 {
+            return Some(());
+        }
+        reader.seek_relative(size[0] as i64).ok()?;
+    }
+}
\ No newline at end of file